@@ -1,15 +1,30 @@
 use std::str::FromStr;
 
+use chrono::{DateTime, NaiveDate};
 use serde::{Deserialize, Serialize};
+use url::Url;
 
-use crate::Value;
+use crate::{Dimension, Value};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub use constraints::{ConstraintViolation, Constraints};
+pub use reference::{Reference, ReferenceTarget};
+
+mod constraints;
+mod reference;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Field {
     name: String,
     field_type: FieldType,
     required: bool,
+    // Note : Optional in files, TOML can't write a null
+    #[serde(default)]
     default: Value,
+    #[serde(default, skip_serializing_if = "Constraints::is_empty")]
+    constraints: Constraints,
+    /// Computed from the sibling fields rather than entered, e.g. `width * height * depth`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expression: Option<String>,
 }
 
 impl Field {
@@ -34,6 +49,38 @@ impl Field {
             field_type,
             required,
             default,
+            constraints: Constraints::default(),
+            expression: None,
+        }
+    }
+
+    pub fn with_constraints(mut self, constraints: Constraints) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    pub fn constraints(&self) -> &Constraints {
+        &self.constraints
+    }
+
+    pub fn with_expression(mut self, expression: &str) -> Self {
+        self.expression = Some(expression.to_string());
+        self
+    }
+
+    pub fn expression(&self) -> Option<&str> {
+        self.expression.as_deref()
+    }
+
+    pub fn is_computed(&self) -> bool {
+        self.expression.is_some()
+    }
+
+    /// The dimension a measurement field is expressed in, `None` for every other field type.
+    pub fn dimension(&self) -> Option<Dimension> {
+        match self.field_type {
+            FieldType::Measurement(dimension) => Some(dimension),
+            _ => None,
         }
     }
 
@@ -52,9 +99,13 @@ impl Field {
     pub fn set_default(&mut self, default: Value) {
         self.default = default;
     }
+
+    pub fn set_constraints(&mut self, constraints: Constraints) {
+        self.constraints = constraints;
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum FieldType {
     String,
@@ -62,17 +113,103 @@ pub enum FieldType {
     Boolean,
     Array,
     Object,
+    Measurement(Dimension),
+
+    // -- Stored as strings --
+    /// `YYYY-MM-DD`
+    Date,
+    /// RFC 3339, e.g. `2025-01-31T10:00:00+08:00`
+    DateTime,
+    /// One of the allowed values, e.g. `{"enum": ["new", "used"]}`
+    Enum(Vec<String>),
+    Url,
+    Email,
+
+    /// The id of an item, a location metadata or a location, e.g. `{"reference": "item"}`
+    Reference(ReferenceTarget),
 }
 
 impl FieldType {
     pub fn is_valid(&self, value: &Value) -> bool {
-        matches!(
-            (self, value),
+        match (self, value) {
             (FieldType::String, Value::String(_))
-                | (FieldType::Number, Value::Number(_))
-                | (FieldType::Boolean, Value::Boolean(_))
-                | (FieldType::Array, Value::Array(_))
-                | (FieldType::Object, Value::Object(_))
+            | (FieldType::Number, Value::Number(_))
+            | (FieldType::Boolean, Value::Boolean(_))
+            | (FieldType::Array, Value::Array(_))
+            | (FieldType::Object, Value::Object(_)) => true,
+            (FieldType::Measurement(dimension), value) => value
+                .as_measurement()
+                .is_some_and(|m| m.dimension() == *dimension),
+            (FieldType::Date, Value::String(s)) => NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok(),
+            (FieldType::DateTime, Value::String(s)) => DateTime::parse_from_rfc3339(s).is_ok(),
+            (FieldType::Enum(allowed), Value::String(s)) => allowed.contains(s),
+            (FieldType::Url, Value::String(s)) => Url::parse(s).is_ok(),
+            (FieldType::Email, Value::String(s)) => is_email(s),
+            // Note : Only the shape, whether the row exists needs the db
+            (FieldType::Reference(_), Value::Number(n)) => n.as_i64().is_some(),
+            _ => false,
+        }
+    }
+
+    /// Whether the values of this type are stored as [`Value::String`].
+    pub fn is_string_based(&self) -> bool {
+        matches!(
+            self,
+            FieldType::String
+                | FieldType::Date
+                | FieldType::DateTime
+                | FieldType::Enum(_)
+                | FieldType::Url
+                | FieldType::Email
         )
     }
 }
+
+// Note : Only checks the overall shape (`local@domain.tld`), not everything RFC 5322 allows.
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && !s.chars().any(char::is_whitespace)
+        && !domain.contains('@')
+        && domain.contains('.')
+        && domain.split('.').all(|label| !label.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FieldType, Value};
+    use serde_json::json;
+
+    #[test]
+    fn test_rich_field_types() {
+        let string = |s: &str| Value::String(s.to_string());
+
+        assert!(FieldType::Date.is_valid(&string("2025-02-28")));
+        assert!(!FieldType::Date.is_valid(&string("2025-02-30")));
+        assert!(!FieldType::Date.is_valid(&Value::Number(20250228.into())));
+
+        assert!(FieldType::DateTime.is_valid(&string("2025-02-28T10:30:00Z")));
+        assert!(FieldType::DateTime.is_valid(&string("2025-02-28T10:30:00+08:00")));
+        assert!(!FieldType::DateTime.is_valid(&string("2025-02-28")));
+
+        let status = FieldType::Enum(vec!["new".into(), "used".into()]);
+        assert!(status.is_valid(&string("used")));
+        assert!(!status.is_valid(&string("broken")));
+
+        assert!(FieldType::Url.is_valid(&string("https://example.com/item?id=1")));
+        assert!(!FieldType::Url.is_valid(&string("example.com")));
+
+        assert!(FieldType::Email.is_valid(&string("someone@example.com")));
+        assert!(!FieldType::Email.is_valid(&string("someone@localhost")));
+        assert!(!FieldType::Email.is_valid(&string("some one@example.com")));
+        assert!(!FieldType::Email.is_valid(&string("@example.com")));
+
+        assert_eq!(
+            serde_json::to_value([FieldType::DateTime, status, FieldType::Url]).unwrap(),
+            json!(["datetime", { "enum": ["new", "used"] }, "url"])
+        );
+    }
+}
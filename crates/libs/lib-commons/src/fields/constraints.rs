@@ -0,0 +1,270 @@
+use crate::{FieldType, Number, Value};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+pub use error::ConstraintViolation;
+
+/// Declarative restrictions on the value of a [`crate::Field`], on top of its [`FieldType`].
+///
+/// Every constraint only applies to the values it makes sense for : `min`/`max` to numbers and
+/// measurements (compared in their base unit), the lengths and `pattern` to strings and the
+/// items ones to arrays.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Constraints {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min: Option<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max: Option<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_items: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_items: Option<usize>,
+    /// The type every element of an array has to be
+    #[serde(skip_serializing_if = "Option::is_none")]
+    items: Option<FieldType>,
+}
+
+impl Constraints {
+    pub fn min(mut self, min: impl Into<Number>) -> Self {
+        self.min = Some(min.into());
+        self
+    }
+
+    pub fn max(mut self, max: impl Into<Number>) -> Self {
+        self.max = Some(max.into());
+        self
+    }
+
+    pub fn min_length(mut self, min_length: usize) -> Self {
+        self.min_length = Some(min_length);
+        self
+    }
+
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    pub fn min_items(mut self, min_items: usize) -> Self {
+        self.min_items = Some(min_items);
+        self
+    }
+
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    pub fn items(mut self, item_type: FieldType) -> Self {
+        self.items = Some(item_type);
+        self
+    }
+
+    pub fn item_type(&self) -> Option<&FieldType> {
+        self.items.as_ref()
+    }
+
+    pub fn get_pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Constraints::default()
+    }
+
+    /// The compiled `pattern`, meant to be built once per schema and given to
+    /// [`Constraints::check_with`].
+    pub fn compile_pattern(&self) -> Result<Option<Regex>, ConstraintViolation> {
+        self.pattern
+            .as_ref()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|_| ConstraintViolation::InvalidPattern {
+                    pattern: pattern.clone(),
+                })
+            })
+            .transpose()
+    }
+
+    /// Check `value` against every constraint, stopping at the first one that fails.
+    ///
+    /// Note : Compiles the pattern on every call, see [`Constraints::check_with`]
+    pub fn check(&self, value: &Value) -> Result<(), ConstraintViolation> {
+        self.check_with(value, self.compile_pattern()?.as_ref())
+    }
+
+    /// [`Constraints::check`] with the pattern already compiled, `None` if there is none.
+    pub fn check_with(
+        &self,
+        value: &Value,
+        pattern: Option<&Regex>,
+    ) -> Result<(), ConstraintViolation> {
+        let number = match value {
+            Value::Number(n) => Some(*n),
+            // Note : Bounds are in the base unit, what was read back may still be in another one
+            _ => value.as_measurement().map(|m| m.to_base().quantity()),
+        };
+
+        if let Some(number) = number {
+            if let Some(min) = self.min
+                && number < min
+            {
+                return Err(ConstraintViolation::Min { min, actual: number });
+            }
+
+            if let Some(max) = self.max
+                && number > max
+            {
+                return Err(ConstraintViolation::Max { max, actual: number });
+            }
+        }
+
+        if let Value::String(s) = value {
+            let length = s.chars().count();
+
+            if let Some(min_length) = self.min_length
+                && length < min_length
+            {
+                return Err(ConstraintViolation::MinLength { min_length, length });
+            }
+
+            if let Some(max_length) = self.max_length
+                && length > max_length
+            {
+                return Err(ConstraintViolation::MaxLength { max_length, length });
+            }
+
+            if let Some(regex) = pattern
+                && !regex.is_match(s)
+            {
+                return Err(ConstraintViolation::Pattern {
+                    pattern: regex.as_str().to_string(),
+                });
+            }
+        }
+
+        if let Value::Array(arr) = value {
+            if let Some(min_items) = self.min_items
+                && arr.len() < min_items
+            {
+                return Err(ConstraintViolation::MinItems {
+                    min_items,
+                    len: arr.len(),
+                });
+            }
+
+            if let Some(max_items) = self.max_items
+                && arr.len() > max_items
+            {
+                return Err(ConstraintViolation::MaxItems {
+                    max_items,
+                    len: arr.len(),
+                });
+            }
+
+            if let Some(item_type) = &self.items
+                && let Some(index) = arr.iter().position(|item| !item_type.is_valid(item))
+            {
+                return Err(ConstraintViolation::ItemType {
+                    index,
+                    expected: item_type.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod error {
+    use crate::{FieldType, Number};
+    use serde::Serialize;
+    use std::fmt;
+
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    #[serde(tag = "constraint", rename_all = "snake_case")]
+    pub enum ConstraintViolation {
+        Min { min: Number, actual: Number },
+        Max { max: Number, actual: Number },
+        MinLength { min_length: usize, length: usize },
+        MaxLength { max_length: usize, length: usize },
+        Pattern { pattern: String },
+        /// The pattern of the schema itself does not compile
+        InvalidPattern { pattern: String },
+        MinItems { min_items: usize, len: usize },
+        MaxItems { max_items: usize, len: usize },
+        ItemType { index: usize, expected: FieldType },
+    }
+
+    impl fmt::Display for ConstraintViolation {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for ConstraintViolation {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConstraintViolation, Constraints, FieldType, Measurement, Unit, Value};
+    use serde_json::json;
+
+    #[test]
+    fn test_constraints_check() {
+        let quantity = Constraints::default().min(0).max(100);
+        assert!(quantity.check(&Value::Number(10.into())).is_ok());
+        assert_eq!(
+            quantity.check(&Value::Number((-1).into())),
+            Err(ConstraintViolation::Min {
+                min: 0.into(),
+                actual: (-1).into()
+            })
+        );
+        // 1 m is 1000 mm once stored
+        assert!(
+            quantity
+                .check(&Measurement::new(1, Unit::M).into())
+                .is_err()
+        );
+
+        let code = Constraints::default().min_length(2).pattern("^[A-Z]+-[0-9]+$");
+        assert!(code.check(&Value::String("AB-12".into())).is_ok());
+        assert_eq!(
+            code.check(&Value::String("ab-12".into())),
+            Err(ConstraintViolation::Pattern {
+                pattern: "^[A-Z]+-[0-9]+$".into()
+            })
+        );
+
+        let tags = Constraints::default().max_items(2).items(FieldType::String);
+        let value: Value = json!(["a", 1]).try_into().unwrap();
+        assert_eq!(
+            tags.check(&value),
+            Err(ConstraintViolation::ItemType {
+                index: 1,
+                expected: FieldType::String
+            })
+        );
+        let value: Value = json!(["a", "b", "c"]).try_into().unwrap();
+        assert!(matches!(
+            tags.check(&value),
+            Err(ConstraintViolation::MaxItems { .. })
+        ));
+
+        let json = serde_json::to_value(&code).unwrap();
+        assert_eq!(json, json!({ "min_length": 2, "pattern": "^[A-Z]+-[0-9]+$" }));
+        assert_eq!(serde_json::from_value::<Constraints>(json).unwrap(), code);
+    }
+}
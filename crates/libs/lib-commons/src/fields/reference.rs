@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Path;
+
+/// The rows a [`crate::FieldType::Reference`] field holds the id of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceTarget {
+    Item,
+    LocationMetadata,
+    /// A rack/bin of a location, the `location_data` rows
+    Location,
+}
+
+/// A reference field set in some values, see `lib_schema::SchemaManager::references`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reference {
+    path: Path,
+    target: ReferenceTarget,
+    id: i64,
+}
+
+impl Reference {
+    pub fn new(path: Path, target: ReferenceTarget, id: i64) -> Self {
+        Reference { path, target, id }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn target(&self) -> ReferenceTarget {
+        self.target
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+}
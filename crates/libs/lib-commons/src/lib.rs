@@ -3,6 +3,23 @@ mod value;
 
 mod fields;
 
-pub use value_store::{ValueStore,ValueStoreError};
-pub use value::{Value,ext::CommonsValue};
-pub use fields::{Field,FieldType};
+mod measurement;
+
+mod migration;
+
+mod report;
+
+mod rule;
+
+pub use value_store::{ValueStore,ValueStoreError,ValueStorePatch,ValueStoreDiff,SchemaNameChange};
+pub use value::{Value, Number, NumberError, ext::CommonsValue};
+pub use value::path::{FromValue, IntoPath, Path, PathError, PathSegment};
+pub use value::patch::{JsonPatch, PatchError, PatchOperation};
+pub use value::diff::ValueChange;
+pub use rust_decimal::Decimal;
+pub use regex::Regex;
+pub use fields::{Field,FieldType,Constraints,ConstraintViolation,Reference,ReferenceTarget};
+pub use measurement::{Dimension, Measurement, MeasurementError, Unit};
+pub use migration::{Conversion, Migration, MigrationError, MigrationStep};
+pub use report::{ValidationReport, Violation, ViolationKind};
+pub use rule::Rule;
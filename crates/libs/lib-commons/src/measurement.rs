@@ -0,0 +1,299 @@
+use crate::{Number, Value};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub use error::MeasurementError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dimension {
+    Length,
+    Mass,
+    Volume,
+}
+
+impl Dimension {
+    /// The unit every measurement of this dimension is converted to before being stored.
+    pub fn base_unit(&self) -> Unit {
+        match self {
+            Dimension::Length => Unit::Mm,
+            Dimension::Mass => Unit::G,
+            Dimension::Volume => Unit::Ml,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    // -- Length --
+    Mm,
+    Cm,
+    M,
+    In,
+
+    // -- Mass --
+    G,
+    Kg,
+    Lb,
+
+    // -- Volume --
+    Ml,
+    L,
+}
+
+impl Unit {
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Unit::Mm | Unit::Cm | Unit::M | Unit::In => Dimension::Length,
+            Unit::G | Unit::Kg | Unit::Lb => Dimension::Mass,
+            Unit::Ml | Unit::L => Dimension::Volume,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Mm => "mm",
+            Unit::Cm => "cm",
+            Unit::M => "m",
+            Unit::In => "in",
+            Unit::G => "g",
+            Unit::Kg => "kg",
+            Unit::Lb => "lb",
+            Unit::Ml => "ml",
+            Unit::L => "l",
+        }
+    }
+
+    /// How many base units (see [`Dimension::base_unit`]) a single one of this unit is.
+    fn factor(&self) -> Decimal {
+        match self {
+            Unit::Mm | Unit::G | Unit::Ml => Decimal::ONE,
+            Unit::Cm => Decimal::TEN,
+            Unit::M | Unit::Kg | Unit::L => Decimal::ONE_THOUSAND,
+            // Both are exact by definition
+            Unit::In => Decimal::new(254, 1),
+            Unit::Lb => Decimal::new(45359237, 5),
+        }
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for Unit {
+    type Err = MeasurementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mm" => Ok(Unit::Mm),
+            "cm" => Ok(Unit::Cm),
+            "m" => Ok(Unit::M),
+            "in" => Ok(Unit::In),
+            "g" => Ok(Unit::G),
+            "kg" => Ok(Unit::Kg),
+            "lb" => Ok(Unit::Lb),
+            "ml" => Ok(Unit::Ml),
+            "l" => Ok(Unit::L),
+            _ => Err(MeasurementError::UnknownUnit(s.to_string())),
+        }
+    }
+}
+
+/// A quantity together with the unit it is expressed in.
+///
+/// Stored inside a [`Value`] as `{"quantity": .., "unit": ..}`, always converted to the
+/// base unit of its dimension first (see [`Measurement::to_base`]). Read back, it stays an
+/// object until the schema of its field says it is a measurement.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Measurement {
+    quantity: Number,
+    unit: Unit,
+}
+
+impl Measurement {
+    pub fn new(quantity: impl Into<Number>, unit: Unit) -> Self {
+        Measurement {
+            quantity: quantity.into(),
+            unit,
+        }
+    }
+
+    pub fn quantity(&self) -> Number {
+        self.quantity
+    }
+
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.unit.dimension()
+    }
+
+    pub fn convert_to(&self, unit: Unit) -> Result<Measurement, MeasurementError> {
+        if self.unit == unit {
+            return Ok(*self);
+        }
+
+        if self.dimension() != unit.dimension() {
+            return Err(MeasurementError::IncompatibleUnit {
+                from: self.unit,
+                to: unit,
+            });
+        }
+
+        let base = self.quantity.as_decimal() * self.unit.factor();
+        let quantity = (base / unit.factor()).normalize();
+
+        Ok(Measurement {
+            quantity: Number::from(quantity).normalize(),
+            unit,
+        })
+    }
+
+    pub fn to_base(&self) -> Measurement {
+        // A unit is always compatible with the base unit of its own dimension
+        self.convert_to(self.dimension().base_unit())
+            .unwrap_or(*self)
+    }
+
+    pub(crate) fn from_object(map: &BTreeMap<String, Value>) -> Option<Measurement> {
+        if map.len() != 2 {
+            return None;
+        }
+
+        let quantity = map.get("quantity")?.as_number()?;
+        let unit = map.get("unit")?.as_string()?.parse().ok()?;
+
+        Some(Measurement::new(quantity, unit))
+    }
+}
+
+impl Display for Measurement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.quantity, self.unit)
+    }
+}
+
+impl FromStr for Measurement {
+    type Err = MeasurementError;
+
+    /// Parse inputs such as `"12.5 cm"` or `"3kg"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| c.is_alphabetic())
+            .ok_or_else(|| MeasurementError::InvalidFormat(s.to_string()))?;
+
+        let (quantity, unit) = s.split_at(split);
+        let quantity = Number::parse(quantity.trim())
+            .ok_or_else(|| MeasurementError::InvalidFormat(s.to_string()))?;
+
+        Ok(Measurement::new(quantity, unit.parse()?))
+    }
+}
+
+impl From<Measurement> for Value {
+    fn from(value: Measurement) -> Self {
+        Value::Measurement(value.to_base())
+    }
+}
+
+mod error {
+    use crate::Unit;
+    use std::fmt;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum MeasurementError {
+        UnknownUnit(String),
+        InvalidFormat(String),
+        IncompatibleUnit { from: Unit, to: Unit },
+    }
+
+    impl fmt::Display for MeasurementError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for MeasurementError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Dimension, Measurement, MeasurementError, Number, Unit, Value, ValueStore, get};
+
+    #[test]
+    fn test_measurement_conversion() {
+        let length = Measurement::new(2, Unit::In).to_base();
+        assert_eq!(length.unit(), Unit::Mm);
+        assert_eq!(length.quantity().to_string(), "50.8");
+
+        let mass = Measurement::new(1500, Unit::G)
+            .convert_to(Unit::Kg)
+            .unwrap();
+        assert_eq!(mass.quantity().to_string(), "1.5");
+
+        let volume = Measurement::new(2, Unit::L).to_base();
+        assert!(matches!(volume.quantity(), Number::PosInt(2000)));
+
+        let err = Measurement::new(1, Unit::Kg)
+            .convert_to(Unit::M)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            MeasurementError::IncompatibleUnit {
+                from: Unit::Kg,
+                to: Unit::M
+            }
+        );
+    }
+
+    #[test]
+    fn test_measurement_parse() {
+        let m: Measurement = "12.5 cm".parse().unwrap();
+        assert_eq!(m.unit(), Unit::Cm);
+        assert_eq!(m.dimension(), Dimension::Length);
+
+        assert!("12 furlong".parse::<Measurement>().is_err());
+        assert!("cm".parse::<Measurement>().is_err());
+    }
+
+    #[test]
+    fn test_measurement_stored_in_base_unit() {
+        let store = ValueStore::builder()
+            .measurement("width", "1.5 m".parse::<Measurement>().unwrap())
+            .build();
+
+        let json = store.to_string();
+        assert!(json.contains(r#""width":{"quantity":1500,"unit":"mm"}"#));
+
+        // Read back as written, only a schema turns it into a measurement
+        let raw = r#"{"schema_name":null,"object_properties_schemas":{},"values":{"weight":{"quantity":2,"unit":"kg"}}}"#;
+        let store: ValueStore = serde_json::from_str(raw).unwrap();
+        assert!(store.get("weight").unwrap().is_object());
+        let weight = get!(store, measurement, weight).unwrap();
+        assert_eq!(weight.to_base(), Measurement::new(2000, Unit::G));
+        assert_eq!(store.to_string(), raw);
+
+        let value: Value = serde_json::json!({ "dims": { "quantity": 5, "unit": "m" } })
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"dims":{"quantity":5,"unit":"m"}}"#
+        );
+        assert_eq!(
+            value.get_path("dims").unwrap().as_measurement(),
+            Some(Measurement::new(5, Unit::M))
+        );
+    }
+}
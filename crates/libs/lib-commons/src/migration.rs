@@ -0,0 +1,362 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Field, FieldType, Measurement, Number, Value, ValueStore};
+
+pub use error::MigrationError;
+
+pub type Result<T> = core::result::Result<T, MigrationError>;
+
+/// The steps taking the values written against the previous version of a schema to `version`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Migration {
+    version: u32,
+    steps: Vec<MigrationStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum MigrationStep {
+    Rename {
+        from: String,
+        to: String,
+    },
+    Retype {
+        field: String,
+        field_type: FieldType,
+        #[serde(default)]
+        conversion: Conversion,
+    },
+    Drop {
+        field: String,
+    },
+    /// The values written before get the default of the field, if it has one
+    Add {
+        field: Field,
+    },
+}
+
+/// How the values of a retyped field are brought to its new type.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Conversion {
+    /// Converted when there is an obvious way to, e.g. `"12"` to `12`, anything else is an error
+    #[default]
+    Cast,
+    /// The old values are set to null
+    Discard,
+    /// The old values are all replaced by this one
+    Replace(Value),
+}
+
+impl MigrationStep {
+    pub fn rename(from: &str, to: &str) -> Self {
+        MigrationStep::Rename {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    pub fn retype(field: &str, field_type: FieldType) -> Self {
+        MigrationStep::retype_with(field, field_type, Conversion::Cast)
+    }
+
+    pub fn retype_with(field: &str, field_type: FieldType, conversion: Conversion) -> Self {
+        MigrationStep::Retype {
+            field: field.to_string(),
+            field_type,
+            conversion,
+        }
+    }
+
+    pub fn drop(field: &str) -> Self {
+        MigrationStep::Drop {
+            field: field.to_string(),
+        }
+    }
+
+    pub fn add(field: Field) -> Self {
+        MigrationStep::Add { field }
+    }
+
+    fn apply_to_fields(&self, fields: &mut Vec<Field>) -> Result<()> {
+        let position = |fields: &Vec<Field>, name: &str| {
+            fields
+                .iter()
+                .position(|field| field.name() == name)
+                .ok_or_else(|| MigrationError::UnknownField(name.to_string()))
+        };
+        let ensure_free = |fields: &Vec<Field>, name: &str| {
+            if fields.iter().any(|field| field.name() == name) {
+                return Err(MigrationError::DuplicateField(name.to_string()));
+            }
+            Ok(())
+        };
+
+        match self {
+            MigrationStep::Rename { from, to } => {
+                let index = position(fields, from)?;
+                ensure_free(fields, to)?;
+                fields[index].set_name(to);
+            }
+            MigrationStep::Retype {
+                field, field_type, ..
+            } => {
+                let index = position(fields, field)?;
+                fields[index].set_field_type(field_type.clone());
+            }
+            MigrationStep::Drop { field } => {
+                let index = position(fields, field)?;
+                fields.remove(index);
+            }
+            MigrationStep::Add { field } => {
+                ensure_free(fields, field.name())?;
+                fields.push(field.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply(&self, store: &mut ValueStore) -> Result<()> {
+        match self {
+            MigrationStep::Rename { from, to } => {
+                if let Some(value) = store.remove(from) {
+                    store.insert(to.clone(), value);
+                }
+                // Note : Or the nested object would no longer be checked against its schema
+                store.rename_property_schema(from, Some(to));
+            }
+            MigrationStep::Retype {
+                field,
+                field_type,
+                conversion,
+            } => {
+                let Some(value) = store.remove(field) else {
+                    return Ok(());
+                };
+
+                let converted = match conversion {
+                    Conversion::Cast => {
+                        cast(&value, field_type).ok_or_else(|| MigrationError::Conversion {
+                            field: field.clone(),
+                            value,
+                            field_type: field_type.clone(),
+                        })?
+                    }
+                    Conversion::Discard => Value::Null,
+                    Conversion::Replace(replacement) => replacement.clone(),
+                };
+
+                store.insert(field.clone(), converted);
+            }
+            MigrationStep::Drop { field } => {
+                store.remove(field);
+                store.rename_property_schema(field, None);
+            }
+            MigrationStep::Add { field } => {
+                if store.get(field.name()).is_none() && !field.default_value().is_null() {
+                    store.insert(field.name().to_string(), field.default_value().clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn cast(value: &Value, field_type: &FieldType) -> Option<Value> {
+    if value.is_null() || field_type.is_valid(value) {
+        return Some(value.clone());
+    }
+
+    let cast = match (field_type, value) {
+        (field_type, Value::Number(n)) if field_type.is_string_based() => {
+            Value::String(n.to_string())
+        }
+        (field_type, Value::Boolean(b)) if field_type.is_string_based() => {
+            Value::String(b.to_string())
+        }
+        (FieldType::Number, Value::String(s)) => Value::Number(Number::parse(s.trim())?),
+        (FieldType::Number, Value::Measurement(_) | Value::Object(_)) => {
+            Value::Number(value.as_measurement()?.to_base().quantity())
+        }
+        (FieldType::Boolean, Value::String(s)) => Value::Boolean(s.trim().parse().ok()?),
+        // Note : A bare number is taken as being in the base unit
+        (FieldType::Measurement(dimension), Value::Number(n)) => {
+            Value::Measurement(Measurement::new(*n, dimension.base_unit()))
+        }
+        (FieldType::Array, value) => Value::Array(vec![value.clone()]),
+        _ => return None,
+    };
+
+    field_type.is_valid(&cast).then_some(cast)
+}
+
+impl Migration {
+    pub fn new(version: u32, steps: Vec<MigrationStep>) -> Self {
+        Migration { version, steps }
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn steps(&self) -> &[MigrationStep] {
+        &self.steps
+    }
+
+    /// Apply the steps on the fields of the previous version, nothing is changed if one fails.
+    pub fn apply_to_fields(&self, fields: &mut Vec<Field>) -> Result<()> {
+        let mut migrated = fields.clone();
+        for step in &self.steps {
+            step.apply_to_fields(&mut migrated)?;
+        }
+
+        *fields = migrated;
+        Ok(())
+    }
+
+    /// Apply the steps on values written against the previous version, and pin `store` to
+    /// this one. Nothing is changed if one of the steps fails.
+    pub fn apply(&self, store: &mut ValueStore) -> Result<()> {
+        let mut migrated = store.clone();
+        for step in &self.steps {
+            step.apply(&mut migrated)?;
+        }
+        migrated.set_schema_version(self.version);
+
+        *store = migrated;
+        Ok(())
+    }
+}
+
+mod error {
+    use crate::{FieldType, Value};
+    use std::fmt;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum MigrationError {
+        UnknownField(String),
+        DuplicateField(String),
+        /// The value can't be cast to the new type of its field
+        Conversion {
+            field: String,
+            value: Value,
+            field_type: FieldType,
+        },
+        /// The values were written against a version the schema does not have (yet)
+        UnknownVersion {
+            schema: String,
+            version: u32,
+        },
+    }
+
+    impl fmt::Display for MigrationError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for MigrationError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dimension, Unit};
+
+    #[test]
+    fn test_migration_apply() {
+        let migration = Migration::new(
+            2,
+            vec![
+                MigrationStep::rename("qty", "quantity"),
+                MigrationStep::retype("quantity", FieldType::Number),
+                MigrationStep::retype("width", FieldType::Measurement(Dimension::Length)),
+                MigrationStep::drop("legacy"),
+                MigrationStep::rename("lid", "cover"),
+                MigrationStep::drop("tags"),
+                MigrationStep::add(Field::create(
+                    "condition",
+                    FieldType::String,
+                    true,
+                    Value::String("new".to_string()),
+                )),
+            ],
+        );
+
+        let mut store = ValueStore::builder()
+            .with_schema("Box")
+            .string("qty", " 12 ")
+            .number("width", 3)
+            .bool("legacy", true)
+            .object("lid", [("open", Value::Boolean(false))], Some("Lid"))
+            .array("tags", vec![], Some("Tag"))
+            .build();
+
+        migration.apply(&mut store).unwrap();
+
+        // The schemas of the nested objects follow them
+        let properties = store.object_properties_schemas();
+        assert_eq!(properties.get("cover").map(String::as_str), Some("Lid"));
+        assert!(!properties.contains_key("lid") && !properties.contains_key("tags"));
+
+        assert_eq!(store.schema_version(), Some(2));
+        assert!(store.get("qty").is_none() && store.get("legacy").is_none());
+        assert_eq!(store.get("quantity"), Some(&Value::Number(12.into())));
+        assert_eq!(
+            store.get("width"),
+            Some(&Value::Measurement(Measurement::new(3, Unit::Mm)))
+        );
+        assert_eq!(
+            store.get("condition"),
+            Some(&Value::String("new".to_string()))
+        );
+
+        // Nothing is applied when a value can't be converted
+        let mut store = ValueStore::builder().string("qty", "a dozen").build();
+        assert!(matches!(
+            migration.apply(&mut store),
+            Err(MigrationError::Conversion { .. })
+        ));
+        assert_eq!(
+            store.get("qty"),
+            Some(&Value::String("a dozen".to_string()))
+        );
+        assert_eq!(store.schema_version(), None);
+    }
+
+    #[test]
+    fn test_migration_apply_to_fields() {
+        let mut fields = vec![
+            Field::create("qty", FieldType::String, true, Value::Null),
+            Field::create("legacy", FieldType::Boolean, false, Value::Null),
+        ];
+
+        let migration = Migration::new(
+            2,
+            vec![
+                MigrationStep::rename("qty", "quantity"),
+                MigrationStep::retype("quantity", FieldType::Number),
+                MigrationStep::drop("legacy"),
+            ],
+        );
+        migration.apply_to_fields(&mut fields).unwrap();
+
+        assert_eq!(
+            fields,
+            vec![Field::create(
+                "quantity",
+                FieldType::Number,
+                true,
+                Value::Null
+            )]
+        );
+
+        let broken = Migration::new(3, vec![MigrationStep::drop("missing")]);
+        assert_eq!(
+            broken.apply_to_fields(&mut fields),
+            Err(MigrationError::UnknownField("missing".to_string()))
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{ConstraintViolation, FieldType, Path, ReferenceTarget, Rule, Value};
+use serde::Serialize;
+
+/// Everything wrong with a [`crate::ValueStore`], see `lib_schema::SchemaManager::validate`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    violations: Vec<Violation>,
+    /// Don't make the value invalid, e.g. extra fields with `AdditionalProperties::Warn`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<Violation>,
+}
+
+impl ValidationReport {
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    pub fn warnings(&self) -> &[Violation] {
+        &self.warnings
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// The violations found at `path`, e.g. to highlight a single input.
+    pub fn at<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a Violation> {
+        self.violations.iter().filter(move |v| &v.path == path)
+    }
+
+    pub fn push(&mut self, violation: Violation) {
+        self.violations.push(violation);
+    }
+
+    pub fn warn(&mut self, warning: Violation) {
+        self.warnings.push(warning);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// JSON pointer into the values of the store, `/other/a` for a nested object
+    path: Path,
+    #[serde(flatten)]
+    kind: ViolationKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected: Option<FieldType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actual: Option<Value>,
+}
+
+impl Violation {
+    pub fn new(path: Path, kind: ViolationKind) -> Self {
+        Violation {
+            path,
+            kind,
+            expected: None,
+            actual: None,
+        }
+    }
+
+    pub fn expected(mut self, expected: &FieldType) -> Self {
+        self.expected = Some(expected.clone());
+        self
+    }
+
+    pub fn actual(mut self, actual: &Value) -> Self {
+        self.actual = Some(actual.clone());
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn kind(&self) -> &ViolationKind {
+        &self.kind
+    }
+
+    pub fn expected_type(&self) -> Option<&FieldType> {
+        self.expected.as_ref()
+    }
+
+    pub fn actual_value(&self) -> Option<&Value> {
+        self.actual.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ViolationKind {
+    MissingField,
+    RequiredFieldMissing,
+    InvalidType,
+    /// Right type, but the string is not a valid date, url, enum value, ...
+    InvalidFormat,
+    IncompatibleUnit,
+    /// Not a field of the schema, see `lib_schema::AdditionalProperties`
+    AdditionalProperty,
+    ConstraintViolation(ConstraintViolation),
+    /// The rule broken, at the path of the field it requires or of the object for the others
+    RuleViolation(Rule),
+    /// The computed field can't be computed from its siblings, see `lib_schema::Expression`
+    ExpressionError {
+        expression: String,
+        error: String,
+    },
+    /// Nothing with that id in the data db, only checked when the metadata gets written
+    DanglingReference {
+        target: ReferenceTarget,
+        id: i64,
+    },
+    SchemaNotFound {
+        schema: String,
+    },
+    SchemaIdentifierMissing,
+}
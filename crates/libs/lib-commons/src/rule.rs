@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// A check between the fields of a schema, on top of the ones of each field.
+///
+/// A field counts as set when it is present and not null.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum Rule {
+    /// The fields of `then` are required when the condition `when` is true, e.g.
+    /// `battery_type` when `powered == true`
+    ///
+    /// Note : `when` is written in the language of the computed fields, see
+    ///        `lib_schema::Expression`
+    RequiredIf { when: String, then: Vec<String> },
+    /// At most one of the fields is set
+    MutuallyExclusive { fields: Vec<String> },
+    /// At least one of the fields is set
+    AtLeastOneOf { fields: Vec<String> },
+}
+
+impl Rule {
+    pub fn required_if(when: &str, then: &[&str]) -> Self {
+        Rule::RequiredIf {
+            when: when.to_string(),
+            then: then.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    pub fn mutually_exclusive(fields: &[&str]) -> Self {
+        Rule::MutuallyExclusive {
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    pub fn at_least_one_of(fields: &[&str]) -> Self {
+        Rule::AtLeastOneOf {
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+}
@@ -1,23 +1,38 @@
 pub use builder::{Any, Arr, Builder, Obj};
+pub use number::{Number, NumberError};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use crate::value::ext::CommonsValue;
+use crate::Measurement;
 
 pub mod builder;
 
 pub mod ext;
 
+pub mod number;
+
+pub mod path;
+
+pub mod patch;
+
+pub mod diff;
+
 /// This is a custom implementation of the 'Value' type apart from the 'serde_json' crate.
 /// As I want to create custom API for this type.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Value {
     String(String),
-    // Note : Every measurement will be using millimeters(mm) as the base unit
-    Number(u64),
+    Number(Number),
+    // Note : Written as `{"quantity": .., "unit": ..}` but never read back as such, an object of
+    //        that shape is only a measurement where a schema field says so, see
+    //        `Value::as_measurement`. Always held in the base unit of its dimension (mm, g, ml).
+    #[serde(skip_deserializing)]
+    Measurement(Measurement),
     Object(BTreeMap<String, Value>),
     Array(Vec<Value>),
     Boolean(bool),
+    #[default]
     Null,
 }
 
@@ -30,7 +45,7 @@ impl Value {
         }
     }
 
-    pub fn as_number(&self) -> Option<u64> {
+    pub fn as_number(&self) -> Option<Number> {
         if let Value::Number(n) = self {
             Some(*n)
         } else {
@@ -38,6 +53,15 @@ impl Value {
         }
     }
 
+    /// A measurement, or an object shaped as one (e.g. read back from storage), as written.
+    pub fn as_measurement(&self) -> Option<Measurement> {
+        match self {
+            Value::Measurement(m) => Some(*m),
+            Value::Object(map) => Measurement::from_object(map),
+            _ => None,
+        }
+    }
+
     pub fn as_object(&self) -> Option<&BTreeMap<String, Value>> {
         if let Value::Object(m) = self {
             Some(m)
@@ -74,6 +98,7 @@ impl Value {
         match self {
             Value::String(v) => v.clone(),
             Value::Number(n) => n.to_string(),
+            Value::Measurement(m) => m.to_string(),
             Value::Null => "null".to_string(),
             Value::Boolean(b) => b.to_string(),
             Value::Array(a) => serde_json::to_string_pretty(a).unwrap(),
@@ -82,14 +107,16 @@ impl Value {
     }
 }
 
-impl From<serde_json::Value> for Value {
-    fn from(value: serde_json::Value) -> Self {
+impl TryFrom<serde_json::Value> for Value {
+    type Error = NumberError;
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
         value.as_commons_value()
     }
 }
 
-impl From<&serde_json::Value> for Value {
-    fn from(value: &serde_json::Value) -> Self {
-        value.clone().into()
+impl TryFrom<&serde_json::Value> for Value {
+    type Error = NumberError;
+    fn try_from(value: &serde_json::Value) -> Result<Self, Self::Error> {
+        value.as_commons_value()
     }
 }
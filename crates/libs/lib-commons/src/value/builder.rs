@@ -1,4 +1,4 @@
-use crate::Value;
+use crate::{Measurement, Number, Value};
 use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
 
@@ -16,7 +16,7 @@ enum BuilderValue {
     #[default]
     Unset,
     String(String),
-    Number(u64),
+    Number(Number),
     Array(Vec<Value>),
     Object(BTreeMap<String, Value>),
     Null,
@@ -67,8 +67,8 @@ impl Builder<Any> {
         self
     }
 
-    pub fn number(mut self, value: u64) -> Self {
-        self.value = BuilderValue::Number(value);
+    pub fn number(mut self, value: impl Into<Number>) -> Self {
+        self.value = BuilderValue::Number(value.into());
         self
     }
 
@@ -79,9 +79,17 @@ impl Builder<Any> {
 }
 
 impl Builder<Arr> {
-    pub fn push_number(mut self, value: u64) -> Self {
+    pub fn push_number(mut self, value: impl Into<Number>) -> Self {
         if let BuilderValue::Array(ref mut arr) = self.value {
-            arr.push(Value::Number(value));
+            arr.push(Value::Number(value.into()));
+        }
+
+        self
+    }
+
+    pub fn push_measurement(mut self, value: Measurement) -> Self {
+        if let BuilderValue::Array(ref mut arr) = self.value {
+            arr.push(value.into());
         }
 
         self
@@ -144,9 +152,17 @@ impl From<Builder<Arr>> for Vec<Value> {
 }
 
 impl Builder<Obj> {
-    pub fn push_number(mut self, key: &str, value: u64) -> Self {
+    pub fn push_number(mut self, key: &str, value: impl Into<Number>) -> Self {
+        if let BuilderValue::Object(ref mut arr) = self.value {
+            arr.entry(key.to_string()).or_insert(Value::Number(value.into()));
+        }
+
+        self
+    }
+
+    pub fn push_measurement(mut self, key: &str, value: Measurement) -> Self {
         if let BuilderValue::Object(ref mut arr) = self.value {
-            arr.entry(key.to_string()).or_insert(Value::Number(value));
+            arr.entry(key.to_string()).or_insert(value.into());
         }
 
         self
@@ -0,0 +1,138 @@
+use crate::Value;
+use crate::value::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A single difference between two [`Value`] trees, see [`Value::diff`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ValueChange {
+    Added { path: Path, value: Value },
+    Removed { path: Path, value: Value },
+    Changed { path: Path, old: Value, new: Value },
+}
+
+impl ValueChange {
+    pub fn path(&self) -> &Path {
+        match self {
+            ValueChange::Added { path, .. }
+            | ValueChange::Removed { path, .. }
+            | ValueChange::Changed { path, .. } => path,
+        }
+    }
+}
+
+impl Value {
+    /// List what has to change to go from `self` to `other`.
+    ///
+    /// Objects and arrays are walked recursively (arrays index by index), anything else is
+    /// reported as a single [`ValueChange::Changed`] at its path.
+    pub fn diff(&self, other: &Value) -> Vec<ValueChange> {
+        let mut changes = Vec::new();
+        diff_value(Path::root(), self, other, &mut changes);
+        changes
+    }
+}
+
+pub(crate) fn diff_value(path: Path, old: &Value, new: &Value, changes: &mut Vec<ValueChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => diff_map(path, old, new, changes),
+        (Value::Array(old), Value::Array(new)) => {
+            for (index, (old, new)) in old.iter().zip(new.iter()).enumerate() {
+                diff_value(path.clone().index(index), old, new, changes);
+            }
+
+            // Reported from the end so that applying them in order keeps the indexes valid
+            for index in (new.len()..old.len()).rev() {
+                changes.push(ValueChange::Removed {
+                    path: path.clone().index(index),
+                    value: old[index].clone(),
+                });
+            }
+
+            for (index, value) in new.iter().enumerate().skip(old.len()) {
+                changes.push(ValueChange::Added {
+                    path: path.clone().index(index),
+                    value: value.clone(),
+                });
+            }
+        }
+        (old, new) if old != new => changes.push(ValueChange::Changed {
+            path,
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+pub(crate) fn diff_map(
+    path: Path,
+    old: &BTreeMap<String, Value>,
+    new: &BTreeMap<String, Value>,
+    changes: &mut Vec<ValueChange>,
+) {
+    for (key, old_value) in old {
+        match new.get(key) {
+            Some(new_value) => diff_value(path.clone().key(key), old_value, new_value, changes),
+            None => changes.push(ValueChange::Removed {
+                path: path.clone().key(key),
+                value: old_value.clone(),
+            }),
+        }
+    }
+
+    for (key, new_value) in new {
+        if !old.contains_key(key) {
+            changes.push(ValueChange::Added {
+                path: path.clone().key(key),
+                value: new_value.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Value, ValueChange};
+    use serde_json::json;
+
+    #[test]
+    fn test_value_diff() {
+        let old: Value = json!({
+            "name": "Rack",
+            "size": 3,
+            "bins": ["a", "b", "c"],
+            "meta": { "color": "red" }
+        })
+        .try_into()
+        .unwrap();
+
+        let new: Value = json!({
+            "name": "Rack",
+            "size": 4,
+            "bins": ["a", "x"],
+            "meta": { "color": "red", "shiny": true }
+        })
+        .try_into()
+        .unwrap();
+
+        let changes = old.diff(&new);
+
+        assert_eq!(
+            serde_json::to_value(&changes).unwrap(),
+            json!([
+                { "kind": "changed", "path": "/bins/1", "old": "b", "new": "x" },
+                { "kind": "removed", "path": "/bins/2", "value": "c" },
+                { "kind": "added", "path": "/meta/shiny", "value": true },
+                { "kind": "changed", "path": "/size", "old": 3, "new": 4 }
+            ])
+        );
+
+        assert!(old.diff(&old).is_empty());
+
+        let roundtrip: Vec<ValueChange> =
+            serde_json::from_value(serde_json::to_value(&changes).unwrap()).unwrap();
+        assert_eq!(roundtrip, changes);
+    }
+}
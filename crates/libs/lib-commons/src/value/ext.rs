@@ -1,37 +1,40 @@
-use crate::Value;
+use crate::{Number, NumberError, Value};
 use std::collections::{BTreeMap, HashMap};
 
 pub trait CommonsValue {
-    fn as_commons_value(&self) -> Value;
-    fn as_hash_map(&self) -> HashMap<String, Value>;
+    fn as_commons_value(&self) -> Result<Value, NumberError>;
+    fn as_hash_map(&self) -> Result<HashMap<String, Value>, NumberError>;
 }
 
 impl CommonsValue for serde_json::Value {
-    fn as_commons_value(&self) -> Value {
-        match self.clone() {
+    // Note : Fails on numbers a `Decimal` cannot hold (e.g. 1e300)
+    fn as_commons_value(&self) -> Result<Value, NumberError> {
+        Ok(match self.clone() {
             serde_json::Value::String(s) => Value::String(s),
-            serde_json::Value::Number(n) => Value::Number(n.as_u64().unwrap()),
+            serde_json::Value::Number(n) => Value::Number(Number::try_from(&n)?),
             serde_json::Value::Object(o) => {
                 let mut map = BTreeMap::new();
                 for (k, v) in o {
-                    map.insert(k, Value::from(v));
+                    map.insert(k, Value::try_from(v)?);
                 }
                 Value::Object(map)
             }
             serde_json::Value::Array(a) => {
-                let vec: Vec<Value> = a.into_iter().map(Value::from).collect();
+                let vec = a.into_iter().map(Value::try_from).collect::<Result<_, _>>()?;
                 Value::Array(vec)
             }
             serde_json::Value::Bool(b) => Value::Boolean(b),
             serde_json::Value::Null => Value::Null,
-        }
+        })
     }
 
-    fn as_hash_map(&self) -> HashMap<String, Value> {
+    fn as_hash_map(&self) -> Result<HashMap<String, Value>, NumberError> {
         if let serde_json::Value::Object(map) = self.clone() {
-            map.into_iter().map(|(k, v)| (k, v.as_commons_value())).collect()
+            map.into_iter()
+                .map(|(k, v)| Ok((k, v.as_commons_value()?)))
+                .collect()
         } else {
-            HashMap::default()
+            Ok(HashMap::default())
         }
     }
 }
@@ -0,0 +1,328 @@
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Numeric value held by [`crate::Value::Number`].
+///
+/// Integers are kept as integers so they round-trip untouched, everything else is
+/// stored as an exact [`Decimal`] (so a price of `4.99` stays `4.99`).
+///
+/// Constructing through the `From` implementations keeps the representation canonical :
+/// non-negative integers always end up in [`Number::PosInt`], negative ones in [`Number::NegInt`].
+#[derive(Debug, Clone, Copy)]
+pub enum Number {
+    PosInt(u64),
+    NegInt(i64),
+    Decimal(Decimal),
+}
+
+impl Number {
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Number::PosInt(n) => Some(*n),
+            Number::NegInt(_) => None,
+            Number::Decimal(d) if d.fract().is_zero() => d.to_u64(),
+            Number::Decimal(_) => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Number::PosInt(n) => i64::try_from(*n).ok(),
+            Number::NegInt(n) => Some(*n),
+            Number::Decimal(d) if d.fract().is_zero() => d.to_i64(),
+            Number::Decimal(_) => None,
+        }
+    }
+
+    /// Every variant fits in a [`Decimal`], so this never loses precision.
+    pub fn as_decimal(&self) -> Decimal {
+        match self {
+            Number::PosInt(n) => Decimal::from(*n),
+            Number::NegInt(n) => Decimal::from(*n),
+            Number::Decimal(d) => *d,
+        }
+    }
+
+    /// Lossy, only meant for display or interop with float based APIs.
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            Number::PosInt(n) => *n as f64,
+            Number::NegInt(n) => *n as f64,
+            Number::Decimal(d) => d.to_f64().unwrap_or(f64::NAN),
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        match self {
+            Number::PosInt(_) | Number::NegInt(_) => true,
+            Number::Decimal(d) => d.fract().is_zero(),
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Number::PosInt(_) => false,
+            Number::NegInt(_) => true,
+            Number::Decimal(d) => d.is_sign_negative() && !d.is_zero(),
+        }
+    }
+
+    /// Collapse a decimal without fractional part back into an integer when it fits.
+    pub fn normalize(self) -> Self {
+        match self {
+            Number::Decimal(d) if d.fract().is_zero() => {
+                if let Some(n) = d.to_u64() {
+                    Number::PosInt(n)
+                } else if let Some(n) = d.to_i64() {
+                    Number::NegInt(n)
+                } else {
+                    self
+                }
+            }
+            _ => self,
+        }
+    }
+
+    /// Parse the textual representation of a number, e.g. `"-0.2"` or `"1e3"`.
+    pub fn parse(s: &str) -> Option<Self> {
+        if let Ok(n) = s.parse::<u64>() {
+            return Some(Number::PosInt(n));
+        }
+        if let Ok(n) = s.parse::<i64>() {
+            return Some(n.into());
+        }
+        Decimal::from_str(s)
+            .or_else(|_| Decimal::from_scientific(s))
+            .ok()
+            .map(Number::Decimal)
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Number {}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Number::PosInt(a), Number::PosInt(b)) => a.cmp(b),
+            (Number::NegInt(a), Number::NegInt(b)) => a.cmp(b),
+            _ => self.as_decimal().cmp(&other.as_decimal()),
+        }
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::PosInt(n) => Display::fmt(n, f),
+            Number::NegInt(n) => Display::fmt(n, f),
+            Number::Decimal(d) => Display::fmt(&d.normalize(), f),
+        }
+    }
+}
+
+macro_rules! impl_from_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(value: $ty) -> Self {
+                    Number::PosInt(value as u64)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_from_signed {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(value: $ty) -> Self {
+                    if value < 0 {
+                        Number::NegInt(value as i64)
+                    } else {
+                        Number::PosInt(value as u64)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_unsigned!(u8, u16, u32, u64, usize);
+impl_from_signed!(i8, i16, i32, i64, isize);
+
+impl From<Decimal> for Number {
+    fn from(value: Decimal) -> Self {
+        Number::Decimal(value)
+    }
+}
+
+impl TryFrom<f64> for Number {
+    type Error = NumberError;
+
+    /// Goes through the shortest textual representation of the float, so `4.99_f64`
+    /// becomes the decimal `4.99` instead of `4.9900000000000002131628...`.
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if !value.is_finite() {
+            return Err(NumberError::NotFinite);
+        }
+
+        Decimal::from_str(&value.to_string())
+            .or_else(|_| Decimal::from_f64(value).ok_or(NumberError::OutOfRange))
+            .map(Number::Decimal)
+    }
+}
+
+impl TryFrom<&serde_json::Number> for Number {
+    type Error = NumberError;
+
+    // Note : The digits as they were written, serde_json is built with `arbitrary_precision`
+    fn try_from(value: &serde_json::Number) -> Result<Self, Self::Error> {
+        Number::parse(&value.to_string()).ok_or(NumberError::OutOfRange)
+    }
+}
+
+impl Serialize for Number {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Number::PosInt(n) => serializer.serialize_u64(*n),
+            Number::NegInt(n) => serializer.serialize_i64(*n),
+            // Note : Written digit for digit (serde_json `arbitrary_precision`), going through a
+            //        float would only keep about 15 significant digits of the decimal.
+            Number::Decimal(d) => serde_json::Number::from_str(&d.to_string())
+                .map_err(serde::ser::Error::custom)?
+                .serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NumberVisitor;
+
+        impl<'de> Visitor<'de> for NumberVisitor {
+            type Value = Number;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                f.write_str("a number")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Number, E> {
+                Ok(v.into())
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Number, E> {
+                Ok(v.into())
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Number, E> {
+                Number::try_from(v).map_err(E::custom)
+            }
+
+            // Note : How serde_json hands over the exact digits of a number, see `Serialize`
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Number, A::Error> {
+                let number = serde_json::Number::deserialize(
+                    de::value::MapAccessDeserializer::new(map),
+                )?;
+
+                Number::try_from(&number).map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_any(NumberVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberError {
+    NotFinite,
+    OutOfRange,
+}
+
+impl Display for NumberError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for NumberError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Number, Value, ValueStore, get};
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_number_canonical_form() {
+        assert!(matches!(Number::from(10_i32), Number::PosInt(10)));
+        assert!(matches!(Number::from(-3_i64), Number::NegInt(-3)));
+        assert_eq!(Number::from(10_u64), Number::Decimal(Decimal::from(10)));
+        assert!(Number::from(-1_i8) < Number::from(0_u8));
+    }
+
+    #[test]
+    fn test_number_json_round_trip() {
+        let json = r#"{"schema_name":null,"object_properties_schemas":{},"values":{"max":18446744073709551615,"min":-40,"price":4.99,"tolerance":-0.2}}"#;
+
+        let store: ValueStore = serde_json::from_str(json).unwrap();
+
+        let price = get!(store, number, price).unwrap();
+        assert_eq!(price.as_decimal(), Decimal::from_str("4.99").unwrap());
+
+        let tolerance = get!(store, number, tolerance).unwrap();
+        assert_eq!(tolerance.as_decimal(), Decimal::from_str("-0.2").unwrap());
+
+        assert_eq!(get!(store, number, min).unwrap().as_i64(), Some(-40));
+        assert_eq!(get!(store, number, max).unwrap().as_u64(), Some(u64::MAX));
+
+        assert_eq!(serde_json::to_string(&store).unwrap(), json);
+
+        // More digits than a float can hold
+        let precise = Number::from(Decimal::from_str("12345678901234.123456789012").unwrap());
+        let json = serde_json::to_string(&precise).unwrap();
+        assert_eq!(json, "12345678901234.123456789012");
+        let back: Number = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.as_decimal(), precise.as_decimal());
+    }
+
+    #[test]
+    fn test_number_from_serde_value() {
+        let value: Value = serde_json::json!({ "a": -1.5, "b": 7 }).try_into().unwrap();
+
+        let a = value
+            .as_object()
+            .unwrap()
+            .get("a")
+            .unwrap()
+            .as_number()
+            .unwrap();
+        assert_eq!(a.to_string(), "-1.5");
+        assert!(a.is_negative());
+
+        assert!(Value::try_from(serde_json::json!({ "a": 1e300 })).is_err());
+    }
+}
@@ -0,0 +1,280 @@
+use crate::Value;
+use crate::value::path::Path;
+use serde::{Deserialize, Serialize};
+
+pub use error::PatchError;
+
+/// A single RFC 6902 operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: Path, value: Value },
+    Remove { path: Path },
+    Replace { path: Path, value: Value },
+    Move { from: Path, path: Path },
+    Copy { from: Path, path: Path },
+    Test { path: Path, value: Value },
+}
+
+/// An RFC 6902 JSON Patch document, serialized as the plain array of operations.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JsonPatch(Vec<PatchOperation>);
+
+impl JsonPatch {
+    pub fn new(operations: Vec<PatchOperation>) -> Self {
+        JsonPatch(operations)
+    }
+
+    pub fn operations(&self) -> &[PatchOperation] {
+        &self.0
+    }
+
+    pub fn push(mut self, operation: PatchOperation) -> Self {
+        self.0.push(operation);
+        self
+    }
+}
+
+impl From<Vec<PatchOperation>> for JsonPatch {
+    fn from(value: Vec<PatchOperation>) -> Self {
+        JsonPatch(value)
+    }
+}
+
+impl Value {
+    /// Apply an RFC 7396 merge patch : objects are merged recursively, `null` removes a member
+    /// and anything else replaces the target.
+    pub fn merge_patch(&mut self, patch: &Value) {
+        let Value::Object(patch) = patch else {
+            *self = patch.clone();
+            return;
+        };
+
+        if !self.is_object() {
+            *self = Value::Object(Default::default());
+        }
+
+        let Value::Object(target) = self else {
+            unreachable!("replaced by an object above");
+        };
+
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                target
+                    .entry(key.clone())
+                    .or_insert(Value::Null)
+                    .merge_patch(value);
+            }
+        }
+    }
+
+    /// Apply an RFC 6902 JSON Patch.
+    ///
+    /// The operations are applied in order and atomically : if any of them fails, `self` is left untouched.
+    pub fn apply_patch(&mut self, patch: &JsonPatch) -> Result<(), PatchError> {
+        let mut patched = self.clone();
+
+        for (index, operation) in patch.operations().iter().enumerate() {
+            patched
+                .apply_operation(operation)
+                .map_err(|err| PatchError::Operation {
+                    index,
+                    error: Box::new(err),
+                })?;
+        }
+
+        *self = patched;
+        Ok(())
+    }
+
+    fn apply_operation(&mut self, operation: &PatchOperation) -> Result<(), PatchError> {
+        match operation {
+            PatchOperation::Add { path, value } => self.insert_path(path, value.clone())?,
+            PatchOperation::Remove { path } => {
+                self.remove_path(path)?;
+            }
+            PatchOperation::Replace { path, value } => {
+                *self.get_path_mut(path)? = value.clone();
+            }
+            PatchOperation::Move { from, path } => {
+                if from == path {
+                    // Still has to exist
+                    self.get_path(from)?;
+                    return Ok(());
+                }
+
+                if path.starts_with(from) {
+                    return Err(PatchError::MoveIntoItself {
+                        from: from.to_string(),
+                        path: path.to_string(),
+                    });
+                }
+
+                let value = self.remove_path(from)?;
+                self.insert_path(path, value)?;
+            }
+            PatchOperation::Copy { from, path } => {
+                let value = self.get_path(from)?.clone();
+                self.insert_path(path, value)?;
+            }
+            PatchOperation::Test { path, value } => {
+                if self.get_path(path)? != value {
+                    return Err(PatchError::TestFailed(path.to_string()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+mod error {
+    use crate::PathError;
+    use std::fmt;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum PatchError {
+        Path(PathError),
+        TestFailed(String),
+        MoveIntoItself {
+            from: String,
+            path: String,
+        },
+        /// The patch would leave a [`crate::ValueStore`] with something else than an object as its values.
+        NotAnObject,
+
+        /// Wraps the error of the `index`-th operation of a [`crate::JsonPatch`].
+        Operation {
+            index: usize,
+            error: Box<PatchError>,
+        },
+    }
+
+    impl From<PathError> for PatchError {
+        fn from(value: PathError) -> Self {
+            PatchError::Path(value)
+        }
+    }
+
+    impl fmt::Display for PatchError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for PatchError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPatch, PatchError, PathError, Value};
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_patch() {
+        // Example from RFC 7396 section 3
+        let mut target: Value = json!({
+            "title": "Goodbye!",
+            "author": { "givenName": "John", "familyName": "Doe" },
+            "tags": ["example", "sample"],
+            "content": "This will be unchanged"
+        })
+        .try_into()
+        .unwrap();
+
+        let patch: Value = json!({
+            "title": "Hello!",
+            "phoneNumber": "+01-234-567-8910",
+            "author": { "familyName": null },
+            "tags": ["example"]
+        })
+        .try_into()
+        .unwrap();
+
+        target.merge_patch(&patch);
+
+        let expected: Value = json!({
+            "title": "Hello!",
+            "author": { "givenName": "John" },
+            "tags": ["example"],
+            "content": "This will be unchanged",
+            "phoneNumber": "+01-234-567-8910"
+        })
+        .try_into()
+        .unwrap();
+
+        assert_eq!(target, expected);
+    }
+
+    #[test]
+    fn test_json_patch() {
+        let mut target: Value = json!({ "foo": ["bar", "baz"], "qux": { "a": 1 } })
+            .try_into()
+            .unwrap();
+
+        let patch: JsonPatch = serde_json::from_value(json!([
+            { "op": "test", "path": "/qux/a", "value": 1 },
+            { "op": "add", "path": "/foo/1", "value": "qux" },
+            { "op": "remove", "path": "/foo/0" },
+            { "op": "replace", "path": "/qux/a", "value": -2.5 },
+            { "op": "copy", "from": "/foo", "path": "/copied" },
+            { "op": "move", "from": "/qux", "path": "/moved" }
+        ]))
+        .unwrap();
+
+        target.apply_patch(&patch).unwrap();
+
+        let expected: Value = json!({
+            "foo": ["qux", "baz"],
+            "copied": ["qux", "baz"],
+            "moved": { "a": -2.5 }
+        })
+        .try_into()
+        .unwrap();
+
+        assert_eq!(target, expected);
+    }
+
+    #[test]
+    fn test_json_patch_is_atomic() {
+        let original: Value = json!({ "a": 1, "b": [1, 2] }).try_into().unwrap();
+        let mut target = original.clone();
+
+        let patch: JsonPatch = serde_json::from_value(json!([
+            { "op": "remove", "path": "/a" },
+            { "op": "test", "path": "/b/1", "value": 3 }
+        ]))
+        .unwrap();
+
+        let err = target.apply_patch(&patch).unwrap_err();
+        assert_eq!(
+            err,
+            PatchError::Operation {
+                index: 1,
+                error: Box::new(PatchError::TestFailed("/b/1".into()))
+            }
+        );
+        assert_eq!(target, original);
+
+        let patch: JsonPatch = serde_json::from_value(json!([
+            { "op": "move", "from": "/b", "path": "/b/0" }
+        ]))
+        .unwrap();
+        assert!(target.apply_patch(&patch).is_err());
+
+        let patch: JsonPatch = serde_json::from_value(json!([
+            { "op": "replace", "path": "/c", "value": 1 }
+        ]))
+        .unwrap();
+        assert_eq!(
+            target.apply_patch(&patch).unwrap_err(),
+            PatchError::Operation {
+                index: 0,
+                error: Box::new(PatchError::Path(PathError::NotFound("/c".into())))
+            }
+        );
+    }
+}
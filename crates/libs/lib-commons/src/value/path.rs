@@ -0,0 +1,649 @@
+use crate::{Measurement, Number, Value};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub use error::PathError;
+
+/// A single step of a [`Path`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),
+    /// Also matches an object key with the same textual form (e.g. `"2024"`).
+    Index(usize),
+    /// One past the last element of an array (`-` in JSON-Pointer), only meaningful when setting.
+    End,
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathSegment::Key(key) => f.write_str(key),
+            PathSegment::Index(index) => write!(f, "{index}"),
+            PathSegment::End => f.write_str("-"),
+        }
+    }
+}
+
+/// Runtime counterpart of the paths accepted by the [`crate::get`] macro.
+///
+/// Parsed either from a JSON-Pointer (`/racks/0/name`) or from the dotted/indexed
+/// syntax (`racks.0.name`, `racks[0].name`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    pub fn root() -> Self {
+        Path::default()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn push(mut self, segment: PathSegment) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    pub fn key(self, key: impl Into<String>) -> Self {
+        self.push(PathSegment::Key(key.into()))
+    }
+
+    pub fn index(self, index: usize) -> Self {
+        self.push(PathSegment::Index(index))
+    }
+
+    pub fn parent(&self) -> Option<(Path, &PathSegment)> {
+        let (last, rest) = self.segments.split_last()?;
+        Some((
+            Path {
+                segments: rest.to_vec(),
+            },
+            last,
+        ))
+    }
+
+    /// Whether `self` points at `other` or somewhere below it.
+    pub fn starts_with(&self, other: &Path) -> bool {
+        self.segments.starts_with(&other.segments)
+    }
+
+    /// The path made of the first `len` segments, used to report where a lookup failed.
+    pub(crate) fn prefix(&self, len: usize) -> String {
+        Path {
+            segments: self.segments[..len].to_vec(),
+        }
+        .to_string()
+    }
+
+    pub fn parse_pointer(pointer: &str) -> Result<Self, PathError> {
+        if pointer.is_empty() {
+            return Ok(Path::root());
+        }
+
+        let Some(rest) = pointer.strip_prefix('/') else {
+            return Err(PathError::Parse(pointer.to_string()));
+        };
+
+        let segments = rest
+            .split('/')
+            .map(|token| {
+                let token = token.replace("~1", "/").replace("~0", "~");
+                if token == "-" {
+                    PathSegment::End
+                } else {
+                    parse_index(&token)
+                        .map(PathSegment::Index)
+                        .unwrap_or(PathSegment::Key(token))
+                }
+            })
+            .collect();
+
+        Ok(Path { segments })
+    }
+
+    pub fn parse_dotted(path: &str) -> Result<Self, PathError> {
+        let mut segments = Vec::new();
+
+        for (position, part) in path.split('.').enumerate() {
+            let (key, mut indexes) = part.split_at(part.find('[').unwrap_or(part.len()));
+
+            if !key.is_empty() {
+                segments.push(
+                    parse_index(key)
+                        .map(PathSegment::Index)
+                        .unwrap_or(PathSegment::Key(key.to_string())),
+                );
+            } else if position != 0 || indexes.is_empty() {
+                // Only a leading `[0]` may come without a key, `a..b` is rejected
+                return Err(PathError::Parse(path.to_string()));
+            }
+
+            while !indexes.is_empty() {
+                let end = indexes
+                    .find(']')
+                    .filter(|_| indexes.starts_with('['))
+                    .ok_or_else(|| PathError::Parse(path.to_string()))?;
+
+                let segment = match &indexes[1..end] {
+                    "-" => PathSegment::End,
+                    inner => PathSegment::Index(
+                        parse_index(inner).ok_or_else(|| PathError::Parse(path.to_string()))?,
+                    ),
+                };
+                segments.push(segment);
+                indexes = &indexes[end + 1..];
+            }
+        }
+
+        Ok(Path { segments })
+    }
+}
+
+fn parse_index(token: &str) -> Option<usize> {
+    // Leading zeros are not indexes as per RFC 6901
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+impl FromStr for Path {
+    type Err = PathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.starts_with('/') {
+            Path::parse_pointer(s)
+        } else {
+            Path::parse_dotted(s)
+        }
+    }
+}
+
+impl Display for Path {
+    /// Always written back as a JSON-Pointer.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for segment in &self.segments {
+            let token = segment.to_string().replace('~', "~0").replace('/', "~1");
+            write!(f, "/{token}")?;
+        }
+        Ok(())
+    }
+}
+
+impl serde::Serialize for Path {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Path {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Anything the path based API accepts as a path.
+pub trait IntoPath {
+    fn into_path(self) -> Result<Path, PathError>;
+}
+
+impl IntoPath for Path {
+    fn into_path(self) -> Result<Path, PathError> {
+        Ok(self)
+    }
+}
+
+impl IntoPath for &Path {
+    fn into_path(self) -> Result<Path, PathError> {
+        Ok(self.clone())
+    }
+}
+
+impl IntoPath for &str {
+    fn into_path(self) -> Result<Path, PathError> {
+        self.parse()
+    }
+}
+
+impl IntoPath for String {
+    fn into_path(self) -> Result<Path, PathError> {
+        self.parse()
+    }
+}
+
+impl IntoPath for &String {
+    fn into_path(self) -> Result<Path, PathError> {
+        self.parse()
+    }
+}
+
+/// Typed extraction used by [`Value::get_path_as`], mirrors the casts of the [`crate::get`] macro.
+pub trait FromValue: Sized {
+    const EXPECTED: &'static str;
+
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+macro_rules! impl_from_value {
+    ($ty:ty, $expected:literal, |$v:ident| $body:expr) => {
+        impl FromValue for $ty {
+            const EXPECTED: &'static str = $expected;
+
+            fn from_value($v: &Value) -> Option<Self> {
+                $body
+            }
+        }
+    };
+}
+
+impl_from_value!(Value, "value", |v| Some(v.clone()));
+impl_from_value!(String, "string", |v| v.as_string());
+impl_from_value!(bool, "boolean", |v| v.as_bool());
+impl_from_value!(Number, "number", |v| v.as_number());
+impl_from_value!(u64, "unsigned integer", |v| v.as_number()?.as_u64());
+impl_from_value!(i64, "integer", |v| v.as_number()?.as_i64());
+impl_from_value!(Decimal, "number", |v| Some(v.as_number()?.as_decimal()));
+impl_from_value!(Measurement, "measurement", |v| v.as_measurement());
+impl_from_value!(Vec<Value>, "array", |v| v.as_array().cloned());
+impl_from_value!(BTreeMap<String, Value>, "object", |v| v
+    .as_object()
+    .cloned());
+
+impl Value {
+    pub fn get_path(&self, path: impl IntoPath) -> Result<&Value, PathError> {
+        walk(self, &path.into_path()?, 0)
+    }
+
+    pub fn get_path_mut(&mut self, path: impl IntoPath) -> Result<&mut Value, PathError> {
+        walk_mut(self, &path.into_path()?, 0)
+    }
+
+    pub fn get_path_as<T: FromValue>(&self, path: impl IntoPath) -> Result<T, PathError> {
+        let path = path.into_path()?;
+        let value = self.get_path(&path)?;
+
+        T::from_value(value).ok_or_else(|| PathError::TypeMismatch {
+            path: path.to_string(),
+            expected: T::EXPECTED,
+        })
+    }
+
+    pub fn path_exists(&self, path: impl IntoPath) -> bool {
+        self.get_path(path).is_ok()
+    }
+
+    /// Set the value at `path`, creating the missing intermediate objects and arrays along the way
+    /// (an object for a key, an array for an index).
+    ///
+    /// Returns the value previously stored at `path`, if any. Nothing is created when it fails.
+    pub fn set_path(
+        &mut self,
+        path: impl IntoPath,
+        value: Value,
+    ) -> Result<Option<Value>, PathError> {
+        let path = path.into_path()?;
+        self.check_settable(&path)?;
+        let mut current = self;
+
+        for (depth, segment) in path.segments.iter().enumerate() {
+            let is_last = depth + 1 == path.segments.len();
+
+            if current.is_null() {
+                *current = match segment {
+                    PathSegment::Key(_) => Value::Object(BTreeMap::new()),
+                    PathSegment::Index(_) | PathSegment::End => Value::Array(Vec::new()),
+                };
+            }
+
+            let slot = match (current, segment) {
+                (Value::Object(map), segment) => {
+                    map.entry(segment.to_string()).or_insert(Value::Null)
+                }
+                (Value::Array(_), PathSegment::Key(_)) => {
+                    return Err(PathError::NotAnIndex(path.prefix(depth + 1)));
+                }
+                (Value::Array(arr), segment) => {
+                    let index = match segment {
+                        PathSegment::Index(index) => *index,
+                        _ => arr.len(),
+                    };
+
+                    if index > arr.len() {
+                        return Err(PathError::IndexOutOfBounds {
+                            path: path.prefix(depth + 1),
+                            len: arr.len(),
+                        });
+                    }
+
+                    if index == arr.len() {
+                        arr.push(Value::Null);
+                    }
+
+                    &mut arr[index]
+                }
+                _ => return Err(PathError::NotAContainer(path.prefix(depth + 1))),
+            };
+
+            if is_last {
+                let previous = std::mem::replace(slot, value);
+                return Ok((!previous.is_null()).then_some(previous));
+            }
+
+            current = slot;
+        }
+
+        // Empty path, replace the whole value
+        Ok(Some(std::mem::replace(current, value)))
+    }
+
+    // Note : The same walk as `set_path` without creating anything, so that a failing set
+    //        leaves the value untouched
+    fn check_settable(&self, path: &Path) -> Result<(), PathError> {
+        let mut current = Some(self);
+
+        for (depth, segment) in path.segments.iter().enumerate() {
+            current = match (current, segment) {
+                // Created by `set_path`, empty
+                (None | Some(Value::Null), PathSegment::Index(index)) if *index > 0 => {
+                    return Err(PathError::IndexOutOfBounds {
+                        path: path.prefix(depth + 1),
+                        len: 0,
+                    });
+                }
+                (None | Some(Value::Null), _) => None,
+                (Some(Value::Object(map)), segment) => map.get(&segment.to_string()),
+                (Some(Value::Array(_)), PathSegment::Key(_)) => {
+                    return Err(PathError::NotAnIndex(path.prefix(depth + 1)));
+                }
+                (Some(Value::Array(arr)), segment) => {
+                    let index = match segment {
+                        PathSegment::Index(index) => *index,
+                        _ => arr.len(),
+                    };
+
+                    if index > arr.len() {
+                        return Err(PathError::IndexOutOfBounds {
+                            path: path.prefix(depth + 1),
+                            len: arr.len(),
+                        });
+                    }
+
+                    arr.get(index)
+                }
+                _ => return Err(PathError::NotAContainer(path.prefix(depth + 1))),
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Insert at `path`, shifting array elements to the right instead of replacing them.
+    /// Only the direct parent has to exist.
+    pub fn insert_path(&mut self, path: impl IntoPath, value: Value) -> Result<(), PathError> {
+        let path = path.into_path()?;
+        let Some((parent, last)) = path.parent() else {
+            *self = value;
+            return Ok(());
+        };
+
+        match (self.get_path_mut(&parent)?, last) {
+            (Value::Object(map), segment) => {
+                map.insert(segment.to_string(), value);
+            }
+            (Value::Array(arr), PathSegment::End) => arr.push(value),
+            (Value::Array(arr), PathSegment::Index(index)) if *index <= arr.len() => {
+                arr.insert(*index, value)
+            }
+            (Value::Array(arr), PathSegment::Index(_)) => {
+                return Err(PathError::IndexOutOfBounds {
+                    path: path.to_string(),
+                    len: arr.len(),
+                });
+            }
+            (Value::Array(_), PathSegment::Key(_)) => {
+                return Err(PathError::NotAnIndex(path.to_string()));
+            }
+            _ => return Err(PathError::NotAContainer(path.to_string())),
+        }
+
+        Ok(())
+    }
+
+    /// Remove the value at `path`, array elements after it are shifted to the left.
+    pub fn remove_path(&mut self, path: impl IntoPath) -> Result<Value, PathError> {
+        let path = path.into_path()?;
+        let Some((parent, last)) = path.parent() else {
+            return Err(PathError::Root);
+        };
+
+        let removed = match (self.get_path_mut(&parent)?, last) {
+            (Value::Object(map), segment) => map.remove(&segment.to_string()),
+            (Value::Array(arr), PathSegment::Index(index)) if *index < arr.len() => {
+                Some(arr.remove(*index))
+            }
+            (Value::Array(_), PathSegment::Index(_) | PathSegment::End) => None,
+            (Value::Array(_), PathSegment::Key(_)) => {
+                return Err(PathError::NotAnIndex(path.to_string()));
+            }
+            _ => return Err(PathError::NotAContainer(path.to_string())),
+        };
+
+        removed.ok_or_else(|| PathError::NotFound(path.to_string()))
+    }
+}
+
+/// Follow `path` from `current`, skipping its first `from` segments.
+pub(crate) fn walk<'a>(
+    mut current: &'a Value,
+    path: &Path,
+    from: usize,
+) -> Result<&'a Value, PathError> {
+    for (depth, segment) in path.segments.iter().enumerate().skip(from) {
+        current = match (current, segment) {
+            (Value::Object(map), segment) => map.get(&segment.to_string()),
+            (Value::Array(arr), PathSegment::Index(index)) => arr.get(*index),
+            (Value::Array(_), _) => return Err(PathError::NotAnIndex(path.prefix(depth + 1))),
+            _ => return Err(PathError::NotAContainer(path.prefix(depth + 1))),
+        }
+        .ok_or_else(|| PathError::NotFound(path.prefix(depth + 1)))?;
+    }
+
+    Ok(current)
+}
+
+pub(crate) fn walk_mut<'a>(
+    mut current: &'a mut Value,
+    path: &Path,
+    from: usize,
+) -> Result<&'a mut Value, PathError> {
+    for (depth, segment) in path.segments.iter().enumerate().skip(from) {
+        current = match (current, segment) {
+            (Value::Object(map), segment) => map.get_mut(&segment.to_string()),
+            (Value::Array(arr), PathSegment::Index(index)) => arr.get_mut(*index),
+            (Value::Array(_), _) => return Err(PathError::NotAnIndex(path.prefix(depth + 1))),
+            _ => return Err(PathError::NotAContainer(path.prefix(depth + 1))),
+        }
+        .ok_or_else(|| PathError::NotFound(path.prefix(depth + 1)))?;
+    }
+
+    Ok(current)
+}
+
+mod error {
+    use std::fmt;
+
+    /// Every variant carries the path (as a JSON-Pointer) up to and including the failing segment.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum PathError {
+        Parse(String),
+        Root,
+        NotFound(String),
+        NotAContainer(String),
+        NotAnIndex(String),
+        IndexOutOfBounds {
+            path: String,
+            len: usize,
+        },
+        TypeMismatch {
+            path: String,
+            expected: &'static str,
+        },
+    }
+
+    impl fmt::Display for PathError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for PathError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Number, Path, PathError, PathSegment, Value, ValueStore};
+
+    fn racks() -> ValueStore {
+        let value: Value = serde_json::json!({
+            "schema_name": "TestSchema",
+            "object_properties_schemas": {},
+            "values": {
+                "racks": [
+                    { "rack_name": "Rack A", "shelves": ["Shelf α", "Shelf β"] },
+                    { "rack_name": "Rack B", "shelves": [] }
+                ]
+            }
+        })
+        .try_into()
+        .unwrap();
+
+        value.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_path_parse() {
+        let dotted: Path = "racks[0].shelves.1".parse().unwrap();
+        let pointer: Path = "/racks/0/shelves/1".parse().unwrap();
+
+        assert_eq!(dotted, pointer);
+        assert_eq!(dotted.to_string(), "/racks/0/shelves/1");
+
+        let escaped: Path = "/a~1b/c~0d/-".parse().unwrap();
+        assert_eq!(
+            escaped.segments(),
+            &[
+                PathSegment::Key("a/b".into()),
+                PathSegment::Key("c~d".into()),
+                PathSegment::End
+            ]
+        );
+
+        assert!("racks..name".parse::<Path>().is_err());
+        assert!("racks[x]".parse::<Path>().is_err());
+    }
+
+    #[test]
+    fn test_path_get() {
+        let store = racks();
+
+        assert_eq!(
+            store.get_path_as::<String>("racks.1.rack_name").unwrap(),
+            "Rack B"
+        );
+        assert!(store.path_exists("/racks/0/shelves/1"));
+        assert!(!store.path_exists("racks.2"));
+
+        assert_eq!(
+            store.get_path("racks.0.missing").unwrap_err(),
+            PathError::NotFound("/racks/0/missing".into())
+        );
+        assert_eq!(
+            store.get_path("racks.0.rack_name.x").unwrap_err(),
+            PathError::NotAContainer("/racks/0/rack_name/x".into())
+        );
+        assert_eq!(
+            store
+                .get_path_as::<Number>("racks.0.rack_name")
+                .unwrap_err(),
+            PathError::TypeMismatch {
+                path: "/racks/0/rack_name".into(),
+                expected: "number"
+            }
+        );
+    }
+
+    #[test]
+    fn test_path_set_and_remove() {
+        let mut store = racks();
+
+        store
+            .set_path("racks[1].shelves[0]", Value::String("Shelf γ".into()))
+            .unwrap();
+        store
+            .set_path("dimensions.sizes[0].width", Value::Number(10.into()))
+            .unwrap();
+        store
+            .set_path("/racks/-", Value::String("Rack C".into()))
+            .unwrap();
+
+        assert_eq!(
+            store.get_path_as::<String>("racks.1.shelves.0").unwrap(),
+            "Shelf γ"
+        );
+        assert_eq!(
+            store
+                .get_path_as::<u64>("dimensions.sizes.0.width")
+                .unwrap(),
+            10
+        );
+        assert_eq!(store.get_path_as::<Vec<Value>>("racks").unwrap().len(), 3);
+
+        assert_eq!(
+            store.set_path("racks.5", Value::Null).unwrap_err(),
+            PathError::IndexOutOfBounds {
+                path: "/racks/5".into(),
+                len: 3
+            }
+        );
+
+        // A failing set creates nothing along the way
+        let mut value: Value = serde_json::json!({ "a": 1 }).try_into().unwrap();
+        let original = value.clone();
+        assert_eq!(
+            value.set_path("/x/y/a/5", Value::Boolean(true)).unwrap_err(),
+            PathError::IndexOutOfBounds {
+                path: "/x/y/a/5".into(),
+                len: 0
+            }
+        );
+        assert!(value.set_path("/x/0/5", Value::Boolean(true)).is_err());
+        assert!(value.set_path("/a/b", Value::Boolean(true)).is_err());
+        assert_eq!(value, original);
+
+        let removed = store.remove_path("racks.0").unwrap();
+        assert_eq!(
+            removed.get_path_as::<String>("rack_name").unwrap(),
+            "Rack A"
+        );
+        assert_eq!(
+            store.get_path_as::<String>("racks.0.rack_name").unwrap(),
+            "Rack B"
+        );
+
+        assert!(store.remove_path("racks.0.nothing").is_err());
+        assert_eq!(store.remove_path("").unwrap_err(), PathError::Root);
+    }
+}
@@ -1,5 +1,7 @@
 use crate::value::Value;
 pub use error::{Result, ValueStoreError};
+pub use patch::ValueStorePatch;
+pub use diff::{SchemaNameChange, ValueStoreDiff};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as SerdeValue;
 use std::fmt::{Display, Formatter};
@@ -12,10 +14,19 @@ pub mod builder;
 
 mod macros;
 
+mod path;
+
+mod patch;
+
+mod diff;
+
 #[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ValueStore {
     schema_name: Option<String>,
+    /// The version of the schema the values were written against, see [`crate::Migration`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schema_version: Option<u32>,
     object_properties_schemas: Arc<HashMap<String, String>>,
     values: BTreeMap<String, Value>,
 }
@@ -39,6 +50,7 @@ impl ValueStore {
     pub fn new(schema_name: Option<String>) -> Self {
         ValueStore {
             schema_name,
+            schema_version: None,
             object_properties_schemas: Arc::new(HashMap::new()),
             values: BTreeMap::new(),
         }
@@ -59,6 +71,16 @@ impl ValueStore {
     pub fn schema_name(&self) -> &Option<String> {
         &self.schema_name
     }
+
+    /// `None` for values written before the schema was versioned, so against its first version.
+    pub fn schema_version(&self) -> Option<u32> {
+        self.schema_version
+    }
+
+    pub fn set_schema_version(&mut self, version: u32) {
+        self.schema_version = Some(version);
+    }
+
     pub fn object_properties_schemas(&self) -> Arc<HashMap<String, String>> {
         self.object_properties_schemas.clone()
     }
@@ -78,8 +100,53 @@ impl ValueStore {
         self.values.get(key)
     }
 
-    pub fn remove(&mut self, key: &str) {
-        self.values.remove(key);
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.values.remove(key)
+    }
+
+    /// Move the schema of the nested object (or array of objects) `from` to the property `to`,
+    /// `None` dropping it.
+    pub fn rename_property_schema(&mut self, from: &str, to: Option<&str>) {
+        if !self.object_properties_schemas.contains_key(from) {
+            return;
+        }
+
+        let properties = Arc::make_mut(&mut self.object_properties_schemas);
+        if let Some(schema) = properties.remove(from)
+            && let Some(to) = to
+        {
+            properties.insert(to.to_string(), schema);
+        }
+    }
+
+    /// Point every reference to the schema `from`, the main one and the nested ones, at `to`,
+    /// `None` dropping them. Gives back whether anything changed.
+    pub fn replace_schema(&mut self, from: &str, to: Option<&str>) -> bool {
+        let mut changed = false;
+
+        if self.schema_name.as_deref() == Some(from) {
+            self.schema_name = to.map(str::to_string);
+            if to.is_none() {
+                // The version only means something for the schema it was pinned to
+                self.schema_version = None;
+            }
+            changed = true;
+        }
+
+        if !self.object_properties_schemas.values().any(|schema| schema == from) {
+            return changed;
+        }
+
+        let properties = Arc::make_mut(&mut self.object_properties_schemas);
+        match to {
+            Some(to) => properties
+                .values_mut()
+                .filter(|schema| *schema == from)
+                .for_each(|schema| *schema = to.to_string()),
+            None => properties.retain(|_, schema| schema != from),
+        }
+
+        true
     }
 }
 
@@ -89,6 +156,11 @@ impl TryFrom<Value> for ValueStore {
         match value {
             Value::Object(map) => {
                 let schema_name = map.get("schema_name").and_then(|v| v.as_string());
+                let schema_version = map
+                    .get("schema_version")
+                    .and_then(|v| v.as_number())
+                    .and_then(|n| n.as_u64())
+                    .and_then(|n| u32::try_from(n).ok());
 
                 let object_properties_schemas: Option<HashMap<String, String>> = map
                     .get("object_properties_schemas")
@@ -107,6 +179,10 @@ impl TryFrom<Value> for ValueStore {
                     ValueStore::builder()
                 };
 
+                if let Some(schema_version) = schema_version {
+                    builder = builder.with_schema_version(schema_version);
+                }
+
                 if let Some(object_properties_schemas) = object_properties_schemas {
                     builder = builder.with_object_properties_schemas(object_properties_schemas);
                 }
@@ -140,7 +216,7 @@ impl TryFrom<&Value> for ValueStore {
 impl TryFrom<SerdeValue> for ValueStore {
     type Error = ValueStoreError;
     fn try_from(value: SerdeValue) -> core::result::Result<Self, Self::Error> {
-        let value: Value = value.into();
+        let value = Value::try_from(value).map_err(|_| ValueStoreError::NotANumber)?;
         value.try_into()
     }
 }
@@ -156,11 +232,11 @@ impl TryFrom<&SerdeValue> for ValueStore {
 macro_rules! value {
     ($schema_name:ident => $value:expr) => {{
         let values: Map<String, SerdeValue> = $value.into();
-        let values = values
+        values
             .into_iter()
-            .map(|(key, value)| (key, value.into()))
-            .collect();
-        ValueStore::new($schema_name).with_values(values)
+            .map(|(key, value)| Ok((key, $crate::Value::try_from(value)?)))
+            .collect::<core::result::Result<_, $crate::NumberError>>()
+            .map(|values| ValueStore::new($schema_name).with_values(values))
     }};
 }
 
@@ -1,5 +1,5 @@
 use crate::value_store::Result;
-use crate::{Value, ValueStore, ValueStoreError};
+use crate::{Measurement, Number, NumberError, Value, ValueStore, ValueStoreError};
 use serde_json::Value as SerdeJson;
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
@@ -7,6 +7,7 @@ use std::sync::Arc;
 #[derive(Debug, Clone, Default)]
 pub struct Builder {
     schema_name: Option<String>,
+    schema_version: Option<u32>,
     object_properties_schemas: HashMap<String, String>,
     values: BTreeMap<String, Value>,
 }
@@ -21,6 +22,7 @@ impl Builder {
     pub fn build(self) -> ValueStore {
         ValueStore {
             schema_name: self.schema_name,
+            schema_version: self.schema_version,
             object_properties_schemas: Arc::new(self.object_properties_schemas),
             values: self.values.into_iter().collect(),
         }
@@ -38,6 +40,12 @@ impl Builder {
 
         self
     }
+
+    pub fn with_schema_version(mut self, version: u32) -> Self {
+        self.schema_version = Some(version);
+        self
+    }
+
     pub fn string(mut self, key: impl AsRef<str>, value: impl AsRef<str>) -> Self {
         self.values
             .entry(key.as_ref().to_string())
@@ -59,10 +67,18 @@ impl Builder {
         self
     }
 
-    pub fn number(mut self, key: impl AsRef<str>, value: u64) -> Self {
+    pub fn number(mut self, key: impl AsRef<str>, value: impl Into<Number>) -> Self {
+        self.values
+            .entry(key.as_ref().to_string())
+            .or_insert(Value::Number(value.into()));
+        self
+    }
+
+    /// Converted to the base unit of its dimension before being stored.
+    pub fn measurement(mut self, key: impl AsRef<str>, value: Measurement) -> Self {
         self.values
             .entry(key.as_ref().to_string())
-            .or_insert(Value::Number(value));
+            .or_insert(value.into());
         self
     }
 
@@ -126,8 +142,9 @@ impl Builder {
             .as_object()
             .ok_or(ValueStoreError::NotAnObject)?
             .into_iter()
-            .map(|(k, v)| (k.clone(), v.clone().into()))
-            .collect();
+            .map(|(k, v)| Ok((k.clone(), Value::try_from(v)?)))
+            .collect::<core::result::Result<_, NumberError>>()
+            .map_err(|_| ValueStoreError::NotANumber)?;
         Ok(self)
     }
 
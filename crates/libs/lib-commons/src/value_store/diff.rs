@@ -0,0 +1,147 @@
+use crate::value::diff::{ValueChange, diff_map};
+use crate::value::path::Path;
+use crate::{Value, ValueStore};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaNameChange {
+    old: Option<String>,
+    new: Option<String>,
+}
+
+impl SchemaNameChange {
+    pub fn old_name(&self) -> Option<&str> {
+        self.old.as_deref()
+    }
+
+    pub fn new_name(&self) -> Option<&str> {
+        self.new.as_deref()
+    }
+}
+
+/// Everything that changed between two [`ValueStore`]s, see [`ValueStore::diff`].
+///
+/// `object_properties_schemas` changes use the property name as their (single segment) path.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ValueStoreDiff {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    schema_name: Option<SchemaNameChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    object_properties_schemas: Vec<ValueChange>,
+    values: Vec<ValueChange>,
+}
+
+impl ValueStoreDiff {
+    pub fn schema_name(&self) -> Option<&SchemaNameChange> {
+        self.schema_name.as_ref()
+    }
+
+    pub fn object_properties_schemas(&self) -> &[ValueChange] {
+        &self.object_properties_schemas
+    }
+
+    pub fn values(&self) -> &[ValueChange] {
+        &self.values
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schema_name.is_none()
+            && self.object_properties_schemas.is_empty()
+            && self.values.is_empty()
+    }
+}
+
+impl ValueStore {
+    /// Diff from `self` (the old version) to `other` (the new one), value paths are rooted at `values`.
+    pub fn diff(&self, other: &ValueStore) -> ValueStoreDiff {
+        let schema_name = (self.schema_name != other.schema_name).then(|| SchemaNameChange {
+            old: self.schema_name.clone(),
+            new: other.schema_name.clone(),
+        });
+
+        // Sorted so that the output does not depend on the HashMap order
+        let keys: BTreeSet<&String> = self
+            .object_properties_schemas
+            .keys()
+            .chain(other.object_properties_schemas.keys())
+            .collect();
+
+        let object_properties_schemas = keys
+            .into_iter()
+            .filter_map(|key| {
+                let path = Path::root().key(key);
+                let old = self.object_properties_schemas.get(key);
+                let new = other.object_properties_schemas.get(key);
+
+                match (old, new) {
+                    (Some(old), Some(new)) if old != new => Some(ValueChange::Changed {
+                        path,
+                        old: Value::String(old.clone()),
+                        new: Value::String(new.clone()),
+                    }),
+                    (Some(old), None) => Some(ValueChange::Removed {
+                        path,
+                        value: Value::String(old.clone()),
+                    }),
+                    (None, Some(new)) => Some(ValueChange::Added {
+                        path,
+                        value: Value::String(new.clone()),
+                    }),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        let mut values = Vec::new();
+        diff_map(Path::root(), &self.values, &other.values, &mut values);
+
+        ValueStoreDiff {
+            schema_name,
+            object_properties_schemas,
+            values,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ValueStore;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_value_store_diff() {
+        let old = ValueStore::builder()
+            .with_schema("Box")
+            .with_object_properties_schemas(HashMap::from([("lid".into(), "Lid".into())]))
+            .string("name", "Small box")
+            .number("count", 2)
+            .build();
+
+        let new = ValueStore::builder()
+            .with_schema("Crate")
+            .with_object_properties_schemas(HashMap::from([("lid".into(), "LidV2".into())]))
+            .string("name", "Small box")
+            .bool("stackable", true)
+            .build();
+
+        let diff = old.diff(&new);
+
+        assert_eq!(
+            serde_json::to_value(&diff).unwrap(),
+            json!({
+                "schema_name": { "old": "Box", "new": "Crate" },
+                "object_properties_schemas": [
+                    { "kind": "changed", "path": "/lid", "old": "Lid", "new": "LidV2" }
+                ],
+                "values": [
+                    { "kind": "removed", "path": "/count", "value": 2 },
+                    { "kind": "added", "path": "/stackable", "value": true }
+                ]
+            })
+        );
+
+        assert!(old.diff(&old).is_empty());
+    }
+}
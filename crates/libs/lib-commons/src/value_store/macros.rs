@@ -34,6 +34,10 @@ macro_rules! get {
         $val.as_number()
     };
 
+    (@cast measurement $val:ident) => {
+        $val.as_measurement()
+    };
+
     (@cast string $val:ident) => {
         $val.as_string()
     };
@@ -0,0 +1,92 @@
+use crate::value::patch::{JsonPatch, PatchError};
+use crate::{Value, ValueStore};
+use serde::{Deserialize, Serialize};
+
+/// An edit to a [`ValueStore`].
+///
+/// Both patch flavours only touch `values`, the schema information is kept as is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "patch", rename_all = "snake_case")]
+pub enum ValueStorePatch {
+    Replace(ValueStore),
+    /// RFC 7396
+    Merge(Value),
+    /// RFC 6902
+    Json(JsonPatch),
+}
+
+impl ValueStore {
+    /// Merge patches have to be objects, anything else would replace `values` wholesale.
+    pub fn merge_patch(&mut self, patch: &Value) -> Result<(), PatchError> {
+        if !patch.is_object() {
+            return Err(PatchError::NotAnObject);
+        }
+
+        self.with_root(|root| root.merge_patch(patch));
+        Ok(())
+    }
+
+    /// See [`Value::apply_patch`], paths are rooted at `values`.
+    pub fn apply_patch(&mut self, patch: &JsonPatch) -> Result<(), PatchError> {
+        let mut root = self.as_value();
+        root.apply_patch(patch)?;
+
+        let Value::Object(values) = root else {
+            return Err(PatchError::NotAnObject);
+        };
+
+        self.values = values;
+        Ok(())
+    }
+
+    pub fn apply(&mut self, patch: &ValueStorePatch) -> Result<(), PatchError> {
+        match patch {
+            ValueStorePatch::Replace(store) => {
+                *self = store.clone();
+                Ok(())
+            }
+            ValueStorePatch::Merge(patch) => self.merge_patch(patch),
+            ValueStorePatch::Json(patch) => self.apply_patch(patch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Value, ValueStore, ValueStorePatch};
+    use serde_json::json;
+
+    #[test]
+    fn test_value_store_patch() {
+        let mut store = ValueStore::builder()
+            .with_schema("TestSchema")
+            .string("a", "this is a string")
+            .number("b", 10)
+            .build();
+
+        let merge: ValueStorePatch =
+            serde_json::from_value(json!({ "type": "merge", "patch": { "b": null, "c": true } }))
+                .unwrap();
+        store.apply(&merge).unwrap();
+
+        let json: ValueStorePatch = serde_json::from_value(json!({
+            "type": "json",
+            "patch": [{ "op": "add", "path": "/d", "value": [1, 2] }]
+        }))
+        .unwrap();
+        store.apply(&json).unwrap();
+
+        assert_eq!(store.schema_name().as_deref(), Some("TestSchema"));
+        assert!(store.get("b").is_none());
+        assert!(store.get_path_as::<bool>("c").unwrap());
+        assert_eq!(store.get_path_as::<u64>("d.1").unwrap(), 2);
+
+        let replace_root: ValueStorePatch = serde_json::from_value(json!({
+            "type": "json",
+            "patch": [{ "op": "replace", "path": "", "value": 1 }]
+        }))
+        .unwrap();
+        assert!(store.apply(&replace_root).is_err());
+        assert!(store.merge_patch(&Value::Number(1.into())).is_err());
+    }
+}
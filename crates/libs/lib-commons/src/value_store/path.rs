@@ -0,0 +1,87 @@
+use crate::value::path::{FromValue, IntoPath, Path, PathError, walk, walk_mut};
+use crate::{Value, ValueStore};
+
+/// Same API as the path based methods of [`Value`], rooted at the `values` of the store.
+///
+/// The root itself is not addressable, so an empty path is rejected with [`PathError::Root`].
+impl ValueStore {
+    pub fn get_path(&self, path: impl IntoPath) -> Result<&Value, PathError> {
+        let path = path.into_path()?;
+        let first = path.segments().first().ok_or(PathError::Root)?;
+
+        let value = self
+            .values
+            .get(&first.to_string())
+            .ok_or_else(|| PathError::NotFound(path.prefix(1)))?;
+
+        walk(value, &path, 1)
+    }
+
+    pub fn get_path_mut(&mut self, path: impl IntoPath) -> Result<&mut Value, PathError> {
+        let path = path.into_path()?;
+        let first = path.segments().first().ok_or(PathError::Root)?;
+
+        let value = self
+            .values
+            .get_mut(&first.to_string())
+            .ok_or_else(|| PathError::NotFound(path.prefix(1)))?;
+
+        walk_mut(value, &path, 1)
+    }
+
+    pub fn get_path_as<T: FromValue>(&self, path: impl IntoPath) -> Result<T, PathError> {
+        let path = path.into_path()?;
+        let value = self.get_path(&path)?;
+
+        T::from_value(value).ok_or_else(|| PathError::TypeMismatch {
+            path: path.to_string(),
+            expected: T::EXPECTED,
+        })
+    }
+
+    pub fn path_exists(&self, path: impl IntoPath) -> bool {
+        self.get_path(path).is_ok()
+    }
+
+    /// See [`Value::set_path`].
+    pub fn set_path(
+        &mut self,
+        path: impl IntoPath,
+        value: Value,
+    ) -> Result<Option<Value>, PathError> {
+        let path = path.into_path()?;
+        if path.is_root() {
+            return Err(PathError::Root);
+        }
+
+        self.with_root(|root| root.set_path(path, value))
+    }
+
+    /// See [`Value::insert_path`].
+    pub fn insert_path(&mut self, path: impl IntoPath, value: Value) -> Result<(), PathError> {
+        let path = path.into_path()?;
+        if path.is_root() {
+            return Err(PathError::Root);
+        }
+
+        self.with_root(|root| root.insert_path(path, value))
+    }
+
+    /// See [`Value::remove_path`].
+    pub fn remove_path(&mut self, path: impl IntoPath) -> Result<Value, PathError> {
+        let path: Path = path.into_path()?;
+        self.with_root(|root| root.remove_path(path))
+    }
+
+    /// Run `f` against the values viewed as a single [`Value::Object`].
+    pub(crate) fn with_root<T>(&mut self, f: impl FnOnce(&mut Value) -> T) -> T {
+        let mut root = Value::Object(std::mem::take(&mut self.values));
+        let result = f(&mut root);
+
+        if let Value::Object(values) = root {
+            self.values = values;
+        }
+
+        result
+    }
+}
@@ -1,21 +1,169 @@
 use crate::store::image::ImageBmc;
 use crate::store::items::ItemsBmc;
 use crate::store::location_metadata::LocationMetadataBmc;
+use crate::store::location_types::LocationTypesBmc;
 use crate::store::locations::LocationsBmc;
 use crate::store::records::RecordsBmc;
+use crate::store::reference::ReferenceBmc;
 use crate::types::params::{
     GetAllRecordPayload, ItemDeletePayload, ItemEditPayload, ItemGetPayload, ItemImagePayload,
     ItemRecordDeletePayload, ItemRecordGetPayload, ItemRecordRegisterPayload,
     ItemRecordUpdatePayload, ItemRegisterPayload, LocationMetadataDeletePayload,
     LocationMetadataGetPayload, LocationMetadataUpdatePayload, LocationMetadateRegisterPayload,
-    LocationRegisterPayload,
+    LocationRegisterPayload, LocationTypeRegisterPayload, LocationTypeUpdatePayload,
 };
-use crate::types::{Item, Items, Location, Locations, Records, RecordsForItem};
-use lib_model::{Error, ModelManager, Result};
+use crate::types::{
+    Item, Items, Location, LocationTypes, Locations, MetadataMigrationReport, MetadataOwner,
+    Records, RecordsForItem, Referenced, ResolvedReference,
+};
+use lib_commons::{ReferenceTarget, ValueStore, ValueStorePatch, Violation, ViolationKind};
+use lib_model::{Error, ModelManager, Result, ValidationPolicy};
 use uuid::Uuid;
 
 pub(crate) mod types;
 
+// How many times a metadata patch is re-applied when the metadata changed in between
+const METADATA_PATCH_RETRIES: usize = 5;
+
+// Note : Checked against the validator of `mm`, if any, with its policy. The rows of its
+//        reference fields have to exist as well
+async fn validate_metadata(mm: &ModelManager, metadata: &ValueStore) -> Result<()> {
+    let Some(validator) = mm.validator() else {
+        return Ok(());
+    };
+
+    let policy = mm.validation_policy();
+    if policy == ValidationPolicy::Off {
+        return Ok(());
+    }
+
+    let mut report = validator.report(metadata)?;
+    for reference in validator.references(metadata)? {
+        if !ReferenceBmc::exists(mm, reference.target(), reference.id()).await? {
+            report.push(Violation::new(
+                reference.path().clone(),
+                ViolationKind::DanglingReference {
+                    target: reference.target(),
+                    id: reference.id(),
+                },
+            ));
+        }
+    }
+
+    if report.is_valid() {
+        return Ok(());
+    }
+
+    match policy {
+        ValidationPolicy::Enforce => Err(Error::ValidationError(Box::new(report))),
+        _ => {
+            validator.warn(metadata, &report);
+            Ok(())
+        }
+    }
+}
+
+// Note : What gets written, e.g. with the computed fields of its schema filled in
+fn materialize_metadata(mm: &ModelManager, metadata: &ValueStore) -> Result<ValueStore> {
+    match mm.validator() {
+        Some(validator) => validator.materialize(metadata),
+        None => Ok(metadata.clone()),
+    }
+}
+
+fn normalize_metadata(mm: &ModelManager, metadata: &ValueStore) -> Result<ValueStore> {
+    match mm.validator() {
+        Some(validator) => validator.normalize(metadata),
+        None => Ok(metadata.clone()),
+    }
+}
+
+async fn patch_metadata(
+    mm: &ModelManager,
+    raw: Option<&str>,
+    patch: &ValueStorePatch,
+) -> Result<String> {
+    let mut metadata = match raw {
+        Some(raw) => serde_json::from_str(raw).map_err(|err| Error::ParseError(err.to_string()))?,
+        None => ValueStore::default(),
+    };
+
+    metadata.apply(patch)?;
+    let metadata = materialize_metadata(mm, &metadata)?;
+    validate_metadata(mm, &metadata).await?;
+
+    Ok(metadata.to_string())
+}
+
+// Note : `None` when there is nothing with that id anymore
+async fn resolve_reference(
+    mm: &ModelManager,
+    target: ReferenceTarget,
+    id: i64,
+) -> Option<Referenced> {
+    match target {
+        ReferenceTarget::Item => ItemsBmc::get(mm, id).await.map(|item| Referenced::Item {
+            name: item.name,
+            location_id: item.location,
+        }),
+        ReferenceTarget::LocationMetadata => LocationMetadataBmc::get(mm, id)
+            .await
+            .map(|metadata| Referenced::LocationMetadata {
+                name: metadata.name,
+            }),
+        ReferenceTarget::Location => {
+            LocationsBmc::get(mm, id)
+                .await
+                .map(|location| Referenced::Location {
+                    location: location.location,
+                    rack: location.rack,
+                    bin: location.bin,
+                })
+        }
+    }
+}
+
+// Note : Nothing to resolve without a validator, as for `validate_metadata`
+async fn resolve_references(
+    mm: &ModelManager,
+    metadata: &ValueStore,
+) -> Result<Vec<ResolvedReference>> {
+    let references = match mm.validator() {
+        Some(validator) => validator.references(metadata)?,
+        None => Vec::new(),
+    };
+
+    let mut resolved = Vec::with_capacity(references.len());
+    for reference in references {
+        let referenced = resolve_reference(mm, reference.target(), reference.id()).await;
+        resolved.push(ResolvedReference::new(
+            reference.path().clone(),
+            reference.target(),
+            reference.id(),
+            referenced,
+        ));
+    }
+
+    Ok(resolved)
+}
+
+fn migrate_metadata_store(mm: &ModelManager, metadata: &ValueStore) -> Result<ValueStore> {
+    match mm.validator() {
+        Some(validator) => validator.migrate(metadata),
+        None => Ok(metadata.clone()),
+    }
+}
+
+// Note : `None` when the metadata is already on the current version of its schema
+fn migrate_raw_metadata(mm: &ModelManager, raw: &str) -> Result<Option<String>> {
+    let metadata: ValueStore =
+        serde_json::from_str(raw).map_err(|err| Error::ParseError(err.to_string()))?;
+
+    let migrated = migrate_metadata_store(mm, &metadata)?;
+
+    Ok((migrated.schema_version() != metadata.schema_version()).then(|| migrated.to_string()))
+}
+
 fn store_image(mm: &ModelManager, data: Vec<u8>) -> Result<String> {
     let key = Uuid::now_v7().to_string();
     mm.image_store().store(&key, data)?;
@@ -40,6 +188,15 @@ async fn register_new_image_or_get_existing(
 
 // region : Item
 pub async fn register_item(mm: &ModelManager, params: ItemRegisterPayload) -> Result<i64> {
+    // Check the metadata before anything is written
+    // Note : Normalizing materializes as well
+    let metadata = if params.fills_defaults() {
+        normalize_metadata(mm, params.metadata_store())?
+    } else {
+        materialize_metadata(mm, params.metadata_store())?
+    };
+    validate_metadata(mm, &metadata).await?;
+
     // Create the image first
     let image_id = register_new_image_or_get_existing(mm, params.image_data()).await?;
 
@@ -55,7 +212,7 @@ pub async fn register_item(mm: &ModelManager, params: ItemRegisterPayload) -> Re
 
     // Create the item entity.
     // Get the newly create item's id
-    let metadata = params.metadata();
+    let metadata = metadata.to_string();
     let item_id = ItemsBmc::create(mm, params.name(), &metadata, image_id, location_id).await?;
     // return the id
     Ok(item_id)
@@ -70,8 +227,20 @@ pub async fn get_items(mm: &ModelManager, params: ItemGetPayload) -> Result<Item
         None => ItemsBmc::get_all(mm).await?,
     }
     .into_iter()
-    .map(|item| item.into())
-    .collect();
+    .map(|mut item| {
+        if params.migrates() {
+            item.item_metadata.0 = migrate_metadata_store(mm, &item.item_metadata)?;
+        }
+        Ok(item.into())
+    })
+    .collect::<Result<_>>()?;
+
+    if params.resolves_references() {
+        for item in result.iter_mut() {
+            let references = resolve_references(mm, item.metadata()).await?;
+            item.with_references(references);
+        }
+    }
 
     if params.with_image() {
         result.iter_mut().for_each(|item| {
@@ -88,10 +257,8 @@ pub async fn get_items(mm: &ModelManager, params: ItemGetPayload) -> Result<Item
 }
 
 pub async fn edit_item(mm: &ModelManager, params: ItemEditPayload) -> Result<()> {
-    if let Some(metadata) = params.metadata() {
-        ItemsBmc::update_metadata(mm, params.id(), &metadata.to_string())
-            .await
-            .ok_or(Error::ItemNotFound(params.id()))?;
+    if let Some(patch) = params.metadata() {
+        patch_item_metadata(mm, params.id(), patch).await?;
     }
 
     if let Some(name) = params.name() {
@@ -129,6 +296,43 @@ pub async fn edit_item(mm: &ModelManager, params: ItemEditPayload) -> Result<()>
     Ok(())
 }
 
+// Note : The patch is applied on the latest stored metadata and only written back if nobody
+//        changed it in the meantime, otherwise it is retried against the new one.
+async fn patch_item_metadata(mm: &ModelManager, id: i64, patch: &ValueStorePatch) -> Result<()> {
+    for _ in 0..METADATA_PATCH_RETRIES {
+        let current = ItemsBmc::get_raw_metadata(mm, id)
+            .await?
+            .ok_or(Error::ItemNotFound(id))?;
+
+        let patched = patch_metadata(mm, Some(&current), patch).await?;
+
+        if ItemsBmc::swap_metadata(mm, id, &patched, &current).await? {
+            return Ok(());
+        }
+    }
+
+    Err(Error::ConcurrentMetadataUpdate(id))
+}
+
+// Note : Written back the same way as a patch, see `patch_item_metadata`
+async fn migrate_item_metadata(mm: &ModelManager, id: i64) -> Result<bool> {
+    for _ in 0..METADATA_PATCH_RETRIES {
+        let current = ItemsBmc::get_raw_metadata(mm, id)
+            .await?
+            .ok_or(Error::ItemNotFound(id))?;
+
+        let Some(migrated) = migrate_raw_metadata(mm, &current)? else {
+            return Ok(false);
+        };
+
+        if ItemsBmc::swap_metadata(mm, id, &migrated, &current).await? {
+            return Ok(true);
+        }
+    }
+
+    Err(Error::ConcurrentMetadataUpdate(id))
+}
+
 pub async fn remove_item(mm: &ModelManager, params: ItemDeletePayload) -> Result<()> {
     ItemsBmc::delete(mm, params.id())
         .await
@@ -209,8 +413,41 @@ pub async fn register_new_location(
     mm: &ModelManager,
     params: LocationMetadateRegisterPayload,
 ) -> Result<i64> {
+    let default_schema = match params.location_type() {
+        Some(location_type) => {
+            LocationTypesBmc::get(mm, location_type)
+                .await
+                .ok_or(Error::LocationTypeNotFound(location_type.to_string()))?
+                .default_schema
+        }
+        None => None,
+    };
+
+    let metadata = match (params.metadata(), default_schema) {
+        // Note : Filled with the defaults of the schema as well, so every location of the type
+        //        starts with the same shape
+        (metadata, Some(schema)) if metadata.is_none_or(|m| m.schema_name().is_none()) => {
+            let metadata = metadata.cloned().unwrap_or_default();
+            let properties = metadata.object_properties_schemas().as_ref().clone();
+            let metadata = ValueStore::builder()
+                .with_schema(schema)
+                .with_object_properties_schemas(properties)
+                .with_values(metadata.get_all().clone())
+                .build();
+            Some(normalize_metadata(mm, &metadata)?)
+        }
+        (Some(metadata), _) => Some(materialize_metadata(mm, metadata)?),
+        (None, _) => None,
+    };
+
+    if let Some(metadata) = &metadata {
+        validate_metadata(mm, metadata).await?;
+    }
+
+    let metadata = metadata.map(|m| m.to_string());
     let id =
-        LocationMetadataBmc::create(mm, params.name(), params.metadata_as_str().as_deref()).await?;
+        LocationMetadataBmc::create(mm, params.name(), metadata.as_deref(), params.location_type())
+            .await?;
 
     Ok(id)
 }
@@ -219,10 +456,16 @@ pub async fn get_location(
     mm: &ModelManager,
     params: LocationMetadataGetPayload,
 ) -> Result<Location> {
-    let result = LocationMetadataBmc::get(mm, params.id())
+    let mut result = LocationMetadataBmc::get(mm, params.id())
         .await
         .ok_or(Error::LocationMetadataNotFound(params.id()))?;
 
+    if params.migrates()
+        && let Some(metadata) = result.metadata.as_mut()
+    {
+        metadata.0 = migrate_metadata_store(mm, metadata)?;
+    }
+
     Ok(result.into())
 }
 
@@ -241,13 +484,58 @@ pub async fn edit_location(mm: &ModelManager, params: LocationMetadataUpdatePayl
         LocationMetadataBmc::update_name(mm, params.id(), name).await?;
     }
 
-    if let Some(metadata) = params.metadata() {
-        LocationMetadataBmc::update_metadata(mm, params.id(), metadata.as_deref()).await?;
+    match params.metadata() {
+        Some(Some(patch)) => patch_location_metadata(mm, params.id(), patch).await?,
+        Some(None) => LocationMetadataBmc::update_metadata(mm, params.id(), None).await?,
+        None => {}
     }
 
     Ok(())
 }
 
+// Note : Same as `patch_item_metadata`, a location without metadata is patched from an empty one
+async fn patch_location_metadata(
+    mm: &ModelManager,
+    id: i64,
+    patch: &ValueStorePatch,
+) -> Result<()> {
+    for _ in 0..METADATA_PATCH_RETRIES {
+        let current = LocationMetadataBmc::get_raw_metadata(mm, id)
+            .await?
+            .ok_or(Error::LocationMetadataNotFound(id))?;
+
+        let patched = patch_metadata(mm, current.as_deref(), patch).await?;
+
+        if LocationMetadataBmc::swap_metadata(mm, id, Some(&patched), current.as_deref()).await? {
+            return Ok(());
+        }
+    }
+
+    Err(Error::ConcurrentMetadataUpdate(id))
+}
+
+async fn migrate_location_metadata(mm: &ModelManager, id: i64) -> Result<bool> {
+    for _ in 0..METADATA_PATCH_RETRIES {
+        let Some(current) = LocationMetadataBmc::get_raw_metadata(mm, id)
+            .await?
+            .ok_or(Error::LocationMetadataNotFound(id))?
+        else {
+            // Nothing to migrate
+            return Ok(false);
+        };
+
+        let Some(migrated) = migrate_raw_metadata(mm, &current)? else {
+            return Ok(false);
+        };
+
+        if LocationMetadataBmc::swap_metadata(mm, id, Some(&migrated), Some(&current)).await? {
+            return Ok(true);
+        }
+    }
+
+    Err(Error::ConcurrentMetadataUpdate(id))
+}
+
 #[allow(unused)]
 pub async fn remove_location(
     mm: &ModelManager,
@@ -259,14 +547,98 @@ pub async fn remove_location(
 
 // endregion
 
+// region : Location Types
+pub async fn register_location_type(
+    mm: &ModelManager,
+    params: LocationTypeRegisterPayload,
+) -> Result<()> {
+    LocationTypesBmc::create(mm, params.name(), params.default_schema()).await
+}
+
+pub async fn list_location_types(mm: &ModelManager) -> Result<LocationTypes> {
+    let result: Vec<_> = LocationTypesBmc::get_all(mm)
+        .await?
+        .into_iter()
+        .map(|i| i.into())
+        .collect();
+
+    Ok(result.into())
+}
+
+// Note : Only new locations get the schema, the existing ones are left as they are
+pub async fn update_location_type(
+    mm: &ModelManager,
+    params: LocationTypeUpdatePayload,
+) -> Result<()> {
+    if !LocationTypesBmc::update_default_schema(mm, params.name(), params.default_schema()).await? {
+        return Err(Error::LocationTypeNotFound(params.name().to_string()));
+    }
+
+    Ok(())
+}
+
+pub async fn remove_location_type(mm: &ModelManager, name: &str) -> Result<()> {
+    LocationTypesBmc::delete(mm, name).await
+}
+// endregion
+
+// region : Metadata migration
+
+/// Bring the metadata of every item and location up to the current version of its schema,
+/// see `lib_schema::Schema::evolve`. Goes through the validator of `mm`, nothing is migrated
+/// without one.
+///
+/// Only fails if the rows can't be listed, the ones that can't be migrated are in the report.
+pub async fn migrate_metadata(mm: &ModelManager) -> Result<MetadataMigrationReport> {
+    let mut report = MetadataMigrationReport::default();
+
+    let items = ItemsBmc::get_all(mm).await?;
+    let locations = LocationMetadataBmc::get_all(mm).await?;
+
+    let owners = items
+        .iter()
+        .map(|item| MetadataOwner::Item(item.id))
+        .chain(locations.iter().map(|l| MetadataOwner::Location(l.id)));
+
+    for owner in owners {
+        let migrated = match owner {
+            MetadataOwner::Item(id) => migrate_item_metadata(mm, id).await,
+            MetadataOwner::Location(id) => migrate_location_metadata(mm, id).await,
+        };
+
+        match migrated {
+            Ok(true) => report.migrated.push(owner),
+            Ok(false) => {}
+            Err(err) => report.errors.push((owner, err)),
+        }
+    }
+
+    Ok(report)
+}
+
+// endregion
+
 #[cfg(test)]
 mod tests {
     use crate::exec::register_item;
     use crate::exec::store_image;
+    use crate::exec::{edit_item, edit_location, get_location, migrate_metadata};
+    use crate::exec::resolve_references;
+    use crate::exec::{register_location_type, register_new_location};
     use crate::store::items::ItemsBmc;
-    use crate::types::params::{ItemImagePayload, ItemRegisterPayload, LocationRegisterPayload};
-    use lib_commons::ValueStore;
+    use crate::store::location_metadata::LocationMetadataBmc;
+    use crate::types::params::{
+        ItemEditPayload, ItemImagePayload, ItemRegisterPayload, LocationMetadataGetPayload,
+        LocationMetadataUpdatePayload, LocationMetadateRegisterPayload, LocationRegisterPayload,
+        LocationTypeRegisterPayload,
+    };
+    use crate::types::{MetadataOwner, Referenced};
+    use lib_commons::{Decimal, MigrationStep, Value, ValueStore, ViolationKind, get};
+    use lib_schema::{Field, FieldType, ReferenceTarget, Registry, Schema, SchemaManager};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use lib_model::_dev_utils::get_dev_env;
+    use lib_model::{Error, ValidationPolicy};
     use serde_json::json;
     use serial_test::serial;
     use std::fs;
@@ -323,4 +695,467 @@ mod tests {
         //Cleanup
         mm.image_store().remove_last().unwrap();
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_edit_item_metadata_patch() {
+        let mm = get_dev_env().await.unwrap();
+
+        let metadata = ValueStore::builder()
+            .with_schema("TestSchema")
+            .string("a", "this is a string")
+            .number("b", 10)
+            .build();
+
+        let id = ItemsBmc::create(&mm, "Patched", &metadata.to_string(), 1, 1)
+            .await
+            .unwrap();
+
+        let patch = serde_json::from_value(json!({
+            "type": "merge",
+            "patch": { "a": null, "c": { "d": true } }
+        }))
+        .unwrap();
+
+        edit_item(&mm, ItemEditPayload::new(id, None, Some(patch), None, None))
+            .await
+            .unwrap();
+
+        let metadata = ItemsBmc::get(&mm, id).await.unwrap().item_metadata.0;
+
+        assert_eq!(metadata.schema_name().as_deref(), Some("TestSchema"));
+        assert!(metadata.get("a").is_none());
+        assert_eq!(get!(metadata, number, b).unwrap().as_u64(), Some(10));
+        assert!(metadata.get_path_as::<bool>("c.d").unwrap());
+
+        // A failing patch leaves the metadata untouched
+        let patch = serde_json::from_value(json!({
+            "type": "json",
+            "patch": [{ "op": "remove", "path": "/missing" }]
+        }))
+        .unwrap();
+
+        assert!(
+            edit_item(&mm, ItemEditPayload::new(id, None, Some(patch), None, None))
+                .await
+                .is_err()
+        );
+        assert!(ItemsBmc::get(&mm, id).await.unwrap().item_metadata.get("b").is_some());
+
+        ItemsBmc::delete(&mm, id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_edit_location_metadata_patch() {
+        let mm = get_dev_env().await.unwrap();
+
+        let id = LocationMetadataBmc::create(&mm, "Empty Container", None, None)
+            .await
+            .unwrap();
+
+        let patch = serde_json::from_value(json!({
+            "type": "json",
+            "patch": [{ "op": "add", "path": "/racks", "value": [{ "name": "Rack Uno" }] }]
+        }))
+        .unwrap();
+
+        edit_location(&mm, LocationMetadataUpdatePayload::new(id, None, Some(Some(patch))))
+            .await
+            .unwrap();
+
+        let metadata = LocationMetadataBmc::get(&mm, id).await.unwrap().metadata.unwrap().0;
+        assert_eq!(get!(metadata, string, racks.0.name).unwrap(), "Rack Uno");
+
+        edit_location(&mm, LocationMetadataUpdatePayload::new(id, None, Some(None)))
+            .await
+            .unwrap();
+
+        assert!(LocationMetadataBmc::get(&mm, id).await.unwrap().metadata.is_none());
+
+        LocationMetadataBmc::delete(&mm, id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_register_item_with_defaults() {
+        let schema = Schema::create(
+            "Bolt",
+            vec![
+                Field::create("size", FieldType::String, true, Value::Null),
+                Field::create("count", FieldType::Number, false, Value::Number(100.into())),
+            ],
+        );
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager.register("Bolt", schema).unwrap();
+
+        let mm = get_dev_env()
+            .await
+            .unwrap()
+            .with_validator(Arc::new(schema_manager), ValidationPolicy::Enforce);
+
+        let metadata = ValueStore::builder()
+            .with_schema("Bolt")
+            .string("size", "M6")
+            .build();
+
+        let item_register_payload = ItemRegisterPayload::new(
+            "Bolts",
+            metadata,
+            ItemImagePayload::Existing(1),
+            LocationRegisterPayload::Existing(1),
+        )
+        .fill_defaults();
+
+        let id = register_item(&mm, item_register_payload).await.unwrap();
+
+        let metadata = ItemsBmc::get(&mm, id).await.unwrap().item_metadata.0;
+        assert_eq!(metadata.get_path_as::<u64>("count").unwrap(), 100);
+
+        ItemsBmc::delete(&mm, id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_migrate_metadata() {
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager
+            .register(
+                "Crate",
+                Schema::create(
+                    "Crate",
+                    vec![Field::create("qty", FieldType::String, true, Value::Null)],
+                ),
+            )
+            .unwrap();
+        let schema_manager = Arc::new(schema_manager);
+
+        let mm = get_dev_env()
+            .await
+            .unwrap()
+            .with_validator(schema_manager.clone(), ValidationPolicy::Off);
+
+        let metadata = ValueStore::builder()
+            .with_schema("Crate")
+            .string("qty", "12")
+            .build();
+        let item_id = ItemsBmc::create(&mm, "Crate of bolts", &metadata.to_string(), 1, 1)
+            .await
+            .unwrap();
+        let location_id = LocationMetadataBmc::create(
+            &mm,
+            "Crate shelf",
+            Some(&metadata.to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+
+        schema_manager
+            .evolve(
+                "Crate",
+                vec![
+                    MigrationStep::rename("qty", "quantity"),
+                    MigrationStep::retype("quantity", FieldType::Number),
+                ],
+            )
+            .unwrap();
+
+        // Lazily, the stored metadata stays on the first version
+        let location = get_location(&mm, LocationMetadataGetPayload::new(location_id).migrate())
+            .await
+            .unwrap();
+        let metadata = location.metadata().as_ref().unwrap();
+        assert_eq!(metadata.get_path_as::<u64>("quantity").unwrap(), 12);
+        assert_eq!(metadata.schema_version(), Some(2));
+
+        let stored = LocationMetadataBmc::get(&mm, location_id).await.unwrap();
+        assert_eq!(stored.metadata.unwrap().schema_version(), None);
+
+        // In bulk
+        let report = migrate_metadata(&mm).await.unwrap();
+        assert!(report.migrated().contains(&MetadataOwner::Item(item_id)));
+        assert!(report.migrated().contains(&MetadataOwner::Location(location_id)));
+
+        let metadata = ItemsBmc::get(&mm, item_id).await.unwrap().item_metadata.0;
+        assert_eq!(metadata.schema_version(), Some(2));
+        assert_eq!(metadata.get_path_as::<u64>("quantity").unwrap(), 12);
+        assert!(metadata.get("qty").is_none());
+
+        // Nothing left to do the second time
+        let report = migrate_metadata(&mm).await.unwrap();
+        assert!(!report.migrated().contains(&MetadataOwner::Item(item_id)));
+
+        ItemsBmc::delete(&mm, item_id).await.unwrap();
+        LocationMetadataBmc::delete(&mm, location_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_metadata_validation_policy() {
+        let warned = Arc::new(AtomicUsize::new(0));
+        let counter = warned.clone();
+        let schema_manager = SchemaManager::from_registry(Registry::new())
+            .with_warning_handler(move |_, _| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+        schema_manager
+            .register(
+                "Lamp",
+                Schema::create(
+                    "Lamp",
+                    vec![
+                        Field::create("watts", FieldType::Number, true, Value::Null),
+                        Field::create("kilowatts", FieldType::Number, false, Value::Null)
+                            .with_expression("watts / 1000"),
+                    ],
+                ),
+            )
+            .unwrap();
+        let schema_manager = Arc::new(schema_manager);
+
+        let mm = get_dev_env()
+            .await
+            .unwrap()
+            .with_validator(schema_manager.clone(), ValidationPolicy::Enforce);
+
+        let register = |metadata: ValueStore| {
+            ItemRegisterPayload::new(
+                "Lamp",
+                metadata,
+                ItemImagePayload::Existing(1),
+                LocationRegisterPayload::Existing(1),
+            )
+        };
+
+        let invalid = ValueStore::builder()
+            .with_schema("Lamp")
+            .string("watts", "bright")
+            .build();
+        match register_item(&mm, register(invalid.clone())).await {
+            Err(Error::ValidationError(report)) => {
+                let paths: Vec<String> =
+                    report.violations().iter().map(|v| v.path().to_string()).collect();
+                // The kilowatts can't be computed from it either
+                assert_eq!(paths, ["/watts", "/kilowatts"]);
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+
+        let valid = ValueStore::builder().with_schema("Lamp").number("watts", 60).build();
+        let id = register_item(&mm, register(valid)).await.unwrap();
+
+        // A patch leaving the metadata invalid is not written
+        let patch = serde_json::from_value(json!({
+            "type": "merge",
+            "patch": { "watts": null }
+        }))
+        .unwrap();
+        assert!(matches!(
+            edit_item(&mm, ItemEditPayload::new(id, None, Some(patch), None, None)).await,
+            Err(Error::ValidationError(_))
+        ));
+        let metadata = ItemsBmc::get(&mm, id).await.unwrap().item_metadata.0;
+        assert_eq!(metadata.get_path_as::<u64>("watts").unwrap(), 60);
+        // Computed on write
+        assert_eq!(
+            metadata.get_path_as::<Decimal>("kilowatts").unwrap(),
+            Decimal::new(6, 2)
+        );
+        ItemsBmc::delete(&mm, id).await.unwrap();
+        // The image store can only be opened once
+        drop(mm);
+
+        // Written anyway when only warning
+        let mm = get_dev_env()
+            .await
+            .unwrap()
+            .with_validator(schema_manager, ValidationPolicy::Warn);
+        assert_eq!(warned.load(Ordering::Relaxed), 0);
+        let id = register_item(&mm, register(invalid)).await.unwrap();
+        assert_eq!(warned.load(Ordering::Relaxed), 1);
+        ItemsBmc::delete(&mm, id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reference_fields() {
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager
+            .register(
+                "Spare",
+                Schema::create(
+                    "Spare",
+                    vec![
+                        Field::create(
+                            "for_item",
+                            FieldType::Reference(ReferenceTarget::Item),
+                            true,
+                            Value::Null,
+                        ),
+                        Field::create(
+                            "shelf",
+                            FieldType::Reference(ReferenceTarget::Location),
+                            false,
+                            Value::Null,
+                        ),
+                    ],
+                ),
+            )
+            .unwrap();
+        schema_manager
+            .register("Tool", Schema::create("Tool", Vec::new()))
+            .unwrap();
+        let schema_manager = Arc::new(schema_manager);
+
+        let mm = get_dev_env()
+            .await
+            .unwrap()
+            .with_validator(schema_manager, ValidationPolicy::Enforce);
+
+        let register = |name: &str, metadata: ValueStore| {
+            ItemRegisterPayload::new(
+                name,
+                metadata,
+                ItemImagePayload::Existing(1),
+                LocationRegisterPayload::Existing(1),
+            )
+        };
+
+        let tool = ValueStore::builder().with_schema("Tool").build();
+        let item_id = register_item(&mm, register("Drill", tool))
+            .await
+            .unwrap();
+
+        let dangling = ValueStore::builder()
+            .with_schema("Spare")
+            .number("for_item", i64::MAX)
+            .number("shelf", 1)
+            .build();
+        match register_item(&mm, register("Drill bit", dangling)).await {
+            Err(Error::ValidationError(report)) => {
+                let violations = report.violations();
+                assert_eq!(violations.len(), 1);
+                assert_eq!(violations[0].path().to_string(), "/for_item");
+                assert_eq!(
+                    violations[0].kind(),
+                    &ViolationKind::DanglingReference {
+                        target: ReferenceTarget::Item,
+                        id: i64::MAX
+                    }
+                );
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+
+        let metadata = ValueStore::builder()
+            .with_schema("Spare")
+            .number("for_item", item_id)
+            .number("shelf", 1)
+            .build();
+        let spare_id = register_item(&mm, register("Drill bit", metadata.clone()))
+            .await
+            .unwrap();
+
+        let references = resolve_references(&mm, &metadata).await.unwrap();
+        assert_eq!(references.len(), 2);
+        assert_eq!(
+            references[0].referenced(),
+            Some(&Referenced::Item {
+                name: "Drill".to_string(),
+                location_id: 1
+            })
+        );
+        assert!(matches!(
+            references[1].referenced(),
+            Some(Referenced::Location { .. })
+        ));
+
+        // Still resolved once deleted, just to nothing
+        ItemsBmc::delete(&mm, item_id).await.unwrap();
+        let references = resolve_references(&mm, &metadata).await.unwrap();
+        assert_eq!(references[0].id(), item_id);
+        assert!(references[0].referenced().is_none());
+
+        ItemsBmc::delete(&mm, spare_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_location_type_default_schema() {
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager
+            .register(
+                "Shelving",
+                Schema::create(
+                    "Shelving",
+                    vec![
+                        Field::create("levels", FieldType::Number, true, Value::Number(4.into())),
+                        Field::create("label", FieldType::String, false, Value::Null),
+                    ],
+                ),
+            )
+            .unwrap();
+
+        let mm = get_dev_env()
+            .await
+            .unwrap()
+            .with_validator(Arc::new(schema_manager), ValidationPolicy::Enforce);
+
+        register_location_type(
+            &mm,
+            LocationTypeRegisterPayload::new("shelving", Some("Shelving")),
+        )
+        .await
+        .unwrap();
+
+        // Gets the schema of its type, with its defaults
+        let id = register_new_location(
+            &mm,
+            LocationMetadateRegisterPayload::new("Shelving 1", None).with_location_type("shelving"),
+        )
+        .await
+        .unwrap();
+        let location = get_location(&mm, LocationMetadataGetPayload::new(id)).await.unwrap();
+        assert_eq!(location.location_type(), Some("shelving"));
+        let metadata = location.metadata().clone().unwrap();
+        assert_eq!(metadata.schema_name().as_deref(), Some("Shelving"));
+        assert_eq!(metadata.get_path_as::<u64>("levels").unwrap(), 4);
+
+        let invalid = ValueStore::builder().string("levels", "many").build();
+        assert!(matches!(
+            register_new_location(
+                &mm,
+                LocationMetadateRegisterPayload::new("Shelving 2", Some(invalid))
+                    .with_location_type("shelving"),
+            )
+            .await,
+            Err(Error::ValidationError(_))
+        ));
+        assert!(matches!(
+            register_new_location(
+                &mm,
+                LocationMetadateRegisterPayload::new("Shed 1", None).with_location_type("shed"),
+            )
+            .await,
+            Err(Error::LocationTypeNotFound(name)) if name == "shed"
+        ));
+
+        // Edits are checked the same way
+        let patch = serde_json::from_value(json!({
+            "type": "merge",
+            "patch": { "levels": "many" }
+        }))
+        .unwrap();
+        assert!(matches!(
+            edit_location(
+                &mm,
+                LocationMetadataUpdatePayload::new(id, None, Some(Some(patch)))
+            )
+            .await,
+            Err(Error::ValidationError(_))
+        ));
+
+        LocationMetadataBmc::delete(&mm, id).await.unwrap();
+    }
 }
@@ -1,8 +1,9 @@
 use crate::store::items::RawItem;
 use crate::store::location_metadata::RawLocationMetadata;
+use crate::store::location_types::RawLocationType;
 use crate::store::records::{RawRecord, TransactionType};
 use chrono::{DateTime, Utc};
-use lib_commons::ValueStore;
+use lib_commons::{Path, ReferenceTarget, ValueStore};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -10,7 +11,7 @@ pub mod params {
     use crate::exec::types::utils::{Pagination, Timeframe};
     use crate::store::records::TransactionType;
     use chrono::Utc;
-    use lib_commons::ValueStore;
+    use lib_commons::{ValueStore, ValueStorePatch};
     use std::sync::Arc;
 
     pub struct ItemRegisterPayload {
@@ -18,6 +19,7 @@ pub mod params {
         metadata: ValueStore,
         image_data: ItemImagePayload,
         location: LocationRegisterPayload,
+        fill_defaults: bool,
     }
 
     impl ItemRegisterPayload {
@@ -32,8 +34,21 @@ pub mod params {
                 metadata: metadata.into(),
                 image_data,
                 location,
+                fill_defaults: false,
             }
         }
+
+        /// Fill the missing metadata fields with the defaults of its schema before storing it,
+        /// through the validator of the `ModelManager`.
+        pub fn fill_defaults(mut self) -> Self {
+            self.fill_defaults = true;
+            self
+        }
+
+        pub fn fills_defaults(&self) -> bool {
+            self.fill_defaults
+        }
+
         pub fn image_data(&self) -> ItemImagePayload {
             self.image_data.clone()
         }
@@ -49,6 +64,10 @@ pub mod params {
         pub fn metadata(&self) -> String {
             self.metadata.to_string()
         }
+
+        pub fn metadata_store(&self) -> &ValueStore {
+            &self.metadata
+        }
     }
 
     #[derive(Debug, Clone)]
@@ -71,9 +90,33 @@ pub mod params {
     pub struct ItemGetPayload {
         with_image: bool,
         pagination: Option<Pagination>,
+        migrate: bool,
+        resolve_references: bool,
     }
 
     impl ItemGetPayload {
+        /// Bring the metadata up to the current version of its schema, not what is stored.
+        ///
+        /// Note : Through the validator of the `ModelManager`, left as is without one
+        pub fn migrate(mut self) -> Self {
+            self.migrate = true;
+            self
+        }
+
+        pub fn migrates(&self) -> bool {
+            self.migrate
+        }
+
+        /// Load what the reference fields of the metadata point to, see [`crate::types::Item`].
+        pub fn resolve_references(mut self) -> Self {
+            self.resolve_references = true;
+            self
+        }
+
+        pub fn resolves_references(&self) -> bool {
+            self.resolve_references
+        }
+
         pub fn pagination(&self) -> &Option<Pagination> {
             &self.pagination
         }
@@ -211,17 +254,33 @@ pub mod params {
     pub struct ItemEditPayload {
         id: i64,
         name: Option<String>,
-        metadata: Option<ValueStore>,
+        metadata: Option<ValueStorePatch>,
         image: Option<ItemImagePayload>,
         location: Option<ItemLocationEditPayload>,
     }
 
     impl ItemEditPayload {
+        pub fn new(
+            id: i64,
+            name: Option<String>,
+            metadata: Option<ValueStorePatch>,
+            image: Option<ItemImagePayload>,
+            location: Option<ItemLocationEditPayload>,
+        ) -> Self {
+            ItemEditPayload {
+                id,
+                name,
+                metadata,
+                image,
+                location,
+            }
+        }
+
         pub fn id(&self) -> i64 {
             self.id
         }
 
-        pub fn metadata(&self) -> &Option<ValueStore> {
+        pub fn metadata(&self) -> &Option<ValueStorePatch> {
             &self.metadata
         }
 
@@ -252,13 +311,37 @@ pub mod params {
     pub struct LocationMetadateRegisterPayload {
         name: String,
         metadata: Option<ValueStore>,
+        location_type: Option<String>,
     }
 
     impl LocationMetadateRegisterPayload {
+        pub fn new(name: &str, metadata: Option<ValueStore>) -> Self {
+            LocationMetadateRegisterPayload {
+                name: name.to_string(),
+                metadata,
+                location_type: None,
+            }
+        }
+
+        /// The metadata gets the default schema of the type, if it has one and the metadata
+        /// does not say otherwise.
+        pub fn with_location_type(mut self, location_type: &str) -> Self {
+            self.location_type = Some(location_type.to_string());
+            self
+        }
+
         pub fn name(&self) -> &str {
             self.name.as_ref()
         }
 
+        pub fn location_type(&self) -> Option<&str> {
+            self.location_type.as_deref()
+        }
+
+        pub fn metadata(&self) -> Option<&ValueStore> {
+            self.metadata.as_ref()
+        }
+
         pub fn metadata_as_str(&self) -> Option<String> {
             self.metadata.as_ref().map(|m| m.to_string())
         }
@@ -266,9 +349,24 @@ pub mod params {
 
     pub struct LocationMetadataGetPayload {
         id: i64,
+        migrate: bool,
     }
 
     impl LocationMetadataGetPayload {
+        pub fn new(id: i64) -> Self {
+            LocationMetadataGetPayload { id, migrate: false }
+        }
+
+        /// Same as [`ItemGetPayload::migrate`]
+        pub fn migrate(mut self) -> Self {
+            self.migrate = true;
+            self
+        }
+
+        pub fn migrates(&self) -> bool {
+            self.migrate
+        }
+
         pub fn id(&self) -> i64 {
             self.id
         }
@@ -277,10 +375,19 @@ pub mod params {
     pub struct LocationMetadataUpdatePayload {
         id: i64,
         name: Option<String>,
-        metadata: Option<Option<ValueStore>>,
+        /// `Some(None)` removes the metadata
+        metadata: Option<Option<ValueStorePatch>>,
     }
 
     impl LocationMetadataUpdatePayload {
+        pub fn new(
+            id: i64,
+            name: Option<String>,
+            metadata: Option<Option<ValueStorePatch>>,
+        ) -> Self {
+            LocationMetadataUpdatePayload { id, name, metadata }
+        }
+
         pub fn id(&self) -> i64 {
             self.id
         }
@@ -289,10 +396,8 @@ pub mod params {
             self.name.as_deref()
         }
 
-        pub fn metadata(&self) -> Option<Option<String>> {
-            self.metadata
-                .as_ref()
-                .map(|i| i.as_ref().map(|m| m.to_string()))
+        pub fn metadata(&self) -> Option<Option<&ValueStorePatch>> {
+            self.metadata.as_ref().map(|i| i.as_ref())
         }
     }
 
@@ -305,6 +410,51 @@ pub mod params {
             self.id
         }
     }
+
+    pub struct LocationTypeRegisterPayload {
+        name: String,
+        default_schema: Option<String>,
+    }
+
+    impl LocationTypeRegisterPayload {
+        pub fn new(name: &str, default_schema: Option<&str>) -> Self {
+            LocationTypeRegisterPayload {
+                name: name.to_string(),
+                default_schema: default_schema.map(str::to_string),
+            }
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        pub fn default_schema(&self) -> Option<&str> {
+            self.default_schema.as_deref()
+        }
+    }
+
+    pub struct LocationTypeUpdatePayload {
+        name: String,
+        /// `None` removes the default schema
+        default_schema: Option<String>,
+    }
+
+    impl LocationTypeUpdatePayload {
+        pub fn new(name: &str, default_schema: Option<&str>) -> Self {
+            LocationTypeUpdatePayload {
+                name: name.to_string(),
+                default_schema: default_schema.map(str::to_string),
+            }
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        pub fn default_schema(&self) -> Option<&str> {
+            self.default_schema.as_deref()
+        }
+    }
 }
 
 pub mod utils {
@@ -423,6 +573,8 @@ pub struct Item {
     image_key: String,
     location_name: Option<String>,
     location_id: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    references: Vec<ResolvedReference>,
 }
 
 impl Item {
@@ -453,6 +605,75 @@ impl Item {
     pub fn location_id(&self) -> i64 {
         self.location_id
     }
+
+    /// Only loaded when asked for, see [`params::ItemGetPayload::resolve_references`].
+    pub fn references(&self) -> &[ResolvedReference] {
+        &self.references
+    }
+
+    pub(crate) fn with_references(&mut self, references: Vec<ResolvedReference>) {
+        self.references = references;
+    }
+}
+
+/// A reference field of some metadata along with what it points to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedReference {
+    path: Path,
+    target: ReferenceTarget,
+    id: i64,
+    /// `None` when the row was deleted since
+    referenced: Option<Referenced>,
+}
+
+impl ResolvedReference {
+    pub(crate) fn new(
+        path: Path,
+        target: ReferenceTarget,
+        id: i64,
+        referenced: Option<Referenced>,
+    ) -> Self {
+        ResolvedReference {
+            path,
+            target,
+            id,
+            referenced,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn target(&self) -> ReferenceTarget {
+        self.target
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn referenced(&self) -> Option<&Referenced> {
+        self.referenced.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Referenced {
+    Item {
+        name: String,
+        location_id: i64,
+    },
+    LocationMetadata {
+        name: String,
+    },
+    Location {
+        /// The name of its location metadata
+        location: String,
+        rack: Option<String>,
+        bin: Option<String>,
+    },
 }
 
 impl From<RawItem> for Item {
@@ -531,6 +752,7 @@ pub struct Location {
     id: i64,
     name: String,
     metadata: Option<ValueStore>,
+    location_type: Option<String>,
 }
 
 impl Location {
@@ -545,6 +767,10 @@ impl Location {
     pub fn metadata(&self) -> &Option<ValueStore> {
         &self.metadata
     }
+
+    pub fn location_type(&self) -> Option<&str> {
+        self.location_type.as_deref()
+    }
 }
 
 impl From<RawLocationMetadata> for Location {
@@ -553,6 +779,66 @@ impl From<RawLocationMetadata> for Location {
             id: value.id,
             name: value.name,
             metadata: value.metadata.map(|m| m.0),
+            location_type: value.location_type,
         }
     }
-}
\ No newline at end of file
+}
+
+pub type LocationTypes = Arc<[LocationType]>;
+
+/// A kind of location, e.g. container or hall, see
+/// [`params::LocationMetadateRegisterPayload::with_location_type`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocationType {
+    name: String,
+    /// The schema new locations of this type get
+    default_schema: Option<String>,
+}
+
+impl LocationType {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn default_schema(&self) -> Option<&str> {
+        self.default_schema.as_deref()
+    }
+}
+
+impl From<RawLocationType> for LocationType {
+    fn from(value: RawLocationType) -> Self {
+        LocationType {
+            name: value.name,
+            default_schema: value.default_schema,
+        }
+    }
+}
+
+/// Whose metadata a [`MetadataMigrationReport`] entry is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataOwner {
+    Item(i64),
+    Location(i64),
+}
+
+/// What [`crate::exec::migrate_metadata`] did, one failing row does not stop the others.
+#[derive(Debug, Default)]
+pub struct MetadataMigrationReport {
+    pub(crate) migrated: Vec<MetadataOwner>,
+    pub(crate) errors: Vec<(MetadataOwner, lib_model::Error)>,
+}
+
+impl MetadataMigrationReport {
+    /// The rows that were written back, the ones already up to date are left out.
+    pub fn migrated(&self) -> &[MetadataOwner] {
+        &self.migrated
+    }
+
+    pub fn errors(&self) -> &[(MetadataOwner, lib_model::Error)] {
+        &self.errors
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
@@ -143,6 +143,44 @@ impl ItemsBmc {
         Some(())
     }
 
+    /// The metadata as it is stored, used as the expected value of [`ItemsBmc::swap_metadata`].
+    pub async fn get_raw_metadata(mm: &ModelManager, item_id: i64) -> Result<Option<String>> {
+        let db = mm.db();
+
+        let result = sqlx::query_scalar!(
+            r#"SELECT item_metadata as "item_metadata!" FROM items WHERE id = $1"#,
+            item_id
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Only write `metadata` if the stored one is still `expected`.
+    ///
+    /// Returns `false` when someone else updated it in between (or the item is gone).
+    pub async fn swap_metadata(
+        mm: &ModelManager,
+        item_id: i64,
+        metadata: &str,
+        expected: &str,
+    ) -> Result<bool> {
+        let db = mm.db();
+
+        let result = sqlx::query!(
+            "UPDATE items SET item_metadata = $1 WHERE id = $2 AND item_metadata = $3",
+            metadata,
+            item_id,
+            expected
+        )
+        .execute(db)
+        .await?
+        .rows_affected();
+
+        Ok(result.ge(&1))
+    }
+
     pub async fn update_image(mm: &ModelManager, item_id: i64, updated_image: i64) -> Option<()> {
         let db = mm.db();
 
@@ -9,26 +9,34 @@ pub struct RawLocationMetadata {
     pub id: i64,
     pub name: String,
     pub metadata: Option<Json<ValueStore>>,
+    pub location_type: Option<String>,
 }
 // endregion
 
 //```sql
 // CREATE TABLE IF NOT EXISTS location_metadata (
-//      id       INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL UNIQUE,
-//      name     TEXT                              NOT NULL,
-//      metadata TEXT
+//      id            INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL UNIQUE,
+//      name          TEXT                              NOT NULL,
+//      metadata      TEXT,
+//      location_type TEXT REFERENCES location_types (name) ON DELETE SET NULL ON UPDATE CASCADE
 //);
 //```
 pub(crate) struct LocationMetadataBmc;
 
 impl LocationMetadataBmc {
-    pub async fn create(mm: &ModelManager, name: &str, metadata: Option<&str>) -> Result<i64> {
+    pub async fn create(
+        mm: &ModelManager,
+        name: &str,
+        metadata: Option<&str>,
+        location_type: Option<&str>,
+    ) -> Result<i64> {
         let db = mm.db();
 
         let result = sqlx::query!(
-            "INSERT INTO location_metadata (name, metadata) VALUES ($1, $2)",
+            "INSERT INTO location_metadata (name, metadata, location_type) VALUES ($1, $2, $3)",
             name,
-            metadata
+            metadata,
+            location_type
         )
         .execute(db)
         .await?
@@ -42,7 +50,7 @@ impl LocationMetadataBmc {
 
         let result = sqlx::query_as!(
             RawLocationMetadata,
-            r#"SELECT id, name, metadata as "metadata?: Json<ValueStore>"
+            r#"SELECT id, name, metadata as "metadata?: Json<ValueStore>", location_type
                 FROM location_metadata
                 WHERE id = $1
                 "#,
@@ -60,7 +68,7 @@ impl LocationMetadataBmc {
 
         let result = sqlx::query_as!(
             RawLocationMetadata,
-            r#"SELECT id, name, metadata as "metadata?: Json<ValueStore>"
+            r#"SELECT id, name, metadata as "metadata?: Json<ValueStore>", location_type
                 FROM location_metadata"#
         )
         .fetch_all(db)
@@ -97,6 +105,40 @@ impl LocationMetadataBmc {
         Ok(())
     }
 
+    /// `None` if the location does not exist, `Some(None)` if it has no metadata.
+    pub async fn get_raw_metadata(mm: &ModelManager, id: i64) -> Result<Option<Option<String>>> {
+        let db = mm.db();
+
+        let result = sqlx::query_scalar!("SELECT metadata FROM location_metadata WHERE id = $1", id)
+            .fetch_optional(db)
+            .await?;
+
+        Ok(result)
+    }
+
+    /// Same as [`LocationMetadataBmc::update_metadata`], but only if the stored metadata is still `expected`.
+    pub async fn swap_metadata(
+        mm: &ModelManager,
+        id: i64,
+        metadata: Option<&str>,
+        expected: Option<&str>,
+    ) -> Result<bool> {
+        let db = mm.db();
+
+        // `IS` so that a missing metadata (NULL) can be compared as well
+        let result = sqlx::query!(
+            "UPDATE location_metadata SET metadata = $1 WHERE id = $2 AND metadata IS $3",
+            metadata,
+            id,
+            expected
+        )
+        .execute(db)
+        .await?
+        .rows_affected();
+
+        Ok(result.ge(&1))
+    }
+
     pub async fn delete(mm: &ModelManager, id: i64) -> Result<()> {
         let db = mm.db();
 
@@ -148,13 +190,23 @@ mod tests {
             )
             .build();
 
-        let _ = LocationMetadataBmc::create(&mm, "Can 1", Some(&metadata.to_string()))
-            .await
-            .unwrap();
+        let _ = LocationMetadataBmc::create(
+            &mm,
+            "Can 1",
+            Some(&metadata.to_string()),
+            None,
+        )
+        .await
+        .unwrap();
 
-        let _ = LocationMetadataBmc::create(&mm, "Can 2", Some(&metadata.to_string()))
-            .await
-            .unwrap();
+        let _ = LocationMetadataBmc::create(
+            &mm,
+            "Can 2",
+            Some(&metadata.to_string()),
+            None,
+        )
+        .await
+        .unwrap();
 
         let result = LocationMetadataBmc::get_all(&mm).await.unwrap();
 
@@ -176,9 +228,14 @@ mod tests {
             )
             .build();
 
-        let id = LocationMetadataBmc::create(&mm, "Container Uno", Some(&metadata.to_string()))
-            .await
-            .unwrap();
+        let id = LocationMetadataBmc::create(
+            &mm,
+            "Container Uno",
+            Some(&metadata.to_string()),
+            None,
+        )
+        .await
+        .unwrap();
 
         let result = LocationMetadataBmc::get(&mm, id).await.unwrap();
 
@@ -200,9 +257,14 @@ mod tests {
             )
             .build();
         // Update name
-        let id = LocationMetadataBmc::create(&mm, "Container Uno", Some(&metadata.to_string()))
-            .await
-            .unwrap();
+        let id = LocationMetadataBmc::create(
+            &mm,
+            "Container Uno",
+            Some(&metadata.to_string()),
+            None,
+        )
+        .await
+        .unwrap();
 
         LocationMetadataBmc::update_name(&mm, id, "Hall Uno")
             .await
@@ -217,9 +279,14 @@ mod tests {
 
         let mm = get_dev_env().await.unwrap();
 
-        let id = LocationMetadataBmc::create(&mm, "Container Uno", Some(&metadata.to_string()))
-            .await
-            .unwrap();
+        let id = LocationMetadataBmc::create(
+            &mm,
+            "Container Uno",
+            Some(&metadata.to_string()),
+            None,
+        )
+        .await
+        .unwrap();
 
         let metadata = ValueStore::builder()
             .array(
@@ -260,9 +327,14 @@ mod tests {
             )
             .build();
         // Update name
-        let id = LocationMetadataBmc::create(&mm, "Container Uno", Some(&metadata.to_string()))
-            .await
-            .unwrap();
+        let id = LocationMetadataBmc::create(
+            &mm,
+            "Container Uno",
+            Some(&metadata.to_string()),
+            None,
+        )
+        .await
+        .unwrap();
 
         let result = LocationMetadataBmc::get_all(&mm).await.unwrap();
 
@@ -0,0 +1,141 @@
+use lib_model::ModelManager;
+use lib_model::Result;
+
+// region : Types
+#[derive(sqlx::FromRow)]
+pub struct RawLocationType {
+    pub name: String,
+    pub default_schema: Option<String>,
+}
+// endregion
+
+//```sql
+// CREATE TABLE IF NOT EXISTS location_types (
+//      name           TEXT PRIMARY KEY NOT NULL UNIQUE,
+//      default_schema TEXT
+//);
+//```
+pub(crate) struct LocationTypesBmc;
+
+impl LocationTypesBmc {
+    pub async fn create(mm: &ModelManager, name: &str, default_schema: Option<&str>) -> Result<()> {
+        let db = mm.db();
+
+        sqlx::query!(
+            "INSERT INTO location_types (name, default_schema) VALUES ($1, $2)",
+            name,
+            default_schema
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get(mm: &ModelManager, name: &str) -> Option<RawLocationType> {
+        let db = mm.db();
+
+        let result = sqlx::query_as!(
+            RawLocationType,
+            r#"SELECT name as "name!", default_schema FROM location_types WHERE name = $1"#,
+            name
+        )
+        .fetch_one(db)
+        .await
+        .ok()?;
+
+        Some(result)
+    }
+
+    pub async fn get_all(mm: &ModelManager) -> Result<Vec<RawLocationType>> {
+        let db = mm.db();
+
+        let result = sqlx::query_as!(
+            RawLocationType,
+            r#"SELECT name as "name!", default_schema FROM location_types ORDER BY name"#
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// `false` if there is no such location type.
+    pub async fn update_default_schema(
+        mm: &ModelManager,
+        name: &str,
+        default_schema: Option<&str>,
+    ) -> Result<bool> {
+        let db = mm.db();
+
+        let result = sqlx::query!(
+            "UPDATE location_types SET default_schema = $1 WHERE name = $2",
+            default_schema,
+            name
+        )
+        .execute(db)
+        .await?
+        .rows_affected();
+
+        Ok(result.ge(&1))
+    }
+
+    // Note : The locations of that type are left without one
+    pub async fn delete(mm: &ModelManager, name: &str) -> Result<()> {
+        let db = mm.db();
+
+        sqlx::query!("DELETE FROM location_types WHERE name = $1", name)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::store::location_types::LocationTypesBmc;
+    use lib_model::_dev_utils::get_dev_env;
+    use serial_test::serial;
+
+    #[tokio::test]
+    #[serial]
+    async fn test_location_types() {
+        let mm = get_dev_env().await.unwrap();
+
+        let container = LocationTypesBmc::get(&mm, "container").await.unwrap();
+        assert_eq!(container.default_schema.as_deref(), Some("TestSchema"));
+
+        LocationTypesBmc::create(&mm, "cupboard", None)
+            .await
+            .unwrap();
+        assert!(
+            LocationTypesBmc::update_default_schema(&mm, "cupboard", Some("Cupboard"))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !LocationTypesBmc::update_default_schema(&mm, "shed", Some("Shed"))
+                .await
+                .unwrap()
+        );
+
+        let names: Vec<(String, Option<String>)> = LocationTypesBmc::get_all(&mm)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.name, t.default_schema))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("container".to_string(), Some("TestSchema".to_string())),
+                ("cupboard".to_string(), Some("Cupboard".to_string())),
+                ("hall".to_string(), None),
+            ]
+        );
+
+        LocationTypesBmc::delete(&mm, "cupboard").await.unwrap();
+        assert!(LocationTypesBmc::get(&mm, "cupboard").await.is_none());
+    }
+}
@@ -52,7 +52,7 @@ impl LocationsBmc {
 
         let result = sqlx::query_as!(
                 RawLocationMetadata,
-                r#"SELECT id, name, metadata as "metadata?: Json<ValueStore>"
+                r#"SELECT id, name, metadata as "metadata?: Json<ValueStore>", location_type
                    FROM location_metadata
                    WHERE id = (SELECT location FROM location_data WHERE id = $1)"#,
                 id
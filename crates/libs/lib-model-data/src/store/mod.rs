@@ -3,5 +3,7 @@ pub(crate) mod locations;
 pub(crate) mod records;
 pub(crate) mod image;
 pub(crate) mod location_metadata;
+pub(crate) mod location_types;
+pub(crate) mod reference;
 
 
@@ -0,0 +1,35 @@
+use lib_commons::ReferenceTarget;
+use lib_model::ModelManager;
+use lib_model::Result;
+
+// Note : The rows a reference field can point to, see `lib_commons::FieldType::Reference`
+pub(crate) struct ReferenceBmc;
+
+impl ReferenceBmc {
+    pub async fn exists(mm: &ModelManager, target: ReferenceTarget, id: i64) -> Result<bool> {
+        let db = mm.db();
+
+        let query = match target {
+            ReferenceTarget::Item => {
+                sqlx::query_scalar!(
+                    r#"SELECT EXISTS (SELECT 1 FROM items WHERE id = $1) as "exists!: bool""#,
+                    id
+                )
+            }
+            ReferenceTarget::LocationMetadata => {
+                sqlx::query_scalar!(
+                    r#"SELECT EXISTS (SELECT 1 FROM location_metadata WHERE id = $1) as "exists!: bool""#,
+                    id
+                )
+            }
+            ReferenceTarget::Location => {
+                sqlx::query_scalar!(
+                    r#"SELECT EXISTS (SELECT 1 FROM location_data WHERE id = $1) as "exists!: bool""#,
+                    id
+                )
+            }
+        };
+
+        Ok(query.fetch_one(db).await?)
+    }
+}
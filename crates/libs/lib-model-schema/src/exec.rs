@@ -1,16 +1,23 @@
+use crate::store::field_group::FieldGroupBmc;
 use crate::store::schema::SchemaBmc;
-use crate::types::params::{SchemaDeletePayload, SchemaGetPayload, SchemaRegisterPayload, SchemaUpdatePayload};
-use crate::types::{Schema, Schemas};
+use crate::store::usage::SchemaUsageBmc;
+use crate::types::params::{
+    FieldGroupRegisterPayload, FieldGroupUpdatePayload, SchemaChange, SchemaDeletePayload,
+    SchemaDeletePolicy, SchemaGetPayload, SchemaRegisterPayload, SchemaUpdatePayload,
+};
+use crate::types::{FieldGroup, Schema, SchemaUsage, Schemas};
 use lib_model::ModelManager;
 use lib_model::{Error, Result};
+use sqlx::{Sqlite, SqliteConnection, Transaction};
 
 pub(crate) mod types {
+    use crate::store::field_group::RawFieldGroup;
     use crate::store::schema::RawSchema;
-    use lib_commons::Field;
+    use lib_commons::{Field, Migration, Rule};
     use std::sync::Arc;
 
     pub mod params {
-        use lib_commons::Field;
+        use lib_commons::{Field, Migration, Rule};
         use serde_json::json;
         use std::sync::Arc;
 
@@ -18,6 +25,15 @@ pub(crate) mod types {
         pub struct SchemaRegisterPayload {
             pub name: String,
             pub fields: Arc<[Field]>,
+            /// `allow`, `warn` or `reject`, see `lib_schema::AdditionalProperties`
+            pub additional_properties: String,
+            /// Names of the parent schemas
+            pub extends: Arc<[String]>,
+            /// Names of the field groups
+            pub groups: Arc<[String]>,
+            /// One per version after the first
+            pub migrations: Arc<[Migration]>,
+            pub rules: Arc<[Rule]>,
         }
 
         impl SchemaRegisterPayload {
@@ -28,6 +44,26 @@ pub(crate) mod types {
             pub fn fields(&self) -> String {
                 json!(self.fields).to_string()
             }
+
+            pub fn additional_properties(&self) -> &str {
+                &self.additional_properties
+            }
+
+            pub fn extends(&self) -> String {
+                json!(self.extends).to_string()
+            }
+
+            pub fn groups(&self) -> String {
+                json!(self.groups).to_string()
+            }
+
+            pub fn migrations(&self) -> String {
+                json!(self.migrations).to_string()
+            }
+
+            pub fn rules(&self) -> String {
+                json!(self.rules).to_string()
+            }
         }
 
         #[derive(Debug, Clone)]
@@ -35,6 +71,11 @@ pub(crate) mod types {
            pub id: i64,
             pub name: Option<String>,
             pub fields: Option<Arc<[Field]>>,
+            pub additional_properties: Option<String>,
+            pub extends: Option<Arc<[String]>>,
+            pub groups: Option<Arc<[String]>>,
+            pub migrations: Option<Arc<[Migration]>>,
+            pub rules: Option<Arc<[Rule]>>,
         }
 
         impl SchemaUpdatePayload {
@@ -48,6 +89,26 @@ pub(crate) mod types {
             pub fn fields(&self) -> Option<String> {
                 self.fields.as_ref().map(|f| json!(f).to_string())
             }
+
+            pub fn additional_properties(&self) -> Option<&str> {
+                self.additional_properties.as_deref()
+            }
+
+            pub fn extends(&self) -> Option<String> {
+                self.extends.as_ref().map(|e| json!(e).to_string())
+            }
+
+            pub fn groups(&self) -> Option<String> {
+                self.groups.as_ref().map(|g| json!(g).to_string())
+            }
+
+            pub fn migrations(&self) -> Option<String> {
+                self.migrations.as_ref().map(|m| json!(m).to_string())
+            }
+
+            pub fn rules(&self) -> Option<String> {
+                self.rules.as_ref().map(|r| json!(r).to_string())
+            }
         }
 
         #[derive(Debug, Clone, Default)]
@@ -61,15 +122,91 @@ pub(crate) mod types {
             }
         }
 
+        /// What happens to the metadata still using a schema that is deleted.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub enum SchemaDeletePolicy {
+            /// The delete fails with `Error::SchemaInUse`
+            #[default]
+            Restrict,
+            /// The metadata loses its reference to the schema, its values are kept
+            Cascade,
+        }
+
         #[derive(Debug, Clone)]
         pub struct SchemaDeletePayload {
             id: i64,
+            policy: SchemaDeletePolicy,
         }
 
         impl SchemaDeletePayload {
+            pub fn new(id: i64) -> Self {
+                SchemaDeletePayload {
+                    id,
+                    policy: SchemaDeletePolicy::default(),
+                }
+            }
+
+            pub fn with_policy(mut self, policy: SchemaDeletePolicy) -> Self {
+                self.policy = policy;
+                self
+            }
+
             pub fn id(&self) -> i64 {
                 self.id
             }
+
+            pub fn policy(&self) -> SchemaDeletePolicy {
+                self.policy
+            }
+        }
+
+        /// One write of [`crate::exec::apply_schema_changes`].
+        #[derive(Debug, Clone)]
+        pub enum SchemaChange {
+            Register(SchemaRegisterPayload),
+            Update(SchemaUpdatePayload),
+            Delete(SchemaDeletePayload),
+            RegisterGroup(FieldGroupRegisterPayload),
+            UpdateGroup(FieldGroupUpdatePayload),
+            /// The id of the field group
+            DeleteGroup(i64),
+        }
+
+        #[derive(Debug, Clone)]
+        pub struct FieldGroupRegisterPayload {
+            pub name: String,
+            pub fields: Arc<[Field]>,
+        }
+
+        impl FieldGroupRegisterPayload {
+            pub fn name(&self) -> &str {
+                &self.name
+            }
+
+            pub fn fields(&self) -> String {
+                json!(self.fields).to_string()
+            }
+        }
+
+        #[derive(Debug, Clone)]
+        pub struct FieldGroupUpdatePayload {
+            pub id: i64,
+            pub name: Option<String>,
+            pub fields: Option<Arc<[Field]>>,
+        }
+
+        impl FieldGroupUpdatePayload {
+            pub fn id(&self) -> i64 {
+                self.id
+            }
+
+            pub fn name(&self) -> Option<&str> {
+                self.name.as_deref()
+            }
+
+            pub fn fields(&self) -> Option<String> {
+                self.fields.as_ref().map(|f| json!(f).to_string())
+            }
         }
     }
 
@@ -85,6 +222,11 @@ pub(crate) mod types {
         id: i64,
         name: String,
         fields: Arc<[Field]>,
+        additional_properties: String,
+        extends: Arc<[String]>,
+        groups: Arc<[String]>,
+        migrations: Arc<[Migration]>,
+        rules: Arc<[Rule]>,
     }
 
     impl Schema {
@@ -99,6 +241,26 @@ pub(crate) mod types {
         pub fn fields(&self) -> Arc<[Field]> {
             self.fields.clone()
         }
+
+        pub fn additional_properties(&self) -> &str {
+            &self.additional_properties
+        }
+
+        pub fn extends(&self) -> Arc<[String]> {
+            self.extends.clone()
+        }
+
+        pub fn groups(&self) -> Arc<[String]> {
+            self.groups.clone()
+        }
+
+        pub fn migrations(&self) -> Arc<[Migration]> {
+            self.migrations.clone()
+        }
+
+        pub fn rules(&self) -> Arc<[Rule]> {
+            self.rules.clone()
+        }
     }
 
     impl From<RawSchema> for Schema {
@@ -107,13 +269,86 @@ pub(crate) mod types {
                 id: value.id,
                 name: value.name,
                 fields: value.fields.0.into(),
+                additional_properties: value.additional_properties,
+                extends: value.extends.0.into(),
+                groups: value.groups.0.into(),
+                migrations: value.migrations.0.into(),
+                rules: value.rules.0.into(),
+            }
+        }
+    }
+
+    /// The items and location metadata whose metadata refers to a schema.
+    #[derive(Debug, Clone, Default)]
+    pub struct SchemaUsage {
+        pub(crate) items: Arc<[i64]>,
+        pub(crate) locations: Arc<[i64]>,
+        pub(crate) location_types: Arc<[String]>,
+    }
+
+    impl SchemaUsage {
+        pub fn items(&self) -> Arc<[i64]> {
+            self.items.clone()
+        }
+
+        pub fn locations(&self) -> Arc<[i64]> {
+            self.locations.clone()
+        }
+
+        /// The names of the location types whose new locations get the schema.
+        pub fn location_types(&self) -> Arc<[String]> {
+            self.location_types.clone()
+        }
+
+        pub fn is_unused(&self) -> bool {
+            self.items.is_empty() && self.locations.is_empty() && self.location_types.is_empty()
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct FieldGroup {
+        id: i64,
+        name: String,
+        fields: Arc<[Field]>,
+    }
+
+    impl FieldGroup {
+        pub fn id(&self) -> i64 {
+            self.id
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        pub fn fields(&self) -> Arc<[Field]> {
+            self.fields.clone()
+        }
+    }
+
+    impl From<RawFieldGroup> for FieldGroup {
+        fn from(value: RawFieldGroup) -> Self {
+            FieldGroup {
+                id: value.id,
+                name: value.name,
+                fields: value.fields.0.into(),
             }
         }
     }
 }
 
 pub async fn register_schema(mm: &ModelManager, params: SchemaRegisterPayload) -> Result<i64> {
-    let id = SchemaBmc::create(mm, params.name(), params.fields().as_ref()).await?;
+    let id = SchemaBmc::create(
+        mm,
+        params.name(),
+        params.fields().as_ref(),
+        params.additional_properties(),
+        params.extends().as_ref(),
+        params.groups().as_ref(),
+        params.migrations().as_ref(),
+        params.rules().as_ref(),
+    )
+    .await?;
 
     Ok(id)
 }
@@ -132,23 +367,472 @@ pub async fn get_schema(mm: &ModelManager, params: SchemaGetPayload) -> Result<S
 }
 
 pub async fn update_schema(mm: &ModelManager, params: SchemaUpdatePayload) -> Result<()> {
+    let mut tx = mm.schema_db().begin().await?;
+    let mut data_tx = mm.db().begin().await?;
+    let mut undo = MetadataUndo::default();
+
+    if let Some(name) = params.name() {
+        let current = SchemaBmc::get_name(&mut *tx, params.id()).await?;
+        rename_references(&mut data_tx, &current, name, &mut undo).await?;
+    }
+
+    SchemaBmc::update(
+        &mut *tx,
+        params.id(),
+        params.name(),
+        params.fields().as_deref(),
+        params.additional_properties(),
+        params.extends().as_deref(),
+        params.groups().as_deref(),
+        params.migrations().as_deref(),
+        params.rules().as_deref(),
+    )
+    .await?;
+
+    commit_schema_change(mm, tx, data_tx, undo).await
+}
+
+/// Apply every change in a single transaction, either all of them are written or none.
+///
+/// Gives back, in order, the id of each registered schema or field group (`None` for updates and
+/// deletes).
+pub async fn apply_schema_changes(
+    mm: &ModelManager,
+    changes: Vec<SchemaChange>,
+) -> Result<Vec<Option<i64>>> {
+    let mut tx = mm.schema_db().begin().await?;
+    // The metadata referring to renamed or deleted schemas lives in the data db
+    let mut data_tx = mm.db().begin().await?;
+    let mut undo = MetadataUndo::default();
+    let mut ids = Vec::with_capacity(changes.len());
+
+    for change in changes {
+        let id = match change {
+            SchemaChange::Register(params) => Some(
+                SchemaBmc::insert(
+                    &mut *tx,
+                    params.name(),
+                    params.fields().as_ref(),
+                    params.additional_properties(),
+                    params.extends().as_ref(),
+                    params.groups().as_ref(),
+                    params.migrations().as_ref(),
+                    params.rules().as_ref(),
+                )
+                .await?,
+            ),
+            SchemaChange::Update(params) => {
+                if let Some(name) = params.name() {
+                    let current = SchemaBmc::get_name(&mut *tx, params.id()).await?;
+                    rename_references(&mut data_tx, &current, name, &mut undo).await?;
+                }
+
+                SchemaBmc::update(
+                    &mut *tx,
+                    params.id(),
+                    params.name(),
+                    params.fields().as_deref(),
+                    params.additional_properties(),
+                    params.extends().as_deref(),
+                    params.groups().as_deref(),
+                    params.migrations().as_deref(),
+                    params.rules().as_deref(),
+                )
+                .await?;
+                None
+            }
+            SchemaChange::Delete(params) => {
+                let name = SchemaBmc::get_name(&mut *tx, params.id()).await?;
+                release_references(&mut data_tx, &name, params.policy(), &mut undo).await?;
+
+                SchemaBmc::remove(&mut *tx, params.id()).await?;
+                None
+            }
+            SchemaChange::RegisterGroup(params) => {
+                Some(FieldGroupBmc::insert(&mut *tx, params.name(), &params.fields()).await?)
+            }
+            SchemaChange::UpdateGroup(params) => {
+                let fields = params.fields();
+                FieldGroupBmc::update(&mut *tx, params.id(), params.name(), fields.as_deref())
+                    .await?;
+                None
+            }
+            SchemaChange::DeleteGroup(id) => {
+                FieldGroupBmc::remove(&mut *tx, id).await?;
+                None
+            }
+        };
+        ids.push(id);
+    }
+
+    // Note : Dropping the transactions on an early return rolls them back
+    commit_schema_change(mm, tx, data_tx, undo).await?;
+
+    Ok(ids)
+}
+
+/// Fails with `Error::SchemaInUse` while some metadata refers to the schema, unless the payload
+/// asks for [`SchemaDeletePolicy::Cascade`].
+pub async fn delete_schema(mm: &ModelManager, params: SchemaDeletePayload) -> Result<()> {
+    let mut tx = mm.schema_db().begin().await?;
+    let mut data_tx = mm.db().begin().await?;
+    let mut undo = MetadataUndo::default();
+
+    let name = SchemaBmc::get_name(&mut *tx, params.id()).await?;
+    release_references(&mut data_tx, &name, params.policy(), &mut undo).await?;
+
+    SchemaBmc::remove(&mut *tx, params.id()).await?;
+
+    commit_schema_change(mm, tx, data_tx, undo).await
+}
+
+/// The items and location metadata referring to the schema `name`, nested objects included,
+/// and the location types defaulting to it.
+pub async fn get_schema_usage(mm: &ModelManager, name: &str) -> Result<SchemaUsage> {
+    let items = SchemaUsageBmc::items(mm.db(), name).await?;
+    let locations = SchemaUsageBmc::locations(mm.db(), name).await?;
+    let location_types = SchemaUsageBmc::location_types(mm.db(), name).await?;
+
+    Ok(SchemaUsage {
+        items: items.iter().map(|item| item.id).collect(),
+        locations: locations.iter().map(|location| location.id).collect(),
+        location_types: location_types.into(),
+    })
+}
+
+/// The metadata a schema change rewrote, as it was before, see [`commit_schema_change`].
+#[derive(Default)]
+struct MetadataUndo {
+    items: Vec<(i64, String)>,
+    locations: Vec<(i64, String)>,
+    /// The location types along with the schema they defaulted to
+    location_types: Vec<(String, String)>,
+}
+
+impl MetadataUndo {
+    async fn restore(self, mm: &ModelManager) -> Result<()> {
+        let mut data_tx = mm.db().begin().await?;
+
+        for (id, metadata) in &self.items {
+            SchemaUsageBmc::update_item(&mut *data_tx, *id, metadata).await?;
+        }
+
+        for (id, metadata) in &self.locations {
+            SchemaUsageBmc::update_location(&mut *data_tx, *id, metadata).await?;
+        }
+
+        for (location_type, schema) in &self.location_types {
+            SchemaUsageBmc::update_location_type(&mut *data_tx, location_type, Some(schema))
+                .await?;
+        }
+
+        data_tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+// Note : The two dbs can't share a transaction. The data one is committed first, so if it fails
+//        the schema db is left untouched, and if the schema one fails after it the metadata
+//        it rewrote is put back.
+async fn commit_schema_change(
+    mm: &ModelManager,
+    tx: Transaction<'_, Sqlite>,
+    data_tx: Transaction<'_, Sqlite>,
+    undo: MetadataUndo,
+) -> Result<()> {
+    data_tx.commit().await?;
+
+    if let Err(err) = tx.commit().await {
+        undo.restore(mm).await?;
+        return Err(err.into());
+    }
+
+    Ok(())
+}
+
+async fn rename_references(
+    data: &mut SqliteConnection,
+    from: &str,
+    to: &str,
+    undo: &mut MetadataUndo,
+) -> Result<()> {
+    if from == to {
+        return Ok(());
+    }
+
+    replace_references(data, from, Some(to), undo).await
+}
+
+async fn release_references(
+    data: &mut SqliteConnection,
+    name: &str,
+    policy: SchemaDeletePolicy,
+    undo: &mut MetadataUndo,
+) -> Result<()> {
+    match policy {
+        SchemaDeletePolicy::Restrict => {
+            let in_use = !SchemaUsageBmc::items(&mut *data, name).await?.is_empty()
+                || !SchemaUsageBmc::locations(&mut *data, name).await?.is_empty()
+                || !SchemaUsageBmc::location_types(&mut *data, name).await?.is_empty();
+
+            if in_use {
+                return Err(Error::SchemaInUse(name.to_string()));
+            }
+
+            Ok(())
+        }
+        SchemaDeletePolicy::Cascade => replace_references(data, name, None, undo).await,
+    }
+}
+
+// Note : See `ValueStore::replace_schema`
+async fn replace_references(
+    data: &mut SqliteConnection,
+    from: &str,
+    to: Option<&str>,
+    undo: &mut MetadataUndo,
+) -> Result<()> {
+    for mut item in SchemaUsageBmc::items(&mut *data, from).await? {
+        let previous = item.metadata.to_string();
+        if item.metadata.replace_schema(from, to) {
+            SchemaUsageBmc::update_item(&mut *data, item.id, &item.metadata.to_string()).await?;
+            undo.items.push((item.id, previous));
+        }
+    }
+
+    for mut location in SchemaUsageBmc::locations(&mut *data, from).await? {
+        let previous = location.metadata.to_string();
+        if location.metadata.replace_schema(from, to) {
+            let metadata = location.metadata.to_string();
+            SchemaUsageBmc::update_location(&mut *data, location.id, &metadata).await?;
+            undo.locations.push((location.id, previous));
+        }
+    }
+
+    // Note : Or the new locations of the type would get a schema that no longer exists
+    for location_type in SchemaUsageBmc::location_types(&mut *data, from).await? {
+        SchemaUsageBmc::update_location_type(&mut *data, &location_type, to).await?;
+        undo.location_types.push((location_type, from.to_string()));
+    }
+
+    Ok(())
+}
+
+pub async fn register_field_group(
+    mm: &ModelManager,
+    params: FieldGroupRegisterPayload,
+) -> Result<i64> {
+    let id = FieldGroupBmc::create(mm, params.name(), params.fields().as_ref()).await?;
+
+    Ok(id)
+}
+
+pub async fn get_field_groups(mm: &ModelManager) -> Result<Vec<FieldGroup>> {
+    let groups = FieldGroupBmc::get_all(mm).await?;
+
+    Ok(groups.into_iter().map(|g| g.into()).collect())
+}
+
+pub async fn update_field_group(mm: &ModelManager, params: FieldGroupUpdatePayload) -> Result<()> {
     if let Some(name) = params.name() {
-        SchemaBmc::update_name(mm, params.id(), name)
+        FieldGroupBmc::update_name(mm, params.id(), name)
             .await
-            .ok_or(Error::SchemaNotFound(params.id()))?;
+            .ok_or(Error::FieldGroupNotFound(params.id()))?;
     }
 
     if let Some(fields) = params.fields() {
-        SchemaBmc::update_fields(mm, params.id(), fields.as_ref())
+        FieldGroupBmc::update_fields(mm, params.id(), fields.as_ref())
             .await
-            .ok_or(Error::SchemaNotFound(params.id()))?;
+            .ok_or(Error::FieldGroupNotFound(params.id()))?;
     }
 
     Ok(())
 }
 
-pub async fn delete_schema(mm: &ModelManager, params: SchemaDeletePayload) -> Result<()> {
-    SchemaBmc::delete(mm, params.id()).await.ok_or(Error::SchemaNotFound(params.id()))?;
+pub async fn delete_field_group(mm: &ModelManager, id: i64) -> Result<()> {
+    FieldGroupBmc::delete(mm, id)
+        .await
+        .ok_or(Error::FieldGroupNotFound(id))?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::exec::{delete_schema, get_schema_usage, register_schema, update_schema};
+    use crate::types::params::{
+        SchemaDeletePayload, SchemaDeletePolicy, SchemaRegisterPayload, SchemaUpdatePayload,
+    };
+    use lib_commons::ValueStore;
+    use lib_model::Error;
+    use lib_model::_dev_utils::get_dev_env;
+    use serial_test::serial;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    #[serial]
+    async fn test_schema_usage() {
+        let mm = get_dev_env().await.unwrap();
+
+        let id = register_schema(
+            &mm,
+            SchemaRegisterPayload {
+                name: "Pallet".to_string(),
+                fields: Arc::from([]),
+                additional_properties: "allow".to_string(),
+                extends: Arc::from([]),
+                groups: Arc::from([]),
+                migrations: Arc::from([]),
+                rules: Arc::from([]),
+            },
+        )
+        .await
+        .unwrap();
+
+        let item_metadata = ValueStore::builder().with_schema("Pallet").build().to_string();
+        // Note : Unchecked, like `SchemaUsageBmc`, the data db tables are not in the schema db
+        let item_id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO items (name, item_metadata, location, image) VALUES ('Pallet', $1, 1, 1) RETURNING id",
+        )
+        .bind(item_metadata)
+        .fetch_one(mm.db())
+        .await
+        .unwrap();
+
+        let location_metadata = ValueStore::builder()
+            .with_schema("Warehouse")
+            .array("racks", vec![], Some("Pallet"))
+            .build()
+            .to_string();
+        let location_id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO location_metadata (name, metadata) VALUES ('Warehouse', $1) RETURNING id",
+        )
+        .bind(location_metadata)
+        .fetch_one(mm.db())
+        .await
+        .unwrap();
+
+        let usage = get_schema_usage(&mm, "Pallet").await.unwrap();
+        assert_eq!(usage.items().as_ref(), &[item_id]);
+        assert_eq!(usage.locations().as_ref(), &[location_id]);
+
+        // A rename follows into the metadata, nested references included
+        let rename = SchemaUpdatePayload {
+            id,
+            name: Some("Skid".to_string()),
+            fields: None,
+            additional_properties: None,
+            extends: None,
+            groups: None,
+            migrations: None,
+            rules: None,
+        };
+        update_schema(&mm, rename).await.unwrap();
+
+        assert!(get_schema_usage(&mm, "Pallet").await.unwrap().is_unused());
+        let usage = get_schema_usage(&mm, "Skid").await.unwrap();
+        assert_eq!(usage.items().as_ref(), &[item_id]);
+        assert_eq!(usage.locations().as_ref(), &[location_id]);
+
+        assert!(matches!(
+            delete_schema(&mm, SchemaDeletePayload::new(id)).await,
+            Err(Error::SchemaInUse(name)) if name == "Skid"
+        ));
+
+        let cascade = SchemaDeletePayload::new(id).with_policy(SchemaDeletePolicy::Cascade);
+        delete_schema(&mm, cascade).await.unwrap();
+
+        assert!(get_schema_usage(&mm, "Skid").await.unwrap().is_unused());
+        let metadata = sqlx::query_scalar::<_, String>(
+            "SELECT metadata FROM location_metadata WHERE id = $1",
+        )
+        .bind(location_id)
+        .fetch_one(mm.db())
+        .await
+        .unwrap();
+        let metadata: ValueStore = serde_json::from_str(&metadata).unwrap();
+        assert_eq!(metadata.schema_name().as_deref(), Some("Warehouse"));
+        assert!(metadata.object_properties_schemas().is_empty());
+        assert!(metadata.get("racks").is_some());
+
+        sqlx::query("DELETE FROM items WHERE id = $1")
+            .bind(item_id)
+            .execute(mm.db())
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM location_metadata WHERE id = $1")
+            .bind(location_id)
+            .execute(mm.db())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_location_type_default_schema() {
+        let mm = get_dev_env().await.unwrap();
+
+        let id = register_schema(
+            &mm,
+            SchemaRegisterPayload {
+                name: "Tray".to_string(),
+                fields: Arc::from([]),
+                additional_properties: "allow".to_string(),
+                extends: Arc::from([]),
+                groups: Arc::from([]),
+                migrations: Arc::from([]),
+                rules: Arc::from([]),
+            },
+        )
+        .await
+        .unwrap();
+
+        sqlx::query("INSERT INTO location_types (name, default_schema) VALUES ('trolley', 'Tray')")
+            .execute(mm.db())
+            .await
+            .unwrap();
+        let default_schema = async || {
+            sqlx::query_scalar::<_, Option<String>>(
+                "SELECT default_schema FROM location_types WHERE name = 'trolley'",
+            )
+            .fetch_one(mm.db())
+            .await
+            .unwrap()
+        };
+
+        let usage = get_schema_usage(&mm, "Tray").await.unwrap();
+        assert_eq!(usage.location_types().as_ref(), &["trolley".to_string()]);
+        assert!(!usage.is_unused());
+
+        // Renamed along
+        let rename = SchemaUpdatePayload {
+            id,
+            name: Some("Tote".to_string()),
+            fields: None,
+            additional_properties: None,
+            extends: None,
+            groups: None,
+            migrations: None,
+            rules: None,
+        };
+        update_schema(&mm, rename).await.unwrap();
+        assert_eq!(default_schema().await.as_deref(), Some("Tote"));
+
+        // Still in use by the location type alone
+        assert!(matches!(
+            delete_schema(&mm, SchemaDeletePayload::new(id)).await,
+            Err(Error::SchemaInUse(name)) if name == "Tote"
+        ));
+        assert_eq!(default_schema().await.as_deref(), Some("Tote"));
+
+        let cascade = SchemaDeletePayload::new(id).with_policy(SchemaDeletePolicy::Cascade);
+        delete_schema(&mm, cascade).await.unwrap();
+        assert_eq!(default_schema().await, None);
+
+        sqlx::query("DELETE FROM location_types WHERE name = 'trolley'")
+            .execute(mm.db())
+            .await
+            .unwrap();
+    }
+}
@@ -1,7 +1,8 @@
 pub(crate) mod schema {
-    use lib_commons::Field;
+    use lib_commons::{Field, Migration, Rule};
     use lib_model::{Error, ModelManager, Result};
     use sqlx::types::Json;
+    use sqlx::{Executor, Sqlite};
 
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Debug, sqlx::FromRow)]
@@ -9,27 +10,74 @@ pub(crate) mod schema {
         pub id: i64,
         pub name: String,
         pub fields: Json<Vec<Field>>,
+        pub additional_properties: String,
+        pub extends: Json<Vec<String>>,
+        pub groups: Json<Vec<String>>,
+        pub migrations: Json<Vec<Migration>>,
+        pub rules: Json<Vec<Rule>>,
     }
 
     /// ```sql
     /// table schema {
     ///    id     INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL UNIQUE,
     //     name   TEXT                              NOT NULL,
-    //     fields TEXT                              NOT NULL
+    //     fields TEXT                              NOT NULL,
+    //     additional_properties TEXT               NOT NULL DEFAULT 'allow',
+    //     extends TEXT                             NOT NULL DEFAULT '[]',
+    //     groups TEXT                              NOT NULL DEFAULT '[]',
+    //     migrations TEXT                          NOT NULL DEFAULT '[]',
+    //     rules TEXT                               NOT NULL DEFAULT '[]'
     /// }
     /// ```
     pub(crate) struct SchemaBmc;
 
     impl SchemaBmc {
         // Implement CRUD operations
-        pub async fn create(mm: &ModelManager, name: &str, fields: &str) -> Result<i64> {
-            // Create an item
-            let db = mm.schema_db();
+        #[allow(clippy::too_many_arguments)]
+        pub async fn create(
+            mm: &ModelManager,
+            name: &str,
+            fields: &str,
+            additional_properties: &str,
+            extends: &str,
+            groups: &str,
+            migrations: &str,
+            rules: &str,
+        ) -> Result<i64> {
+            Self::insert(
+                mm.schema_db(),
+                name,
+                fields,
+                additional_properties,
+                extends,
+                groups,
+                migrations,
+                rules,
+            )
+            .await
+        }
 
+        /// [`SchemaBmc::create`] on any executor, a transaction included.
+        #[allow(clippy::too_many_arguments)]
+        pub async fn insert<'e>(
+            db: impl Executor<'e, Database = Sqlite>,
+            name: &str,
+            fields: &str,
+            additional_properties: &str,
+            extends: &str,
+            groups: &str,
+            migrations: &str,
+            rules: &str,
+        ) -> Result<i64> {
             let id = sqlx::query!(
-                "INSERT INTO schema (name, fields) VALUES ($1, $2)",
+                "INSERT INTO schema (name, fields, additional_properties, extends, groups, migrations, rules) VALUES ($1, $2, $3, $4, $5, $6, $7)",
                 name,
-                fields
+                fields,
+                additional_properties,
+                extends,
+                groups,
+                migrations,
+                rules
             )
             .execute(db)
             .await
@@ -49,7 +97,7 @@ pub(crate) mod schema {
 
             let result = sqlx::query_as!(
                 RawSchema,
-                r#"SELECT id, name, fields as "fields: Json<Vec<Field>>" FROM schema"#
+                r#"SELECT id, name, fields as "fields: Json<Vec<Field>>", additional_properties, extends as "extends: Json<Vec<String>>", groups as "groups: Json<Vec<String>>", migrations as "migrations: Json<Vec<Migration>>", rules as "rules: Json<Vec<Rule>>" FROM schema"#
             )
             .fetch_all(db)
             .await?;
@@ -62,7 +110,7 @@ pub(crate) mod schema {
 
             sqlx::query_as!(
                 RawSchema,
-                r#"SELECT id, name, fields as "fields: Json<Vec<Field>>" FROM schema WHERE id = $1"#,
+                r#"SELECT id, name, fields as "fields: Json<Vec<Field>>", additional_properties, extends as "extends: Json<Vec<String>>", groups as "groups: Json<Vec<String>>", migrations as "migrations: Json<Vec<Migration>>", rules as "rules: Json<Vec<Rule>>" FROM schema WHERE id = $1"#,
                 id
             )
             .fetch_optional(db)
@@ -70,6 +118,14 @@ pub(crate) mod schema {
             .ok()?
         }
 
+        /// On any executor, failing if `id` does not exist.
+        pub async fn get_name<'e>(db: impl Executor<'e, Database = Sqlite>, id: i64) -> Result<String> {
+            sqlx::query_scalar!("SELECT name FROM schema WHERE id = $1", id)
+                .fetch_optional(db)
+                .await?
+                .ok_or(Error::SchemaNotFound(id))
+        }
+
         pub async fn update_name(mm: &ModelManager, id: i64, name: &str) -> Option<()> {
             let db = mm.schema_db();
 
@@ -92,14 +148,61 @@ pub(crate) mod schema {
             Some(())
         }
 
-        pub async fn delete(mm: &ModelManager, id: i64) -> Option<()> {
-            let db = mm.schema_db();
-            sqlx::query!("DELETE FROM schema WHERE id = $1", id)
+        /// Update every column that is given in one query, failing if `id` does not exist.
+        #[allow(clippy::too_many_arguments)]
+        pub async fn update<'e>(
+            db: impl Executor<'e, Database = Sqlite>,
+            id: i64,
+            name: Option<&str>,
+            fields: Option<&str>,
+            additional_properties: Option<&str>,
+            extends: Option<&str>,
+            groups: Option<&str>,
+            migrations: Option<&str>,
+            rules: Option<&str>,
+        ) -> Result<()> {
+            let rows = sqlx::query!(
+                "UPDATE schema SET
+                    name = COALESCE($1, name),
+                    fields = COALESCE($2, fields),
+                    additional_properties = COALESCE($3, additional_properties),
+                    extends = COALESCE($4, extends),
+                    groups = COALESCE($5, groups),
+                    migrations = COALESCE($6, migrations),
+                    rules = COALESCE($7, rules)
+                WHERE id = $8",
+                name,
+                fields,
+                additional_properties,
+                extends,
+                groups,
+                migrations,
+                rules,
+                id
+            )
+            .execute(db)
+            .await?
+            .rows_affected();
+
+            if rows == 0 {
+                return Err(Error::SchemaNotFound(id));
+            }
+
+            Ok(())
+        }
+
+        /// Delete on any executor, failing if `id` does not exist.
+        pub async fn remove<'e>(db: impl Executor<'e, Database = Sqlite>, id: i64) -> Result<()> {
+            let rows = sqlx::query!("DELETE FROM schema WHERE id = $1", id)
                 .execute(db)
-                .await
-                .ok()?;
+                .await?
+                .rows_affected();
 
-            Some(())
+            if rows == 0 {
+                return Err(Error::SchemaNotFound(id));
+            }
+
+            Ok(())
         }
     }
 
@@ -119,7 +222,267 @@ pub(crate) mod schema {
             let field = Field::create("Field 1", FieldType::String, true, Value::Null);
             let fields = json!(vec![field]).to_string();
 
-            SchemaBmc::create(&mm, name, &fields).await.unwrap();
+            SchemaBmc::create(&mm, name, &fields, "allow", "[]", "[]", "[]", "[]")
+                .await
+                .unwrap();
+        }
+    }
+}
+
+pub(crate) mod field_group {
+    use lib_commons::Field;
+    use lib_model::{Error, ModelManager, Result};
+    use sqlx::types::Json;
+    use sqlx::{Executor, Sqlite};
+
+    #[derive(Debug, sqlx::FromRow)]
+    pub struct RawFieldGroup {
+        pub id: i64,
+        pub name: String,
+        pub fields: Json<Vec<Field>>,
+    }
+
+    /// ```sql
+    /// table field_group {
+    ///    id     INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL UNIQUE,
+    //     name   TEXT                              NOT NULL UNIQUE,
+    //     fields TEXT                              NOT NULL
+    /// }
+    /// ```
+    pub(crate) struct FieldGroupBmc;
+
+    impl FieldGroupBmc {
+        pub async fn create(mm: &ModelManager, name: &str, fields: &str) -> Result<i64> {
+            let db = mm.schema_db();
+
+            let id = sqlx::query!(
+                "INSERT INTO field_group (name, fields) VALUES ($1, $2)",
+                name,
+                fields
+            )
+            .execute(db)
+            .await
+            .map_err(|err| match err {
+                sqlx::error::Error::RowNotFound => {
+                    Error::QueryError(format!("Failed to create field group: {}", name))
+                }
+                _ => err.into(),
+            })?
+            .last_insert_rowid();
+
+            Ok(id)
+        }
+
+        /// [`FieldGroupBmc::create`] on any executor, a transaction included.
+        pub async fn insert<'e>(
+            db: impl Executor<'e, Database = Sqlite>,
+            name: &str,
+            fields: &str,
+        ) -> Result<i64> {
+            let id = sqlx::query!(
+                "INSERT INTO field_group (name, fields) VALUES ($1, $2)",
+                name,
+                fields
+            )
+            .execute(db)
+            .await?
+            .last_insert_rowid();
+
+            Ok(id)
+        }
+
+        /// Update every column that is given in one query, failing if `id` does not exist.
+        pub async fn update<'e>(
+            db: impl Executor<'e, Database = Sqlite>,
+            id: i64,
+            name: Option<&str>,
+            fields: Option<&str>,
+        ) -> Result<()> {
+            let rows = sqlx::query!(
+                "UPDATE field_group SET name = COALESCE($1, name), fields = COALESCE($2, fields)
+                WHERE id = $3",
+                name,
+                fields,
+                id
+            )
+            .execute(db)
+            .await?
+            .rows_affected();
+
+            if rows == 0 {
+                return Err(Error::FieldGroupNotFound(id));
+            }
+
+            Ok(())
+        }
+
+        /// Delete on any executor, failing if `id` does not exist.
+        pub async fn remove<'e>(db: impl Executor<'e, Database = Sqlite>, id: i64) -> Result<()> {
+            let rows = sqlx::query!("DELETE FROM field_group WHERE id = $1", id)
+                .execute(db)
+                .await?
+                .rows_affected();
+
+            if rows == 0 {
+                return Err(Error::FieldGroupNotFound(id));
+            }
+
+            Ok(())
+        }
+
+        pub async fn get_all(mm: &ModelManager) -> Result<Vec<RawFieldGroup>> {
+            let db = mm.schema_db();
+
+            let result = sqlx::query_as!(
+                RawFieldGroup,
+                r#"SELECT id, name, fields as "fields: Json<Vec<Field>>" FROM field_group"#
+            )
+            .fetch_all(db)
+            .await?;
+
+            Ok(result)
+        }
+
+        pub async fn update_name(mm: &ModelManager, id: i64, name: &str) -> Option<()> {
+            let db = mm.schema_db();
+
+            sqlx::query!("UPDATE field_group SET name = $1 WHERE id = $2", name, id)
+                .execute(db)
+                .await
+                .ok()?;
+
+            Some(())
+        }
+
+        pub async fn update_fields(mm: &ModelManager, id: i64, fields: &str) -> Option<()> {
+            let db = mm.schema_db();
+
+            sqlx::query!("UPDATE field_group SET fields = $1 WHERE id = $2", fields, id)
+                .execute(db)
+                .await
+                .ok()?;
+
+            Some(())
+        }
+
+        pub async fn delete(mm: &ModelManager, id: i64) -> Option<()> {
+            let db = mm.schema_db();
+            sqlx::query!("DELETE FROM field_group WHERE id = $1", id)
+                .execute(db)
+                .await
+                .ok()?;
+
+            Some(())
+        }
+    }
+}
+
+/// Which rows of the data db refer to a schema, by name, in their metadata.
+pub(crate) mod usage {
+    use lib_commons::ValueStore;
+    use lib_model::Result;
+    use sqlx::types::Json;
+    use sqlx::{Executor, Sqlite};
+
+    #[derive(Debug, sqlx::FromRow)]
+    pub struct RawMetadataRef {
+        pub id: i64,
+        pub metadata: Json<ValueStore>,
+    }
+
+    // Note : Runs on the data db (`ModelManager::db`), not the schema one, so the queries are
+    //        not checked against the schema db at compile time. The nested objects refer to
+    //        their schema through `object_properties_schemas`.
+    pub(crate) struct SchemaUsageBmc;
+
+    impl SchemaUsageBmc {
+        pub async fn items<'e>(
+            db: impl Executor<'e, Database = Sqlite>,
+            name: &str,
+        ) -> Result<Vec<RawMetadataRef>> {
+            let result = sqlx::query_as::<_, RawMetadataRef>(
+                "SELECT id, item_metadata as metadata FROM items
+                WHERE json_extract(item_metadata, '$.schema_name') = $1
+                    OR EXISTS (SELECT 1 FROM json_each(item_metadata, '$.object_properties_schemas') WHERE value = $1)",
+            )
+            .bind(name)
+            .fetch_all(db)
+            .await?;
+
+            Ok(result)
+        }
+
+        pub async fn locations<'e>(
+            db: impl Executor<'e, Database = Sqlite>,
+            name: &str,
+        ) -> Result<Vec<RawMetadataRef>> {
+            let result = sqlx::query_as::<_, RawMetadataRef>(
+                "SELECT id, metadata FROM location_metadata
+                WHERE json_extract(metadata, '$.schema_name') = $1
+                    OR EXISTS (SELECT 1 FROM json_each(metadata, '$.object_properties_schemas') WHERE value = $1)",
+            )
+            .bind(name)
+            .fetch_all(db)
+            .await?;
+
+            Ok(result)
+        }
+
+        /// The names of the location types whose locations default to the schema `name`.
+        pub async fn location_types<'e>(
+            db: impl Executor<'e, Database = Sqlite>,
+            name: &str,
+        ) -> Result<Vec<String>> {
+            let result = sqlx::query_scalar::<_, String>(
+                "SELECT name FROM location_types WHERE default_schema = $1",
+            )
+            .bind(name)
+            .fetch_all(db)
+            .await?;
+
+            Ok(result)
+        }
+
+        pub async fn update_location_type<'e>(
+            db: impl Executor<'e, Database = Sqlite>,
+            location_type: &str,
+            default_schema: Option<&str>,
+        ) -> Result<()> {
+            sqlx::query("UPDATE location_types SET default_schema = $1 WHERE name = $2")
+                .bind(default_schema)
+                .bind(location_type)
+                .execute(db)
+                .await?;
+
+            Ok(())
+        }
+
+        pub async fn update_item<'e>(
+            db: impl Executor<'e, Database = Sqlite>,
+            id: i64,
+            metadata: &str,
+        ) -> Result<()> {
+            sqlx::query("UPDATE items SET item_metadata = $1 WHERE id = $2")
+                .bind(metadata)
+                .bind(id)
+                .execute(db)
+                .await?;
+
+            Ok(())
+        }
+
+        pub async fn update_location<'e>(
+            db: impl Executor<'e, Database = Sqlite>,
+            id: i64,
+            metadata: &str,
+        ) -> Result<()> {
+            sqlx::query("UPDATE location_metadata SET metadata = $1 WHERE id = $2")
+                .bind(metadata)
+                .bind(id)
+                .execute(db)
+                .await?;
+
+            Ok(())
         }
     }
 }
@@ -8,7 +8,9 @@ pub enum Error {
 
     ItemNotFound(i64),
     LocationMetadataNotFound(i64),
+    LocationTypeNotFound(String),
     SchemaNotFound(i64),
+    FieldGroupNotFound(i64),
     
     QueryNotFound(u32),
     QueryError(String),
@@ -21,7 +23,17 @@ pub enum Error {
 
     IntegerConversionError(String),
 
-    RedDbError(redb::Error),
+    MetadataPatchError(lib_commons::PatchError),
+    /// The metadata kept changing underneath a patch, see `lib_model_data::exec::edit_item`.
+    ConcurrentMetadataUpdate(i64),
+    SchemaError(String),
+    /// The metadata does not match its schema, see `ModelManager::with_validator`
+    ValidationError(Box<lib_commons::ValidationReport>),
+    /// The schema is still used by some metadata, see `lib_model_schema::exec::get_schema_usage`
+    SchemaInUse(String),
+
+    // Note : Boxed, `redb::Error` alone is 160 bytes
+    RedDbError(Box<redb::Error>),
 }
 
 impl From<sqlx::Error> for Error {
@@ -38,7 +50,7 @@ impl From<sqlx::Error> for Error {
 
 impl From<redb::Error> for Error {
     fn from(err: redb::Error) -> Self {
-        Self::RedDbError(err)
+        Self::RedDbError(Box::new(err))
     }
 }
 
@@ -48,9 +60,15 @@ impl From<std::num::TryFromIntError> for Error {
     }
 }
 
+impl From<lib_commons::PatchError> for Error {
+    fn from(err: lib_commons::PatchError) -> Self {
+        Self::MetadataPatchError(err)
+    }
+}
+
 impl std::error::Error for Error {}
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
+        write!(f, "{:?}", self)
     }
 }
@@ -7,16 +7,22 @@ mod store;
 
 mod image_store;
 
+mod validation;
+
 #[cfg(debug_assertions)]
 pub mod _dev_utils;
 
 use crate::image_store::ImageStore;
 pub use error::{Error, Result};
+pub use validation::{MetadataValidator, ValidationPolicy};
+use std::sync::Arc;
 
 pub struct ModelManager {
     db: Db,
     image_store: ImageStore,
     schema_db : Db,
+    validator: Option<Arc<dyn MetadataValidator>>,
+    validation_policy: ValidationPolicy,
 }
 
 impl ModelManager {
@@ -27,7 +33,36 @@ impl ModelManager {
 
         let schema_db = store::get_db_pool(schema_db_url).await?;
 
-        Ok(Self { db, image_store, schema_db })
+        Ok(Self {
+            db,
+            image_store,
+            schema_db,
+            validator: None,
+            validation_policy: ValidationPolicy::Off,
+        })
+    }
+
+    /// Check every metadata written through `lib_model_data::exec` with `validator`.
+    pub fn with_validator(
+        mut self,
+        validator: Arc<dyn MetadataValidator>,
+        policy: ValidationPolicy,
+    ) -> Self {
+        self.validator = Some(validator);
+        self.validation_policy = policy;
+        self
+    }
+
+    pub fn validator(&self) -> Option<&dyn MetadataValidator> {
+        self.validator.as_deref()
+    }
+
+    /// Always [`ValidationPolicy::Off`] without a validator.
+    pub fn validation_policy(&self) -> ValidationPolicy {
+        match self.validator {
+            Some(_) => self.validation_policy,
+            None => ValidationPolicy::Off,
+        }
     }
 
     pub fn db(&self) -> &Db {
@@ -0,0 +1,46 @@
+use lib_commons::{Reference, ValidationReport, ValueStore};
+
+use crate::Result;
+
+/// What the data layer does with metadata that does not match its schema.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValidationPolicy {
+    /// The write fails with [`crate::Error::ValidationError`]
+    Enforce,
+    /// Written anyway, the report goes to [`MetadataValidator::warn`]
+    Warn,
+    #[default]
+    Off,
+}
+
+/// Checks metadata before it is written, see [`crate::ModelManager::with_validator`].
+///
+/// Note : Implemented by `lib_schema::SchemaManager`, which can't be named here as it depends
+///        on this crate.
+pub trait MetadataValidator: Send + Sync {
+    /// Everything wrong with `metadata`, only an `Err` when it can't be checked at all.
+    fn report(&self, metadata: &ValueStore) -> Result<ValidationReport>;
+
+    /// The reference fields set in `metadata`, whose rows have to exist for it to be written.
+    fn references(&self, _metadata: &ValueStore) -> Result<Vec<Reference>> {
+        Ok(Vec::new())
+    }
+
+    /// `metadata` as it should be written, e.g. with its computed fields filled in.
+    fn materialize(&self, metadata: &ValueStore) -> Result<ValueStore> {
+        Ok(metadata.clone())
+    }
+
+    /// Same as [`MetadataValidator::materialize`], with the defaults of its schema filled in too.
+    fn normalize(&self, metadata: &ValueStore) -> Result<ValueStore> {
+        self.materialize(metadata)
+    }
+
+    /// `metadata` brought up to the current version of its schema, for what is read back.
+    fn migrate(&self, metadata: &ValueStore) -> Result<ValueStore> {
+        Ok(metadata.clone())
+    }
+
+    /// `metadata` is written although it does not match its schema, see [`ValidationPolicy::Warn`].
+    fn warn(&self, metadata: &ValueStore, report: &ValidationReport);
+}
@@ -0,0 +1,314 @@
+//! `#[derive(InviSchema)]`, see `lib_schema::InviSchema` for what gets generated.
+//!
+//! ```ignore
+//! #[derive(InviSchema)]
+//! #[invi(name = "Box")]
+//! struct Package {
+//!     label: String,
+//!     #[invi(default = 1)]
+//!     count: u32,
+//!     #[invi(dimension = Mass)]
+//!     weight: Measurement,
+//!     #[invi(rename = "lid_kind")]
+//!     lid: Option<Lid>,
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Expr, Fields, LitStr, parse_macro_input};
+
+#[proc_macro_derive(InviSchema, attributes(invi))]
+pub fn derive_invi_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct SchemaField {
+    ident: syn::Ident,
+    ty: syn::Type,
+    name: String,
+    default: Option<FieldDefault>,
+    field_type: Option<Expr>,
+    // `#[invi(dimension = ..)]`, required for measurements
+    dimension: Option<syn::Ident>,
+}
+
+enum FieldDefault {
+    // `#[invi(default)]`
+    Trait,
+    // `#[invi(default = ..)]`
+    Expr(Expr),
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut schema_name = ident.to_string();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("invi")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                schema_name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"..\"`"))
+            }
+        })?;
+    }
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "InviSchema can only be derived for structs",
+        ));
+    };
+
+    let Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "InviSchema needs a struct with named fields",
+        ));
+    };
+
+    let fields = named
+        .named
+        .iter()
+        .map(parse_field)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let commons = quote!(::lib_schema::__private::lib_commons);
+    let schema = quote!(::lib_schema);
+
+    // Note : Whether a field holds measurements is only known from its type, so it is checked
+    //        once the types are resolved
+    let dimension_checks = fields.iter().filter(|f| f.field_type.is_none()).map(|f| {
+        let SchemaField { ty, name, .. } = f;
+        let (measured, message) = match &f.dimension {
+            Some(_) => (
+                quote!(<#ty as #schema::SchemaValue>::MEASURED),
+                format!("`{name}` is not a measurement, `dimension` does not apply"),
+            ),
+            None => (
+                quote!(!<#ty as #schema::SchemaValue>::MEASURED),
+                format!("`{name}` holds measurements, it needs `#[invi(dimension = ..)]`"),
+            ),
+        };
+        quote!(const { assert!(#measured, #message) };)
+    });
+
+    let field_defs = fields.iter().map(|f| {
+        let SchemaField { ty, name, .. } = f;
+        let field_type = match (&f.field_type, &f.dimension) {
+            (Some(field_type), _) => quote!(#field_type),
+            (None, Some(dimension)) => quote! {
+                <#ty as #schema::SchemaValue>::field_type_in(#commons::Dimension::#dimension)
+            },
+            (None, None) => quote!(<#ty as #schema::SchemaValue>::field_type()),
+        };
+        let constraints = match &f.dimension {
+            Some(dimension) => quote! {
+                <#ty as #schema::SchemaValue>::constraints_in(#commons::Dimension::#dimension)
+            },
+            None => quote!(<#ty as #schema::SchemaValue>::constraints()),
+        };
+        let default = match &f.default {
+            Some(default) => {
+                let expr = default_expr(default, ty);
+                quote!(<#ty as #schema::SchemaValue>::to_value(&#expr))
+            }
+            None => quote!(#commons::Value::Null),
+        };
+
+        quote! {
+            #commons::Field::create(
+                #name,
+                #field_type,
+                <#ty as #schema::SchemaValue>::required(),
+                #default,
+            )
+            .with_constraints(#constraints)
+        }
+    });
+
+    let nested_schemas = fields.iter().map(|f| {
+        let ty = &f.ty;
+        quote!(schemas.extend(<#ty as #schema::SchemaValue>::schemas());)
+    });
+
+    let properties = fields.iter().map(|f| {
+        let SchemaField { ty, name, .. } = f;
+        quote! {
+            if let Some(schema_name) = <#ty as #schema::SchemaValue>::schema_name() {
+                properties.extend(<#ty as #schema::SchemaValue>::object_properties_schemas());
+                properties.insert(#name.to_string(), schema_name.to_string());
+            }
+        }
+    });
+
+    let to_values = fields.iter().map(|f| {
+        let SchemaField {
+            ident, ty, name, ..
+        } = f;
+        quote! {
+            values.insert(#name.to_string(), <#ty as #schema::SchemaValue>::to_value(&self.#ident));
+        }
+    });
+
+    let from_values = fields.iter().map(|f| {
+        let SchemaField {
+            ident, ty, name, ..
+        } = f;
+        let missing = match &f.default {
+            Some(default) => default_expr(default, ty),
+            None => quote! {
+                <#ty as #schema::SchemaValue>::from_value(&#commons::Value::Null)
+                    .ok_or_else(|| #schema::ConversionError::MissingField(#name.to_string()))?
+            },
+        };
+
+        quote! {
+            let #ident: #ty = match values.get(#name) {
+                Some(value) => <#ty as #schema::SchemaValue>::from_value(value)
+                    .ok_or_else(|| #schema::ConversionError::InvalidValue(#name.to_string()))?,
+                None => #missing,
+            };
+        }
+    });
+
+    let idents = fields.iter().map(|f| &f.ident);
+
+    Ok(quote! {
+        impl #impl_generics #schema::InviSchema for #ident #ty_generics #where_clause {
+            const SCHEMA_NAME: &'static str = #schema_name;
+
+            fn schema() -> #schema::Schema {
+                #(#dimension_checks)*
+                #schema::Schema::create(#schema_name, vec![#(#field_defs),*])
+            }
+
+            fn schemas() -> Vec<#schema::Schema> {
+                let mut schemas = vec![<Self as #schema::InviSchema>::schema()];
+                #(#nested_schemas)*
+                schemas
+            }
+
+            fn object_properties_schemas() -> ::std::collections::HashMap<String, String> {
+                let mut properties = ::std::collections::HashMap::new();
+                #(#properties)*
+                properties
+            }
+
+            fn to_values(&self) -> ::std::collections::BTreeMap<String, #commons::Value> {
+                let mut values = ::std::collections::BTreeMap::new();
+                #(#to_values)*
+                values
+            }
+
+            fn from_values(
+                values: &::std::collections::BTreeMap<String, #commons::Value>,
+            ) -> ::std::result::Result<Self, #schema::ConversionError> {
+                #(#from_values)*
+                Ok(Self { #(#idents),* })
+            }
+        }
+
+        impl #impl_generics #schema::SchemaValue for #ident #ty_generics #where_clause {
+            fn field_type() -> #commons::FieldType {
+                #commons::FieldType::Object
+            }
+
+            fn schema_name() -> Option<&'static str> {
+                Some(<Self as #schema::InviSchema>::SCHEMA_NAME)
+            }
+
+            fn schemas() -> Vec<#schema::Schema> {
+                <Self as #schema::InviSchema>::schemas()
+            }
+
+            fn object_properties_schemas() -> ::std::collections::HashMap<String, String> {
+                <Self as #schema::InviSchema>::object_properties_schemas()
+            }
+
+            fn to_value(&self) -> #commons::Value {
+                #commons::Value::Object(#schema::InviSchema::to_values(self))
+            }
+
+            fn from_value(value: &#commons::Value) -> Option<Self> {
+                <Self as #schema::InviSchema>::from_values(value.as_object()?).ok()
+            }
+        }
+
+        impl #impl_generics From<#ident #ty_generics> for #commons::ValueStore #where_clause {
+            fn from(value: #ident #ty_generics) -> Self {
+                #schema::InviSchema::to_value_store(&value)
+            }
+        }
+
+        impl #impl_generics TryFrom<#commons::ValueStore> for #ident #ty_generics #where_clause {
+            type Error = #schema::ConversionError;
+
+            fn try_from(value: #commons::ValueStore) -> ::std::result::Result<Self, Self::Error> {
+                <Self as #schema::InviSchema>::from_value_store(&value)
+            }
+        }
+    })
+}
+
+fn parse_field(field: &syn::Field) -> syn::Result<SchemaField> {
+    let ident = field.ident.clone().expect("only called on named fields");
+
+    let mut name = ident.to_string();
+    let mut default = None;
+    let mut field_type = None;
+    let mut dimension = None;
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("invi")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                default = Some(if meta.input.peek(syn::Token![=]) {
+                    FieldDefault::Expr(meta.value()?.parse()?)
+                } else {
+                    FieldDefault::Trait
+                });
+                Ok(())
+            } else if meta.path.is_ident("field_type") {
+                field_type = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("dimension") {
+                dimension = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "expected `rename = \"..\"`, `default`, `field_type = ..` or `dimension = ..`",
+                ))
+            }
+        })?;
+    }
+
+    Ok(SchemaField {
+        ident,
+        ty: field.ty.clone(),
+        name,
+        default,
+        field_type,
+        dimension,
+    })
+}
+
+fn default_expr(default: &FieldDefault, ty: &syn::Type) -> TokenStream2 {
+    match default {
+        FieldDefault::Trait => quote!(<#ty as ::std::default::Default>::default()),
+        FieldDefault::Expr(expr) => quote!({
+            let default: #ty = #expr;
+            default
+        }),
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use lib_commons::{Field, Regex, Rule};
+
+use crate::registry::Error as RegistryError;
+use crate::{Expression, ExpressionError, Schema};
+
+type Parsed = Option<Result<Expression, ExpressionError>>;
+
+/// A resolved schema ready to validate values against: its fields indexed by name and its
+/// expressions parsed once, instead of on every value.
+#[derive(Debug)]
+pub(crate) struct CompiledSchema {
+    schema: Arc<Schema>,
+    fields: HashMap<String, usize>,
+    /// The expression of each field, `None` for the ones that are not computed
+    expressions: Vec<Parsed>,
+    /// The condition of each rule, `None` for the ones without any
+    conditions: Vec<Parsed>,
+    /// The `pattern` constraint of each field, `None` for the ones without any
+    patterns: Vec<Option<Regex>>,
+}
+
+impl CompiledSchema {
+    /// `schema` is expected to be resolved already, see [`crate::Registry::resolve`].
+    ///
+    /// Only fails on a pattern that does not compile, see [`Schema::check_patterns`].
+    pub fn compile(schema: Arc<Schema>) -> Result<Self, RegistryError> {
+        schema.check_patterns()?;
+        let fields = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (field.name().to_string(), index))
+            .collect();
+
+        let expressions = schema
+            .fields()
+            .iter()
+            .map(|field| field.expression().map(Expression::parse))
+            .collect();
+
+        let conditions = schema
+            .rules()
+            .iter()
+            .map(|rule| match rule {
+                Rule::RequiredIf { when, .. } => Some(Expression::parse(when)),
+                _ => None,
+            })
+            .collect();
+
+        let patterns = schema
+            .fields()
+            .iter()
+            .map(|field| field.constraints().compile_pattern().ok().flatten())
+            .collect();
+
+        Ok(CompiledSchema {
+            schema,
+            fields,
+            expressions,
+            conditions,
+            patterns,
+        })
+    }
+
+    pub fn schema(&self) -> &Arc<Schema> {
+        &self.schema
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        self.schema.fields()
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        self.schema.rules()
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<&Field> {
+        self.fields
+            .get(name)
+            .map(|index| &self.schema.fields()[*index])
+    }
+
+    /// The parsed expression of the field at `index` of [`CompiledSchema::fields`].
+    pub fn expression(&self, index: usize) -> Option<&Result<Expression, ExpressionError>> {
+        self.expressions[index].as_ref()
+    }
+
+    /// The compiled pattern of the field at `index` of [`CompiledSchema::fields`].
+    pub fn pattern(&self, index: usize) -> Option<&Regex> {
+        self.patterns[index].as_ref()
+    }
+
+    /// The parsed condition of the rule at `index` of [`CompiledSchema::rules`].
+    pub fn condition(&self, index: usize) -> Option<&Result<Expression, ExpressionError>> {
+        self.conditions[index].as_ref()
+    }
+}
+
+/// The schemas compiled so far, all of them dropped as soon as the registry they come from
+/// changes, see [`crate::Registry::generation`].
+#[derive(Debug, Default)]
+pub(crate) struct CompiledCache {
+    generation: u64,
+    schemas: HashMap<String, Arc<CompiledSchema>>,
+}
+
+impl CompiledCache {
+    pub fn get(&self, generation: u64, name: &str) -> Option<Arc<CompiledSchema>> {
+        if self.generation != generation {
+            return None;
+        }
+
+        self.schemas.get(name).cloned()
+    }
+
+    pub fn insert(&mut self, generation: u64, name: &str, schema: Arc<CompiledSchema>) {
+        if self.generation != generation {
+            self.schemas.clear();
+            self.generation = generation;
+        }
+
+        self.schemas.insert(name.to_string(), schema);
+    }
+}
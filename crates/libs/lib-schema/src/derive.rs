@@ -0,0 +1,494 @@
+use crate::Schema;
+use lib_commons::{
+    Constraints, Decimal, Dimension, FieldType, Measurement, Number, Value, ValueStore,
+};
+use std::collections::{BTreeMap, HashMap};
+
+pub use error::ConversionError;
+
+/// A Rust struct backed by a [`Schema`], usually through `#[derive(InviSchema)]`.
+///
+/// Converting to a [`ValueStore`] fills `schema_name` and `object_properties_schemas`, and
+/// converting back gives the exact same struct.
+pub trait InviSchema: Sized {
+    const SCHEMA_NAME: &'static str;
+
+    fn schema() -> Schema;
+
+    /// [`InviSchema::schema`] followed by the schemas of every nested object.
+    fn schemas() -> Vec<Schema>;
+
+    fn object_properties_schemas() -> HashMap<String, String>;
+
+    fn to_values(&self) -> BTreeMap<String, Value>;
+
+    fn from_values(values: &BTreeMap<String, Value>) -> Result<Self, ConversionError>;
+
+    fn to_value_store(&self) -> ValueStore {
+        let values = self.to_values();
+
+        // Optional objects that are not set have nothing to be validated against, at any depth
+        let mut properties = Self::object_properties_schemas();
+        properties.retain(|field, _| is_set(&values, field));
+
+        ValueStore::builder()
+            .with_schema(Self::SCHEMA_NAME)
+            .with_object_properties_schemas(properties)
+            .with_values(values)
+            .build()
+    }
+
+    fn from_value_store(store: &ValueStore) -> Result<Self, ConversionError> {
+        if let Some(found) = store.schema_name()
+            && found != Self::SCHEMA_NAME
+        {
+            return Err(ConversionError::SchemaMismatch {
+                expected: Self::SCHEMA_NAME.to_string(),
+                found: found.clone(),
+            });
+        }
+
+        Self::from_values(store.get_all())
+    }
+}
+
+// Note : `object_properties_schemas` is keyed by field name only, so nested objects count too
+fn is_set(values: &BTreeMap<String, Value>, field: &str) -> bool {
+    values.iter().any(|(name, value)| {
+        (name == field && !value.is_null())
+            || match value {
+                Value::Object(object) => is_set(object, field),
+                Value::Array(array) => array.iter().any(|value| match value {
+                    Value::Object(object) => is_set(object, field),
+                    _ => false,
+                }),
+                _ => false,
+            }
+    })
+}
+
+/// How a Rust type maps to a field of a [`Schema`], used for every field of an [`InviSchema`].
+pub trait SchemaValue: Sized {
+    /// Whether the field holds measurements, the derive then needs `#[invi(dimension = ..)]`.
+    const MEASURED: bool = false;
+
+    fn field_type() -> FieldType;
+
+    /// [`SchemaValue::field_type`] of a field whose measurements are in `dimension`.
+    fn field_type_in(dimension: Dimension) -> FieldType {
+        let _ = dimension;
+        Self::field_type()
+    }
+
+    fn required() -> bool {
+        true
+    }
+
+    fn constraints() -> Constraints {
+        Constraints::default()
+    }
+
+    fn constraints_in(dimension: Dimension) -> Constraints {
+        let _ = dimension;
+        Self::constraints()
+    }
+
+    /// Set for nested [`InviSchema`]s, ends up in `object_properties_schemas`.
+    fn schema_name() -> Option<&'static str> {
+        None
+    }
+
+    fn schemas() -> Vec<Schema> {
+        Vec::new()
+    }
+
+    fn object_properties_schemas() -> HashMap<String, String> {
+        HashMap::new()
+    }
+
+    fn to_value(&self) -> Value;
+
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl SchemaValue for String {
+    fn field_type() -> FieldType {
+        FieldType::String
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_string()
+    }
+}
+
+impl SchemaValue for bool {
+    fn field_type() -> FieldType {
+        FieldType::Boolean
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Boolean(*self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+macro_rules! impl_schema_value_number {
+    ($($ty:ty => |$n:ident| $from:expr),* $(,)?) => {
+        $(
+            impl SchemaValue for $ty {
+                fn field_type() -> FieldType {
+                    FieldType::Number
+                }
+
+                fn to_value(&self) -> Value {
+                    Value::Number((*self).into())
+                }
+
+                fn from_value(value: &Value) -> Option<Self> {
+                    let $n = value.as_number()?;
+                    $from
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_value_number!(
+    u8 => |n| n.as_u64()?.try_into().ok(),
+    u16 => |n| n.as_u64()?.try_into().ok(),
+    u32 => |n| n.as_u64()?.try_into().ok(),
+    u64 => |n| n.as_u64(),
+    usize => |n| n.as_u64()?.try_into().ok(),
+    i8 => |n| n.as_i64()?.try_into().ok(),
+    i16 => |n| n.as_i64()?.try_into().ok(),
+    i32 => |n| n.as_i64()?.try_into().ok(),
+    i64 => |n| n.as_i64(),
+    Decimal => |n| Some(n.as_decimal()),
+    Number => |n| Some(n),
+);
+
+impl SchemaValue for f64 {
+    fn field_type() -> FieldType {
+        FieldType::Number
+    }
+
+    // Note : Non finite floats can't be stored, they end up as null
+    fn to_value(&self) -> Value {
+        Number::try_from(*self)
+            .map(Value::Number)
+            .unwrap_or(Value::Null)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(value.as_number()?.as_f64())
+    }
+}
+
+impl SchemaValue for Measurement {
+    const MEASURED: bool = true;
+
+    // Note : The dimension is not known from the type alone, the derive refuses measurement
+    //        fields without `#[invi(dimension = ..)]` so this is only a fallback
+    fn field_type() -> FieldType {
+        FieldType::Measurement(Dimension::Length)
+    }
+
+    fn field_type_in(dimension: Dimension) -> FieldType {
+        FieldType::Measurement(dimension)
+    }
+
+    fn to_value(&self) -> Value {
+        (*self).into()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_measurement()
+    }
+}
+
+impl<T: SchemaValue> SchemaValue for Option<T> {
+    const MEASURED: bool = T::MEASURED;
+
+    fn field_type() -> FieldType {
+        T::field_type()
+    }
+
+    fn field_type_in(dimension: Dimension) -> FieldType {
+        T::field_type_in(dimension)
+    }
+
+    fn required() -> bool {
+        false
+    }
+
+    fn constraints() -> Constraints {
+        T::constraints()
+    }
+
+    fn constraints_in(dimension: Dimension) -> Constraints {
+        T::constraints_in(dimension)
+    }
+
+    fn schema_name() -> Option<&'static str> {
+        T::schema_name()
+    }
+
+    fn schemas() -> Vec<Schema> {
+        T::schemas()
+    }
+
+    fn object_properties_schemas() -> HashMap<String, String> {
+        T::object_properties_schemas()
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Some(value) => value.to_value(),
+            None => Value::Null,
+        }
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => Some(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: SchemaValue> SchemaValue for Vec<T> {
+    const MEASURED: bool = T::MEASURED;
+
+    fn field_type() -> FieldType {
+        FieldType::Array
+    }
+
+    fn constraints() -> Constraints {
+        Constraints::default().items(T::field_type())
+    }
+
+    fn constraints_in(dimension: Dimension) -> Constraints {
+        Constraints::default().items(T::field_type_in(dimension))
+    }
+
+    // Note : An array of objects is registered in `object_properties_schemas` like a single
+    //        object, the validator checks every element against it.
+    fn schema_name() -> Option<&'static str> {
+        T::schema_name()
+    }
+
+    fn schemas() -> Vec<Schema> {
+        T::schemas()
+    }
+
+    fn object_properties_schemas() -> HashMap<String, String> {
+        T::object_properties_schemas()
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(T::to_value).collect())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_array()?.iter().map(T::from_value).collect()
+    }
+}
+
+mod error {
+    use std::fmt;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum ConversionError {
+        SchemaMismatch { expected: String, found: String },
+        MissingField(String),
+        InvalidValue(String),
+    }
+
+    impl fmt::Display for ConversionError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for ConversionError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::Registry;
+    use crate::{ConversionError, InviSchema, SchemaManager};
+    use lib_commons::{Dimension, FieldType, Measurement, Unit, Value, ValueStore};
+    use std::sync::{Arc, RwLock};
+
+    #[derive(Debug, Clone, PartialEq, InviSchema)]
+    struct Lid {
+        color: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, InviSchema)]
+    #[invi(name = "Box")]
+    struct Package {
+        label: String,
+        #[invi(default = 1)]
+        count: u32,
+        note: Option<String>,
+        #[invi(rename = "size", dimension = Length)]
+        width: Measurement,
+        #[invi(dimension = Mass)]
+        weight: Option<Measurement>,
+        tags: Vec<String>,
+        lid: Option<Lid>,
+        #[invi(dimension = Volume)]
+        doses: Vec<Measurement>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, InviSchema)]
+    struct Shelf {
+        lids: Vec<Lid>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, InviSchema)]
+    struct Handle {
+        grip: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, InviSchema)]
+    struct HandledLid {
+        handle: Option<Handle>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, InviSchema)]
+    struct Crate {
+        lid: HandledLid,
+    }
+
+    #[test]
+    fn test_derived_schema() {
+        let schema = Package::schema();
+
+        assert_eq!(schema.name(), "Box");
+        assert!(schema.get_field("label").unwrap().required());
+        assert!(!schema.get_field("note").unwrap().required());
+        assert!(schema.get_field("width").is_none());
+        assert_eq!(
+            schema.get_field("weight").unwrap().dimension(),
+            Some(Dimension::Mass)
+        );
+        assert_eq!(
+            schema.get_field("count").unwrap().default_value(),
+            &Value::Number(1.into())
+        );
+        assert_eq!(
+            schema.get_field("tags").unwrap().constraints().item_type(),
+            Some(&FieldType::String)
+        );
+        assert_eq!(
+            schema.get_field("doses").unwrap().constraints().item_type(),
+            Some(&FieldType::Measurement(Dimension::Volume))
+        );
+
+        let names: Vec<_> = Package::schemas().iter().map(|s| s.name()).collect();
+        assert_eq!(names, vec!["Box", "Lid"]);
+
+        // Arrays of objects point at the schema of their elements
+        assert_eq!(
+            Shelf::object_properties_schemas().get("lids").map(String::as_str),
+            Some("Lid")
+        );
+    }
+
+    #[test]
+    fn test_derived_value_store_roundtrip() {
+        let package = Package {
+            label: "Screws".into(),
+            count: 40,
+            note: None,
+            width: Measurement::new(3, Unit::Cm),
+            weight: None,
+            tags: vec!["metal".into()],
+            lid: Some(Lid {
+                color: "red".into(),
+            }),
+            doses: vec![Measurement::new(5, Unit::Ml)],
+        };
+
+        let store: ValueStore = package.clone().into();
+
+        assert_eq!(store.schema_name().as_deref(), Some("Box"));
+        assert_eq!(
+            store
+                .object_properties_schemas()
+                .get("lid")
+                .map(String::as_str),
+            Some("Lid")
+        );
+        assert_eq!(store.get_path_as::<String>("lid.color").unwrap(), "red");
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager.register_derived::<Package>().unwrap();
+        assert!(schema_manager.validate(&store).is_ok());
+
+        let without_lid: ValueStore = Package {
+            lid: None,
+            ..package.clone()
+        }
+        .into();
+        assert!(without_lid.object_properties_schemas().is_empty());
+
+        // Measurements are stored in their base unit
+        let back = Package::try_from(store).unwrap();
+        assert_eq!(back.width, Measurement::new(30, Unit::Mm));
+        assert_eq!(
+            back,
+            Package {
+                width: Measurement::new(30, Unit::Mm),
+                ..package
+            }
+        );
+
+        let store = ValueStore::builder()
+            .with_schema("Box")
+            .string("label", "Nails")
+            .measurement("size", Measurement::new(1, Unit::Mm))
+            .build();
+        assert!(matches!(
+            Package::try_from(store),
+            Err(ConversionError::MissingField(field)) if field == "tags"
+        ));
+
+        let store = ValueStore::builder().with_schema("Lid").build();
+        assert!(matches!(
+            Package::try_from(store),
+            Err(ConversionError::SchemaMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_derived_nested_object_properties() {
+        let store: ValueStore = Crate {
+            lid: HandledLid {
+                handle: Some(Handle {
+                    grip: "rubber".into(),
+                }),
+            },
+        }
+        .into();
+
+        let properties = store.object_properties_schemas();
+        assert_eq!(properties.get("lid").map(String::as_str), Some("HandledLid"));
+        assert_eq!(properties.get("handle").map(String::as_str), Some("Handle"));
+
+        let without_handle: ValueStore = Crate {
+            lid: HandledLid { handle: None },
+        }
+        .into();
+        assert!(!without_handle.object_properties_schemas().contains_key("handle"));
+    }
+}
@@ -0,0 +1,739 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use lib_commons::{Decimal, Number, Value};
+use rust_decimal::prelude::ToPrimitive;
+
+pub use error::ExpressionError;
+
+type Result<T> = core::result::Result<T, ExpressionError>;
+
+/// The formula of a computed field, e.g. `width * height * depth` or `brand + " " + model`.
+///
+/// Only reads the sibling fields of the one it computes, with :
+/// - number, string, `true`, `false` and `null` literals
+/// - `+ - * / %` on numbers, `+` also joining strings
+/// - `== != < <= > >=`, `&& || !` and parentheses
+/// - `round(x)`, `round(x, digits)`, `min(..)`, `max(..)`, `abs(x)`, `upper(s)` and `lower(s)`
+///
+/// A measurement is read as its quantity in the base unit. Anything computed from an absent
+/// or null field is null.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    root: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Literal(Value),
+    Field(String),
+    Not(Box<Node>),
+    Negate(Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Round,
+    Min,
+    Max,
+    Abs,
+    Upper,
+    Lower,
+}
+
+impl BinaryOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::Remainder => "%",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessOrEqual => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterOrEqual => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        }
+    }
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "round" => Some(Function::Round),
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            "abs" => Some(Function::Abs),
+            "upper" => Some(Function::Upper),
+            "lower" => Some(Function::Lower),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Function::Round => "round",
+            Function::Min => "min",
+            Function::Max => "max",
+            Function::Abs => "abs",
+            Function::Upper => "upper",
+            Function::Lower => "lower",
+        }
+    }
+
+    fn accepts(&self, arguments: usize) -> bool {
+        match self {
+            Function::Round => (1..=2).contains(&arguments),
+            Function::Min | Function::Max => arguments >= 1,
+            Function::Abs | Function::Upper | Function::Lower => arguments == 1,
+        }
+    }
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+
+        let root = parser.or()?;
+        if let Some((position, token)) = parser.tokens.get(parser.position) {
+            return Err(ExpressionError::UnexpectedToken {
+                position: *position,
+                token: token.to_string(),
+            });
+        }
+
+        Ok(Expression { root })
+    }
+
+    /// The fields the expression reads, in the order they appear.
+    pub fn fields(&self) -> Vec<&str> {
+        let mut fields = Vec::new();
+        self.root.collect_fields(&mut fields);
+        fields
+    }
+
+    pub fn evaluate(&self, values: &BTreeMap<String, Value>) -> Result<Value> {
+        self.root.evaluate(values)
+    }
+}
+
+impl Node {
+    fn collect_fields<'a>(&'a self, fields: &mut Vec<&'a str>) {
+        match self {
+            Node::Literal(_) => {}
+            Node::Field(name) => {
+                if !fields.contains(&name.as_str()) {
+                    fields.push(name);
+                }
+            }
+            Node::Not(inner) | Node::Negate(inner) => inner.collect_fields(fields),
+            Node::Binary(_, left, right) => {
+                left.collect_fields(fields);
+                right.collect_fields(fields);
+            }
+            Node::Call(_, arguments) => arguments.iter().for_each(|a| a.collect_fields(fields)),
+        }
+    }
+
+    fn evaluate(&self, values: &BTreeMap<String, Value>) -> Result<Value> {
+        match self {
+            Node::Literal(value) => Ok(value.clone()),
+            Node::Field(name) => Ok(match values.get(name) {
+                // Note : Objects too, a measurement read back from storage is one
+                Some(value) => match value.as_measurement() {
+                    Some(m) => Value::Number(m.to_base().quantity()),
+                    None => value.clone(),
+                },
+                None => Value::Null,
+            }),
+            Node::Not(inner) => match inner.evaluate(values)? {
+                Value::Null => Ok(Value::Null),
+                Value::Boolean(b) => Ok(Value::Boolean(!b)),
+                other => Err(ExpressionError::type_mismatch("!", &[&other])),
+            },
+            Node::Negate(inner) => match inner.evaluate(values)? {
+                Value::Null => Ok(Value::Null),
+                Value::Number(n) => Ok(number(-n.as_decimal())),
+                other => Err(ExpressionError::type_mismatch("-", &[&other])),
+            },
+            Node::Binary(op, left, right) => {
+                binary(*op, left.evaluate(values)?, right.evaluate(values)?)
+            }
+            Node::Call(function, arguments) => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(values))
+                    .collect::<Result<Vec<_>>>()?;
+
+                call(*function, arguments)
+            }
+        }
+    }
+}
+
+fn number(decimal: Decimal) -> Value {
+    Value::Number(Number::from(decimal.normalize()).normalize())
+}
+
+fn binary(op: BinaryOp, left: Value, right: Value) -> Result<Value> {
+    match op {
+        BinaryOp::Equal => return Ok(Value::Boolean(left == right)),
+        BinaryOp::NotEqual => return Ok(Value::Boolean(left != right)),
+        _ => {}
+    }
+
+    if left.is_null() || right.is_null() {
+        return Ok(Value::Null);
+    }
+
+    let mismatch = || ExpressionError::type_mismatch(op.symbol(), &[&left, &right]);
+
+    match (op, &left, &right) {
+        (BinaryOp::And, Value::Boolean(l), Value::Boolean(r)) => Ok(Value::Boolean(*l && *r)),
+        (BinaryOp::Or, Value::Boolean(l), Value::Boolean(r)) => Ok(Value::Boolean(*l || *r)),
+        (BinaryOp::Add, Value::String(l), r) => Ok(Value::String(format!("{}{}", l, text(r)))),
+        (BinaryOp::Add, l, Value::String(r)) => Ok(Value::String(format!("{}{}", text(l), r))),
+        (_, Value::Number(l), Value::Number(r)) => {
+            let (l, r) = (l.as_decimal(), r.as_decimal());
+
+            let result = match op {
+                BinaryOp::Add => l.checked_add(r),
+                BinaryOp::Subtract => l.checked_sub(r),
+                BinaryOp::Multiply => l.checked_mul(r),
+                BinaryOp::Divide | BinaryOp::Remainder if r.is_zero() => {
+                    return Err(ExpressionError::DivisionByZero);
+                }
+                BinaryOp::Divide => l.checked_div(r),
+                BinaryOp::Remainder => l.checked_rem(r),
+                BinaryOp::Less => return Ok(Value::Boolean(l < r)),
+                BinaryOp::LessOrEqual => return Ok(Value::Boolean(l <= r)),
+                BinaryOp::Greater => return Ok(Value::Boolean(l > r)),
+                BinaryOp::GreaterOrEqual => return Ok(Value::Boolean(l >= r)),
+                _ => return Err(mismatch()),
+            };
+
+            result.map(number).ok_or(ExpressionError::Overflow)
+        }
+        (_, Value::String(l), Value::String(r)) => match op {
+            BinaryOp::Less => Ok(Value::Boolean(l < r)),
+            BinaryOp::LessOrEqual => Ok(Value::Boolean(l <= r)),
+            BinaryOp::Greater => Ok(Value::Boolean(l > r)),
+            BinaryOp::GreaterOrEqual => Ok(Value::Boolean(l >= r)),
+            _ => Err(mismatch()),
+        },
+        _ => Err(mismatch()),
+    }
+}
+
+// Note : What a value looks like once joined to a string
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_value_string(),
+    }
+}
+
+fn call(function: Function, arguments: Vec<Value>) -> Result<Value> {
+    if arguments.iter().any(Value::is_null) {
+        return Ok(Value::Null);
+    }
+
+    let mismatch = || {
+        let arguments: Vec<&Value> = arguments.iter().collect();
+        ExpressionError::type_mismatch(function.name(), &arguments)
+    };
+
+    let decimals = || {
+        arguments
+            .iter()
+            .map(|argument| argument.as_number().map(|n| n.as_decimal()))
+            .collect::<Option<Vec<Decimal>>>()
+            .ok_or_else(mismatch)
+    };
+
+    match function {
+        Function::Round => {
+            let decimals = decimals()?;
+            let digits = match decimals.get(1) {
+                Some(digits) if digits.is_integer() && !digits.is_sign_negative() => {
+                    // Note : Not the mantissa, `2.0` is stored as 20 with a scale of 1
+                    digits.to_u32().ok_or_else(mismatch)?
+                }
+                Some(_) => return Err(mismatch()),
+                None => 0,
+            };
+
+            Ok(number(decimals[0].round_dp(digits)))
+        }
+        Function::Min => Ok(number(decimals()?.into_iter().min().unwrap_or_default())),
+        Function::Max => Ok(number(decimals()?.into_iter().max().unwrap_or_default())),
+        Function::Abs => Ok(number(decimals()?[0].abs())),
+        Function::Upper | Function::Lower => {
+            let Value::String(s) = &arguments[0] else {
+                return Err(mismatch());
+            };
+
+            Ok(Value::String(match function {
+                Function::Upper => s.to_uppercase(),
+                _ => s.to_lowercase(),
+            }))
+        }
+    }
+}
+
+// region : Parsing
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Number),
+    String(String),
+    Identifier(String),
+    Symbol(&'static str),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::String(s) => write!(f, "{:?}", s),
+            Token::Identifier(name) => f.write_str(name),
+            Token::Symbol(symbol) => f.write_str(symbol),
+        }
+    }
+}
+
+// Note : Longest first, so that `<=` is not read as `<` then `=`
+const SYMBOLS: [&str; 17] = [
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "(", ")", ",",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if c.is_ascii_digit() || c == '.' {
+            let mut end = position;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+
+            let literal = &source[position..end];
+            let number = Number::parse(literal).ok_or(ExpressionError::UnexpectedToken {
+                position,
+                token: literal.to_string(),
+            })?;
+            tokens.push((position, Token::Number(number)));
+            continue;
+        }
+
+        if c == '"' || c == '\'' {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) => string.push(escaped),
+                        None => return Err(ExpressionError::UnterminatedString { position }),
+                    },
+                    Some((_, end)) if end == c => break,
+                    Some((_, other)) => string.push(other),
+                    None => return Err(ExpressionError::UnterminatedString { position }),
+                }
+            }
+            tokens.push((position, Token::String(string)));
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let mut end = position;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push((
+                position,
+                Token::Identifier(source[position..end].to_string()),
+            ));
+            continue;
+        }
+
+        let Some(symbol) = SYMBOLS.iter().find(|s| source[position..].starts_with(**s)) else {
+            return Err(ExpressionError::UnexpectedToken {
+                position,
+                token: c.to_string(),
+            });
+        };
+        for _ in 0..symbol.len() {
+            chars.next();
+        }
+        tokens.push((position, Token::Symbol(symbol)));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<()> {
+        if self.eat(symbol) {
+            return Ok(());
+        }
+        Err(self.unexpected())
+    }
+
+    fn unexpected(&self) -> ExpressionError {
+        match self.tokens.get(self.position) {
+            Some((position, token)) => ExpressionError::UnexpectedToken {
+                position: *position,
+                token: token.to_string(),
+            },
+            None => ExpressionError::UnexpectedEnd,
+        }
+    }
+
+    fn binary(
+        &mut self,
+        operators: &[(&str, BinaryOp)],
+        operand: fn(&mut Self) -> Result<Node>,
+    ) -> Result<Node> {
+        let mut left = operand(self)?;
+
+        'outer: loop {
+            for (symbol, op) in operators {
+                if self.eat(symbol) {
+                    let right = operand(self)?;
+                    left = Node::Binary(*op, Box::new(left), Box::new(right));
+                    continue 'outer;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn or(&mut self) -> Result<Node> {
+        self.binary(&[("||", BinaryOp::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Node> {
+        self.binary(&[("&&", BinaryOp::And)], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Node> {
+        self.binary(
+            &[
+                ("==", BinaryOp::Equal),
+                ("!=", BinaryOp::NotEqual),
+                ("<=", BinaryOp::LessOrEqual),
+                (">=", BinaryOp::GreaterOrEqual),
+                ("<", BinaryOp::Less),
+                (">", BinaryOp::Greater),
+            ],
+            Self::additive,
+        )
+    }
+
+    fn additive(&mut self) -> Result<Node> {
+        self.binary(
+            &[("+", BinaryOp::Add), ("-", BinaryOp::Subtract)],
+            Self::multiplicative,
+        )
+    }
+
+    fn multiplicative(&mut self) -> Result<Node> {
+        self.binary(
+            &[
+                ("*", BinaryOp::Multiply),
+                ("/", BinaryOp::Divide),
+                ("%", BinaryOp::Remainder),
+            ],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Node> {
+        if self.eat("!") {
+            return Ok(Node::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            return Ok(Node::Negate(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Node> {
+        let Some((position, token)) = self.tokens.get(self.position).cloned() else {
+            return Err(ExpressionError::UnexpectedEnd);
+        };
+
+        match token {
+            Token::Number(n) => {
+                self.position += 1;
+                Ok(Node::Literal(Value::Number(n)))
+            }
+            Token::String(s) => {
+                self.position += 1;
+                Ok(Node::Literal(Value::String(s)))
+            }
+            Token::Symbol("(") => {
+                self.position += 1;
+                let inner = self.or()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Token::Identifier(name) => {
+                self.position += 1;
+                match name.as_str() {
+                    "true" => return Ok(Node::Literal(Value::Boolean(true))),
+                    "false" => return Ok(Node::Literal(Value::Boolean(false))),
+                    "null" => return Ok(Node::Literal(Value::Null)),
+                    _ => {}
+                }
+
+                if !self.eat("(") {
+                    return Ok(Node::Field(name));
+                }
+
+                let function = Function::from_name(&name)
+                    .ok_or(ExpressionError::UnknownFunction(name.clone()))?;
+
+                let mut arguments = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        arguments.push(self.or()?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+
+                if !function.accepts(arguments.len()) {
+                    return Err(ExpressionError::WrongArgumentCount {
+                        function: name,
+                        count: arguments.len(),
+                    });
+                }
+
+                Ok(Node::Call(function, arguments))
+            }
+            Token::Symbol(symbol) => Err(ExpressionError::UnexpectedToken {
+                position,
+                token: symbol.to_string(),
+            }),
+        }
+    }
+}
+
+// endregion
+
+mod error {
+    use lib_commons::Value;
+    use std::fmt;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum ExpressionError {
+        // -- Parsing --
+        UnexpectedToken {
+            position: usize,
+            token: String,
+        },
+        UnexpectedEnd,
+        UnterminatedString {
+            position: usize,
+        },
+        UnknownFunction(String),
+        WrongArgumentCount {
+            function: String,
+            count: usize,
+        },
+
+        // -- Evaluation --
+        TypeMismatch {
+            operator: String,
+            operands: Vec<Value>,
+        },
+        DivisionByZero,
+        Overflow,
+    }
+
+    impl ExpressionError {
+        pub(crate) fn type_mismatch(operator: &str, operands: &[&Value]) -> Self {
+            ExpressionError::TypeMismatch {
+                operator: operator.to_string(),
+                operands: operands.iter().map(|v| (*v).clone()).collect(),
+            }
+        }
+    }
+
+    impl fmt::Display for ExpressionError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for ExpressionError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Expression, ExpressionError};
+    use lib_commons::{Decimal, Measurement, Unit, Value};
+    use std::collections::BTreeMap;
+
+    fn values() -> BTreeMap<String, Value> {
+        BTreeMap::from([
+            ("width".to_string(), Measurement::new(2, Unit::Cm).into()),
+            ("height".to_string(), Value::Number(3.into())),
+            ("depth".to_string(), Value::Number(4.into())),
+            ("brand".to_string(), Value::String("Makita".to_string())),
+            ("model".to_string(), Value::String("DF333".to_string())),
+            ("powered".to_string(), Value::Boolean(true)),
+        ])
+    }
+
+    fn evaluate(source: &str) -> Result<Value, ExpressionError> {
+        Expression::parse(source).unwrap().evaluate(&values())
+    }
+
+    #[test]
+    fn test_expression_evaluate() {
+        // The width is read in mm
+        assert_eq!(
+            evaluate("width * height * depth").unwrap(),
+            Value::Number(240.into())
+        );
+        assert_eq!(
+            evaluate("brand + \" \" + model").unwrap(),
+            Value::String("Makita DF333".to_string())
+        );
+        assert_eq!(evaluate("1 + 2 * 3 - -1").unwrap(), Value::Number(8.into()));
+        assert_eq!(
+            evaluate("(1 + 2) * 3 % 4").unwrap(),
+            Value::Number(1.into())
+        );
+        assert_eq!(
+            evaluate("round(10 / 3, 2)").unwrap(),
+            Value::Number(Decimal::new(333, 2).into())
+        );
+        assert_eq!(
+            evaluate("round(10 / 3, 2.0)").unwrap(),
+            Value::Number(Decimal::new(333, 2).into())
+        );
+        assert_eq!(
+            evaluate("max(height, depth, 1)").unwrap(),
+            Value::Number(4.into())
+        );
+        assert_eq!(
+            evaluate("upper(brand) + '-' + height").unwrap(),
+            Value::String("MAKITA-3".to_string())
+        );
+        assert_eq!(
+            evaluate("powered && height < depth || !powered").unwrap(),
+            Value::Boolean(true)
+        );
+
+        // Absent fields make the result null
+        assert_eq!(evaluate("height * missing").unwrap(), Value::Null);
+        assert_eq!(evaluate("missing == null").unwrap(), Value::Boolean(true));
+
+        assert_eq!(evaluate("height / 0"), Err(ExpressionError::DivisionByZero));
+        assert!(matches!(
+            evaluate("powered * 2"),
+            Err(ExpressionError::TypeMismatch { .. })
+        ));
+
+        assert_eq!(
+            Expression::parse("width * (height + depth) + width")
+                .unwrap()
+                .fields(),
+            vec!["width", "height", "depth"]
+        );
+    }
+
+    #[test]
+    fn test_expression_parse_errors() {
+        assert_eq!(
+            Expression::parse("width *"),
+            Err(ExpressionError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Expression::parse("width height"),
+            Err(ExpressionError::UnexpectedToken {
+                position: 6,
+                token: "height".to_string()
+            })
+        );
+        assert_eq!(
+            Expression::parse("'open"),
+            Err(ExpressionError::UnterminatedString { position: 0 })
+        );
+        assert_eq!(
+            Expression::parse("sqrt(width)"),
+            Err(ExpressionError::UnknownFunction("sqrt".to_string()))
+        );
+        assert!(matches!(
+            Expression::parse("abs(1, 2)"),
+            Err(ExpressionError::WrongArgumentCount { count: 2, .. })
+        ));
+        assert!(matches!(
+            Expression::parse("width # 2"),
+            Err(ExpressionError::UnexpectedToken { position: 6, .. })
+        ));
+    }
+}
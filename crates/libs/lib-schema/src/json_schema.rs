@@ -0,0 +1,687 @@
+use std::collections::HashMap;
+
+use lib_commons::{Constraints, Dimension, Field, FieldType, ReferenceTarget, Rule, Value};
+use serde_json::{Map, Value as JsonValue, json};
+
+use crate::registry::{self, Registry};
+use crate::{AdditionalProperties, Schema};
+
+pub use error::JsonSchemaError;
+
+pub const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+// Note : Keywords JSON Schema has no equivalent for, other tools ignore them
+const DIMENSION_KEYWORD: &str = "x-invi-dimension";
+const ADDITIONAL_PROPERTIES_KEYWORD: &str = "x-invi-additional-properties";
+const EXPRESSION_KEYWORD: &str = "x-invi-expression";
+const RULES_KEYWORD: &str = "x-invi-rules";
+const REFERENCE_KEYWORD: &str = "x-invi-reference";
+
+/// Constraints keywords, JSON Schema name first then the one of [`Constraints`].
+const CONSTRAINT_KEYWORDS: [(&str, &str); 7] = [
+    ("minimum", "min"),
+    ("maximum", "max"),
+    ("minLength", "min_length"),
+    ("maxLength", "max_length"),
+    ("pattern", "pattern"),
+    ("minItems", "min_items"),
+    ("maxItems", "max_items"),
+];
+
+/// The JSON Schema document of the schema `name`, inherited fields included.
+///
+/// `properties` are the `object_properties_schemas` of the values, objects (and arrays of
+/// objects) listed there become a `$ref` to a definition in `$defs`.
+pub(crate) fn export(
+    registry: &Registry,
+    name: &str,
+    properties: &HashMap<String, String>,
+) -> Result<JsonValue, registry::Error> {
+    let mut defs = Map::new();
+    let schema = export_schema(registry, name, properties, &mut defs)?;
+
+    let mut document = Map::new();
+    document.insert("$schema".to_string(), json!(DRAFT_2020_12));
+    document.insert("title".to_string(), json!(name));
+    document.extend(schema);
+    if !defs.is_empty() {
+        document.insert("$defs".to_string(), JsonValue::Object(defs));
+    }
+
+    Ok(JsonValue::Object(document))
+}
+
+fn export_schema(
+    registry: &Registry,
+    name: &str,
+    properties: &HashMap<String, String>,
+    defs: &mut Map<String, JsonValue>,
+) -> Result<Map<String, JsonValue>, registry::Error> {
+    let schema = registry.resolve(name)?;
+
+    let mut fields = Map::new();
+    let mut required = Vec::new();
+
+    for field in schema.fields() {
+        let mut keywords = match properties.get(field.name()) {
+            Some(reference) => {
+                // Placeholder first so that recursive schemas terminate
+                if !defs.contains_key(reference) {
+                    defs.insert(reference.clone(), JsonValue::Null);
+                    let def = export_schema(registry, reference, properties, defs)?;
+                    defs.insert(reference.clone(), JsonValue::Object(def));
+                }
+
+                let reference = json!({ "$ref": format!("#/$defs/{}", reference) });
+                match field.field_type() {
+                    FieldType::Array => json!({ "type": "array", "items": reference }),
+                    _ => reference,
+                }
+                .as_object()
+                .cloned()
+                .unwrap_or_default()
+            }
+            None => type_keywords(field.field_type()),
+        };
+
+        export_constraints(field.constraints(), &mut keywords);
+
+        if !field.default_value().is_null()
+            && let Ok(default) = serde_json::to_value(field.default_value())
+        {
+            keywords.insert("default".to_string(), default);
+        }
+
+        // Note : Computed on write, so never entered
+        if let Some(expression) = field.expression() {
+            keywords.insert(EXPRESSION_KEYWORD.to_string(), json!(expression));
+            keywords.insert("readOnly".to_string(), json!(true));
+        }
+
+        if field.required() {
+            required.push(field.name().to_string());
+        }
+        fields.insert(field.name().to_string(), JsonValue::Object(keywords));
+    }
+
+    let mut document = Map::new();
+    document.insert("type".to_string(), json!("object"));
+    document.insert("properties".to_string(), JsonValue::Object(fields));
+    if !required.is_empty() {
+        document.insert("required".to_string(), json!(required));
+    }
+
+    match schema.additional_properties() {
+        AdditionalProperties::Allow => {}
+        AdditionalProperties::Warn => {
+            document.insert(ADDITIONAL_PROPERTIES_KEYWORD.to_string(), json!("warn"));
+        }
+        AdditionalProperties::Reject => {
+            document.insert("additionalProperties".to_string(), json!(false));
+        }
+    }
+
+    if !schema.rules().is_empty() {
+        document.insert(RULES_KEYWORD.to_string(), json!(schema.rules()));
+    }
+
+    Ok(document)
+}
+
+fn type_keywords(field_type: &FieldType) -> Map<String, JsonValue> {
+    let keywords = match field_type {
+        FieldType::String => json!({ "type": "string" }),
+        FieldType::Number => json!({ "type": "number" }),
+        FieldType::Boolean => json!({ "type": "boolean" }),
+        FieldType::Array => json!({ "type": "array" }),
+        FieldType::Object => json!({ "type": "object" }),
+        FieldType::Measurement(dimension) => json!({
+            "type": "object",
+            DIMENSION_KEYWORD: dimension,
+            "properties": {
+                "quantity": { "type": "number" },
+                "unit": { "type": "string" }
+            },
+            "required": ["quantity", "unit"]
+        }),
+        FieldType::Date => json!({ "type": "string", "format": "date" }),
+        FieldType::DateTime => json!({ "type": "string", "format": "date-time" }),
+        FieldType::Enum(variants) => json!({ "type": "string", "enum": variants }),
+        FieldType::Url => json!({ "type": "string", "format": "uri" }),
+        FieldType::Email => json!({ "type": "string", "format": "email" }),
+        FieldType::Reference(target) => json!({ "type": "integer", REFERENCE_KEYWORD: target }),
+    };
+
+    keywords.as_object().cloned().unwrap_or_default()
+}
+
+fn export_constraints(constraints: &Constraints, keywords: &mut Map<String, JsonValue>) {
+    let Ok(JsonValue::Object(mut constraints)) = serde_json::to_value(constraints) else {
+        return;
+    };
+
+    for (keyword, name) in CONSTRAINT_KEYWORDS {
+        if let Some(value) = constraints.remove(name) {
+            keywords.insert(keyword.to_string(), value);
+        }
+    }
+
+    // A `$ref` to the element schema wins over the item type
+    if let Some(item_type) = constraints.get("items")
+        && !keywords.contains_key("items")
+        && let Ok(item_type) = serde_json::from_value::<FieldType>(item_type.clone())
+    {
+        keywords.insert(
+            "items".to_string(),
+            JsonValue::Object(type_keywords(&item_type)),
+        );
+    }
+}
+
+/// A keyword of an imported document that has no equivalent in a [`Schema`] and was left out.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct UnsupportedKeyword {
+    /// JSON pointer to the (sub)schema holding the keyword
+    pub path: String,
+    pub keyword: String,
+}
+
+/// The schemas read from a JSON Schema document, see [`JsonSchemaImport::parse`].
+#[derive(Debug, Clone)]
+pub struct JsonSchemaImport {
+    schemas: Vec<Schema>,
+    object_properties_schemas: HashMap<String, String>,
+    unsupported: Vec<UnsupportedKeyword>,
+}
+
+impl JsonSchemaImport {
+    /// Read a draft 2020-12 document, its `title` naming the main schema and every entry of
+    /// `$defs` becoming a schema of its own.
+    ///
+    /// Keywords that can't be represented are listed in [`JsonSchemaImport::unsupported`].
+    pub fn parse(document: &JsonValue) -> Result<Self, JsonSchemaError> {
+        let root = document
+            .as_object()
+            .ok_or(JsonSchemaError::NotAnObject(String::new()))?;
+
+        if let Some(draft) = root.get("$schema")
+            && draft != DRAFT_2020_12
+        {
+            return Err(JsonSchemaError::UnsupportedDraft(draft.to_string()));
+        }
+
+        let name = root
+            .get("title")
+            .and_then(JsonValue::as_str)
+            .ok_or(JsonSchemaError::MissingTitle)?;
+
+        let mut import = JsonSchemaImport {
+            schemas: Vec::new(),
+            object_properties_schemas: HashMap::new(),
+            unsupported: Vec::new(),
+        };
+
+        let defs = match root.get("$defs") {
+            Some(JsonValue::Object(defs)) => Some(defs),
+            Some(_) => return Err(JsonSchemaError::NotAnObject("/$defs".to_string())),
+            None => None,
+        };
+
+        let schema = import.parse_schema(name, root, "", defs)?;
+        import.schemas.push(schema);
+
+        for (def_name, def) in defs.into_iter().flatten() {
+            let path = format!("/$defs/{}", def_name);
+            let def = def
+                .as_object()
+                .ok_or_else(|| JsonSchemaError::NotAnObject(path.clone()))?;
+
+            let schema = import.parse_schema(def_name, def, &path, defs)?;
+            import.schemas.push(schema);
+        }
+
+        Ok(import)
+    }
+
+    /// The main schema followed by the definitions.
+    pub fn schemas(&self) -> &[Schema] {
+        &self.schemas
+    }
+
+    /// The objects that referenced a definition, to use when building the values.
+    pub fn object_properties_schemas(&self) -> &HashMap<String, String> {
+        &self.object_properties_schemas
+    }
+
+    pub fn unsupported(&self) -> &[UnsupportedKeyword] {
+        &self.unsupported
+    }
+
+    fn report_unsupported(&mut self, path: &str, keyword: &str) {
+        self.unsupported.push(UnsupportedKeyword {
+            path: path.to_string(),
+            keyword: keyword.to_string(),
+        });
+    }
+
+    fn parse_schema(
+        &mut self,
+        name: &str,
+        object: &Map<String, JsonValue>,
+        path: &str,
+        defs: Option<&Map<String, JsonValue>>,
+    ) -> Result<Schema, JsonSchemaError> {
+        let required: Vec<&str> = match object.get("required") {
+            Some(JsonValue::Array(required)) => {
+                required.iter().filter_map(JsonValue::as_str).collect()
+            }
+            Some(_) => {
+                return Err(JsonSchemaError::InvalidKeyword(format!(
+                    "{}/required",
+                    path
+                )));
+            }
+            None => Vec::new(),
+        };
+
+        let mut fields = Vec::new();
+        let mut additional_properties = AdditionalProperties::Allow;
+        let mut rules = Vec::new();
+
+        for (keyword, value) in object {
+            match keyword.as_str() {
+                "$schema" | "title" | "$defs" | "required" => {}
+                "type" if value == "object" => {}
+                "properties" => {
+                    let properties = value.as_object().ok_or_else(|| {
+                        JsonSchemaError::NotAnObject(format!("{}/properties", path))
+                    })?;
+
+                    for (field_name, property) in properties {
+                        let property_path = format!("{}/properties/{}", path, field_name);
+                        let property = property
+                            .as_object()
+                            .ok_or_else(|| JsonSchemaError::NotAnObject(property_path.clone()))?;
+
+                        fields.extend(self.parse_field(
+                            field_name,
+                            property,
+                            required.contains(&field_name.as_str()),
+                            &property_path,
+                            defs,
+                        )?);
+                    }
+                }
+                "additionalProperties" if value == false => {
+                    additional_properties = AdditionalProperties::Reject;
+                }
+                "additionalProperties" if value == true => {}
+                ADDITIONAL_PROPERTIES_KEYWORD if value == "warn" => {
+                    additional_properties = AdditionalProperties::Warn;
+                }
+                RULES_KEYWORD => match serde_json::from_value::<Vec<Rule>>(value.clone()) {
+                    Ok(parsed) => rules = parsed,
+                    Err(_) => self.report_unsupported(path, keyword),
+                },
+                _ => self.report_unsupported(path, keyword),
+            }
+        }
+
+        let mut schema =
+            Schema::create(name, fields).with_additional_properties(additional_properties);
+        schema.set_rules(rules);
+
+        Ok(schema)
+    }
+
+    // Note : `None` when the type of the property can't be represented, the field is left out
+    fn parse_field(
+        &mut self,
+        name: &str,
+        property: &Map<String, JsonValue>,
+        required: bool,
+        path: &str,
+        defs: Option<&Map<String, JsonValue>>,
+    ) -> Result<Option<Field>, JsonSchemaError> {
+        let mut field_type = match type_name(property) {
+            Some("string") => FieldType::String,
+            // References are integers too, the type is set once the target is read
+            Some("number") | Some("integer") => FieldType::Number,
+            Some("boolean") => FieldType::Boolean,
+            Some("array") => FieldType::Array,
+            Some("object") => FieldType::Object,
+            Some(_) => {
+                self.report_unsupported(path, "type");
+                return Ok(None);
+            }
+            None if property.contains_key("$ref") => FieldType::Object,
+            None if property.contains_key("enum") => FieldType::String,
+            None => return Err(JsonSchemaError::MissingType(path.to_string())),
+        };
+        let mut constraints = Map::new();
+        let mut default = Value::Null;
+        let mut expression = None;
+
+        for (keyword, value) in property {
+            match keyword.as_str() {
+                "type" => {}
+                "$ref" => {
+                    let reference = self.reference(value, &format!("{}/$ref", path), defs)?;
+                    self.object_properties_schemas
+                        .insert(name.to_string(), reference);
+                }
+                "format" => match (&field_type, value.as_str()) {
+                    (FieldType::String, Some("date")) => field_type = FieldType::Date,
+                    (FieldType::String, Some("date-time")) => field_type = FieldType::DateTime,
+                    (FieldType::String, Some("uri")) => field_type = FieldType::Url,
+                    (FieldType::String, Some("email")) => field_type = FieldType::Email,
+                    _ => self.report_unsupported(path, keyword),
+                },
+                "enum" => match serde_json::from_value::<Vec<String>>(value.clone()) {
+                    Ok(variants) if field_type == FieldType::String => {
+                        field_type = FieldType::Enum(variants)
+                    }
+                    _ => self.report_unsupported(path, keyword),
+                },
+                DIMENSION_KEYWORD => match serde_json::from_value::<Dimension>(value.clone()) {
+                    Ok(dimension) => field_type = FieldType::Measurement(dimension),
+                    Err(_) => self.report_unsupported(path, keyword),
+                },
+                REFERENCE_KEYWORD => {
+                    match serde_json::from_value::<ReferenceTarget>(value.clone()) {
+                        Ok(target) => field_type = FieldType::Reference(target),
+                        Err(_) => self.report_unsupported(path, keyword),
+                    }
+                }
+                // The shape of a measurement, already known from its dimension
+                "properties" | "required" if property.contains_key(DIMENSION_KEYWORD) => {}
+                "items" => {
+                    let items_path = format!("{}/items", path);
+                    let items = value
+                        .as_object()
+                        .ok_or_else(|| JsonSchemaError::NotAnObject(items_path.clone()))?;
+
+                    if let Some(reference) = items.get("$ref") {
+                        let reference =
+                            self.reference(reference, &format!("{}/$ref", items_path), defs)?;
+                        self.object_properties_schemas
+                            .insert(name.to_string(), reference);
+                        constraints.insert("items".to_string(), json!(FieldType::Object));
+                    } else if let Some(item) =
+                        self.parse_field(name, items, false, &items_path, defs)?
+                    {
+                        constraints.insert("items".to_string(), json!(item.field_type()));
+                    }
+                }
+                "default" => match Value::try_from(value) {
+                    Ok(value) => default = value,
+                    Err(_) => self.report_unsupported(path, keyword),
+                },
+                EXPRESSION_KEYWORD => match value.as_str() {
+                    Some(source) => expression = Some(source),
+                    None => self.report_unsupported(path, keyword),
+                },
+                // Implied by the expression
+                "readOnly" if property.contains_key(EXPRESSION_KEYWORD) => {}
+                keyword => match CONSTRAINT_KEYWORDS.iter().find(|(k, _)| *k == keyword) {
+                    Some((_, constraint)) => {
+                        constraints.insert(constraint.to_string(), value.clone());
+                    }
+                    None => self.report_unsupported(path, keyword),
+                },
+            }
+        }
+
+        let constraints = serde_json::from_value::<Constraints>(JsonValue::Object(constraints))
+            .map_err(|_| JsonSchemaError::InvalidKeyword(path.to_string()))?;
+
+        let field =
+            Field::create(name, field_type, required, default).with_constraints(constraints);
+
+        Ok(Some(match expression {
+            Some(expression) => field.with_expression(expression),
+            None => field,
+        }))
+    }
+
+    fn reference(
+        &self,
+        reference: &JsonValue,
+        path: &str,
+        defs: Option<&Map<String, JsonValue>>,
+    ) -> Result<String, JsonSchemaError> {
+        reference
+            .as_str()
+            .and_then(|r| r.strip_prefix("#/$defs/"))
+            .filter(|name| defs.is_some_and(|defs| defs.contains_key(*name)))
+            .map(str::to_string)
+            .ok_or_else(|| JsonSchemaError::UnresolvedReference(path.to_string()))
+    }
+}
+
+/// The `type` of a property, `["string", "null"]` being the same as `"string"` as every
+/// non required field can be null.
+fn type_name(property: &Map<String, JsonValue>) -> Option<&str> {
+    match property.get("type")? {
+        JsonValue::String(name) => Some(name),
+        JsonValue::Array(names) => {
+            let mut names = names
+                .iter()
+                .filter_map(JsonValue::as_str)
+                .filter(|name| *name != "null");
+
+            match (names.next(), names.next()) {
+                (Some(name), None) => Some(name),
+                _ => Some("union"),
+            }
+        }
+        _ => Some("invalid"),
+    }
+}
+
+mod error {
+    use std::fmt;
+
+    /// Problems that stop a document from being imported at all, the path being a JSON pointer.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum JsonSchemaError {
+        UnsupportedDraft(String),
+        MissingTitle,
+        NotAnObject(String),
+        MissingType(String),
+        InvalidKeyword(String),
+        /// Only `#/$defs/..` references are supported
+        UnresolvedReference(String),
+    }
+
+    impl fmt::Display for JsonSchemaError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for JsonSchemaError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::Registry;
+    use crate::{
+        AdditionalProperties, JsonSchemaError, JsonSchemaImport, Schema, SchemaManager,
+        UnsupportedKeyword,
+    };
+    use lib_commons::{Constraints, Dimension, Field, FieldType, ReferenceTarget, Rule, Value};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn box_schemas() -> Vec<Schema> {
+        vec![
+            Schema::create(
+                "Box",
+                vec![
+                    Field::create("label", FieldType::String, true, Value::Null)
+                        .with_constraints(Constraints::default().max_length(20)),
+                    Field::create("count", FieldType::Number, false, Value::Number(1.into()))
+                        .with_constraints(Constraints::default().min(0)),
+                    Field::create("tags", FieldType::Array, false, Value::Null).with_constraints(
+                        Constraints::default().max_items(3).items(FieldType::String),
+                    ),
+                    Field::create(
+                        "width",
+                        FieldType::Measurement(Dimension::Length),
+                        true,
+                        Value::Null,
+                    ),
+                    Field::create(
+                        "kind",
+                        FieldType::Enum(vec!["small".into(), "large".into()]),
+                        false,
+                        Value::Null,
+                    ),
+                    Field::create("lid", FieldType::Object, false, Value::Null),
+                    Field::create(
+                        "shelf",
+                        FieldType::Reference(ReferenceTarget::Location),
+                        false,
+                        Value::Null,
+                    ),
+                    Field::create("summary", FieldType::String, false, Value::Null)
+                        .with_expression("label + \" x\" + count"),
+                ],
+            )
+            .with_additional_properties(AdditionalProperties::Reject)
+            .with_rule(Rule::mutually_exclusive(&["kind", "tags"])),
+            Schema::create(
+                "Lid",
+                vec![Field::create("color", FieldType::String, true, Value::Null)],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_json_schema_roundtrip() {
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        for schema in box_schemas() {
+            schema_manager.register(&schema.name(), schema).unwrap();
+        }
+
+        let properties = HashMap::from([("lid".to_string(), "Lid".to_string())]);
+        let document = schema_manager
+            .export_json_schema("Box", &properties)
+            .unwrap();
+
+        assert_eq!(document["$schema"], crate::DRAFT_2020_12);
+        assert_eq!(document["required"], json!(["label", "width"]));
+        assert_eq!(document["additionalProperties"], false);
+        assert_eq!(
+            document["properties"]["tags"],
+            json!({ "type": "array", "maxItems": 3, "items": { "type": "string" } })
+        );
+        assert_eq!(
+            document["properties"]["count"],
+            json!({ "type": "number", "minimum": 0, "default": 1 })
+        );
+        assert_eq!(
+            document["properties"]["lid"],
+            json!({ "$ref": "#/$defs/Lid" })
+        );
+        assert_eq!(document["$defs"]["Lid"]["required"], json!(["color"]));
+
+        let import = JsonSchemaImport::parse(&document).unwrap();
+
+        assert!(import.unsupported().is_empty());
+        assert_eq!(import.object_properties_schemas(), &properties);
+
+        for (imported, original) in import.schemas().iter().zip(box_schemas()) {
+            assert_eq!(imported.name(), original.name());
+            assert_eq!(imported.fields(), original.fields());
+            assert_eq!(
+                imported.additional_properties(),
+                original.additional_properties()
+            );
+            assert_eq!(imported.rules(), original.rules());
+        }
+    }
+
+    #[test]
+    fn test_json_schema_import_unsupported() {
+        let document = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Shelf",
+            "type": "object",
+            "description": "A shelf",
+            "properties": {
+                "name": { "type": ["string", "null"], "minLength": 1 },
+                "level": { "type": "integer" },
+                "ip": { "type": "string", "format": "ipv4" },
+                "slot": { "type": ["string", "number"] },
+                "racks": { "type": "array", "items": { "$ref": "#/$defs/Rack" } }
+            },
+            "required": ["name"],
+            "$defs": {
+                "Rack": {
+                    "type": "object",
+                    "properties": { "slots": { "anyOf": [{ "type": "number" }], "type": "number" } }
+                }
+            }
+        });
+
+        let import = JsonSchemaImport::parse(&document).unwrap();
+
+        let shelf = &import.schemas()[0];
+        assert!(shelf.get_field("name").unwrap().required());
+        assert_eq!(
+            shelf.get_field("level").unwrap().field_type(),
+            &FieldType::Number
+        );
+        // Types that can't be represented leave the field out
+        assert!(shelf.get_field("slot").is_none());
+        assert_eq!(
+            shelf.get_field("ip").unwrap().field_type(),
+            &FieldType::String
+        );
+        assert_eq!(
+            shelf.get_field("racks").unwrap().constraints().item_type(),
+            Some(&FieldType::Object)
+        );
+        assert_eq!(
+            import
+                .object_properties_schemas()
+                .get("racks")
+                .map(String::as_str),
+            Some("Rack")
+        );
+
+        let unsupported = |path: &str, keyword: &str| UnsupportedKeyword {
+            path: path.to_string(),
+            keyword: keyword.to_string(),
+        };
+        assert_eq!(
+            import.unsupported(),
+            &[
+                unsupported("", "description"),
+                unsupported("/properties/ip", "format"),
+                unsupported("/properties/slot", "type"),
+                unsupported("/$defs/Rack/properties/slots", "anyOf"),
+            ]
+        );
+
+        let document = json!({
+            "title": "Shelf",
+            "properties": { "rack": { "$ref": "https://example.com/rack.json" } }
+        });
+        assert_eq!(
+            JsonSchemaImport::parse(&document).unwrap_err(),
+            JsonSchemaError::UnresolvedReference("/properties/rack/$ref".to_string())
+        );
+
+        let document = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "Shelf"
+        });
+        assert!(matches!(
+            JsonSchemaImport::parse(&document),
+            Err(JsonSchemaError::UnsupportedDraft(_))
+        ));
+    }
+}
@@ -1,113 +1,469 @@
+mod compiled;
+mod derive;
+mod expression;
+mod json_schema;
+mod loader;
+mod normalizer;
 mod registry;
 mod schema;
+mod shared;
 mod validator;
 
-use crate::Error::ValidationError;
-use crate::validator::{Validator, ValidatorError};
+// So that the code generated by `#[derive(InviSchema)]` also resolves inside this crate
+extern crate self as lib_schema;
+
+use crate::compiled::{CompiledCache, CompiledSchema};
+use crate::shared::SharedRegistry;
+use crate::validator::Validator;
 pub use error::{Error, Result};
-use lib_commons::ValueStore;
-pub use lib_commons::{Field, FieldType};
-use lib_model::ModelManager;
-pub use registry::Registry;
-pub use schema::Schema;
-use std::collections::HashMap;
+use lib_commons::{MigrationStep, Path, Value, ValueStore};
+pub use lib_commons::{Constraints, Field, FieldType, Reference, ReferenceTarget, Rule};
+use lib_model::{MetadataValidator, ModelManager};
+pub use registry::{BulkSchema, PendingChange, Registry, SchemaEvent};
+pub use loader::{FileError, LoadError, LoadReport, SchemaFormat, SchemaWatcher};
+pub use lib_commons::{ValidationReport, Violation, ViolationKind};
+pub use schema::{AdditionalProperties, FieldGroup, Schema};
+pub use derive::{ConversionError, InviSchema, SchemaValue};
+pub use expression::{Expression, ExpressionError};
+pub use json_schema::{DRAFT_2020_12, JsonSchemaError, JsonSchemaImport, UnsupportedKeyword};
+pub use lib_schema_derive::InviSchema;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+
+/// Called with the metadata written despite not matching its schema, see
+/// [`SchemaManager::with_warning_handler`].
+pub type WarningHandler = Box<dyn Fn(&ValueStore, &ValidationReport) + Send + Sync>;
 
 pub struct SchemaManager {
-    registry: Arc<RwLock<Registry>>,
+    registry: Arc<SharedRegistry>,
+    /// The resolved schemas values get validated against, see [`Registry::generation`]
+    compiled: RwLock<CompiledCache>,
+    on_warning: Option<WarningHandler>,
 }
 
 pub struct ValidationDescriptor<'a> {
-    main: Arc<Schema>,
-    properties: Arc<HashMap<&'a str, Arc<Schema>>>,
+    main: Arc<CompiledSchema>,
+    properties: HashMap<&'a str, Arc<CompiledSchema>>,
 }
 
 impl ValidationDescriptor<'_> {
     pub fn main(&self) -> Arc<Schema> {
-        self.main.clone()
+        self.main.schema().clone()
     }
 
     pub fn properties(&self) -> Arc<HashMap<&str, Arc<Schema>>> {
-        self.properties.clone()
+        Arc::new(
+            self.properties
+                .iter()
+                .map(|(field, schema)| (*field, schema.schema().clone()))
+                .collect(),
+        )
     }
 }
 
 impl SchemaManager {
     pub async fn new(mm: &ModelManager) -> Result<Self> {
         let registry = Registry::load_from_db(mm).await?;
-        Ok(SchemaManager {
-            registry: Arc::new(RwLock::new(registry)),
-        })
+        Ok(SchemaManager::from_registry(registry))
+    }
+
+    pub fn from_registry(registry: Registry) -> Self {
+        SchemaManager {
+            registry: Arc::new(SharedRegistry::new(registry)),
+            compiled: RwLock::new(CompiledCache::default()),
+            on_warning: None,
+        }
+    }
+
+    /// What to do with the metadata written under [`lib_model::ValidationPolicy::Warn`] that
+    /// does not match its schema, nothing by default.
+    pub fn with_warning_handler(
+        mut self,
+        handler: impl Fn(&ValueStore, &ValidationReport) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_warning = Some(Box::new(handler));
+        self
     }
 
     pub fn register(&self, name: &str, schema: Schema) -> Result<()> {
-        self.registry.write().map_err(|_| Error::LockError)?.register(name, schema);
+        schema.check_patterns()?;
+
+        self.registry.edit(|registry| registry.register(name, schema))
+    }
+
+    pub fn register_group(&self, name: &str, group: FieldGroup) -> Result<()> {
+        group.check_patterns()?;
+
+        self.registry.edit(|registry| registry.register_group(name, group))
+    }
+
+    /// Register the schema of `T` along with the ones of its nested objects.
+    pub fn register_derived<T: InviSchema>(&self) -> Result<()> {
+        for schema in T::schemas() {
+            self.register(&schema.name(), schema)?;
+        }
 
         Ok(())
     }
 
-    pub fn registry(&self) -> Arc<RwLock<Registry>> {
-        self.registry.clone()
+    /// The JSON Schema (draft 2020-12) of the schema `name`, `properties` being the
+    /// `object_properties_schemas` the nested objects are described with.
+    pub fn export_json_schema(
+        &self,
+        name: &str,
+        properties: &HashMap<String, String>,
+    ) -> Result<serde_json::Value> {
+        Ok(json_schema::export(&self.registry(), name, properties)?)
+    }
+
+    /// Register every schema of a JSON Schema document, check
+    /// [`JsonSchemaImport::unsupported`] for what got left out.
+    pub fn import_json_schema(&self, document: &serde_json::Value) -> Result<JsonSchemaImport> {
+        let import = JsonSchemaImport::parse(document)?;
+
+        for schema in import.schemas() {
+            self.register(&schema.name(), schema.clone())?;
+        }
+
+        Ok(import)
+    }
+
+    /// Register every schema file of `dir`, see [`Registry::load_dir`].
+    pub fn load_dir(&self, dir: impl AsRef<std::path::Path>) -> Result<LoadReport> {
+        Ok(self.registry.edit(|registry| registry.load_dir(dir))??)
+    }
+
+    /// Load `dir` then keep re-registering the files that change in it, until the watcher is dropped.
+    ///
+    /// The first report is the one of the initial load, the watcher sends one for each reload.
+    pub fn watch_dir(
+        &self,
+        dir: impl AsRef<std::path::Path>,
+    ) -> Result<(LoadReport, SchemaWatcher)> {
+        let report = self.load_dir(dir.as_ref())?;
+        let sources = report.sources().clone();
+        let watcher = SchemaWatcher::new(self.registry.clone(), dir.as_ref(), sources)?;
+
+        Ok((report, watcher))
+    }
+
+    /// The registry as it is now, later changes are not seen by it.
+    ///
+    /// Never waits, even on the writers.
+    pub fn registry(&self) -> Arc<Registry> {
+        self.registry.snapshot()
+    }
+
+    /// Change the registry, readers keep seeing the previous one until `edit` is done.
+    pub fn edit_registry<T>(&self, edit: impl FnOnce(&mut Registry) -> T) -> Result<T> {
+        self.registry.edit(edit)
+    }
+
+    /// Swap the whole registry, e.g. for the one reloaded from the schema db.
+    pub fn replace_registry(&self, registry: Registry) -> Result<()> {
+        self.registry.edit(|current| *current = registry)
+    }
+
+    /// Write the pending changes of the registry to the schema db, see [`Registry::flush`].
+    ///
+    /// The registry can still be read and edited while the db is written.
+    pub async fn flush(&self, mm: &ModelManager) -> Result<()> {
+        self.registry
+            .persist(async |registry: &mut Registry| registry.flush(mm).await)
+            .await
+    }
+
+    /// Persist the schema `name` alone, see [`Registry::finalize_schema`].
+    pub async fn finalize_schema(&self, mm: &ModelManager, name: &str) -> Result<Option<i64>> {
+        self.registry
+            .persist(async |registry: &mut Registry| registry.finalize_schema(mm, name).await)
+            .await
+    }
+
+    /// Persist the group `name` alone, see [`Registry::finalize_group`].
+    pub async fn finalize_group(&self, mm: &ModelManager, name: &str) -> Result<Option<i64>> {
+        self.registry
+            .persist(async |registry: &mut Registry| registry.finalize_group(mm, name).await)
+            .await
+    }
+
+    /// A [`SchemaEvent`] for every schema added, changed or removed from now on.
+    ///
+    /// A subscriber too far behind gets [`broadcast::error::RecvError::Lagged`], and should
+    /// reload what it has from [`SchemaManager::registry`].
+    pub fn subscribe(&self) -> broadcast::Receiver<SchemaEvent> {
+        self.registry.subscribe()
+    }
+
+    /// Move the schema `name` to a new version through `steps`, see [`Schema::evolve`].
+    pub fn evolve(&self, name: &str, steps: Vec<MigrationStep>) -> Result<u32> {
+        self.registry.edit(|registry| {
+            let schema = registry
+                .edit_schema(name)
+                .ok_or(registry::Error::SchemaNotFound(name.to_string()))?;
+
+            Ok(Arc::make_mut(schema).evolve(steps)?)
+        })?
+    }
+
+    /// A copy of `value` brought up to the current version of its schema, for the values read
+    /// back from the db. Values without a schema are returned as is.
+    pub fn migrate(&self, value: &ValueStore) -> Result<ValueStore> {
+        let Some(name) = value.schema_name() else {
+            return Ok(value.clone());
+        };
+
+        let schema = self
+            .registry()
+            .get_schema(name)
+            .ok_or(registry::Error::SchemaNotFound(name.to_string()))?;
+
+        let mut migrated = value.clone();
+        schema.migrate(&mut migrated)?;
+
+        Ok(migrated)
+    }
+
+    /// A copy of `value` where every absent (or null) field that has a default gets it,
+    /// nested objects included, and the computed fields are materialised (the measurements
+    /// being converted to their base unit, see [`SchemaManager::compute`]).
+    ///
+    /// Values whose schema is not registered are returned as is, validation reports those.
+    /// Unversioned values are taken as new ones and pinned to the current version.
+    pub fn normalize(&self, value: &ValueStore) -> Result<ValueStore> {
+        self.fill(value, |schema, values, properties| {
+            normalizer::fill_measurements(schema, values, properties);
+            normalizer::fill_defaults(schema, values, properties);
+            normalizer::fill_computed(schema, values, properties);
+        })
+    }
+
+    /// A copy of `value` with the computed fields materialised, nested objects included, see
+    /// [`Field::with_expression`]. A field that can't be computed is set to null.
+    ///
+    /// The measurement fields are converted to their base unit along the way, the objects shaped
+    /// as a measurement anywhere else are left as they are.
+    ///
+    /// Same as [`SchemaManager::normalize`] for values whose schema is not registered, and the
+    /// unversioned ones.
+    pub fn compute(&self, value: &ValueStore) -> Result<ValueStore> {
+        self.fill(value, |schema, values, properties| {
+            normalizer::fill_measurements(schema, values, properties);
+            normalizer::fill_computed(schema, values, properties);
+        })
+    }
+
+    fn fill(
+        &self,
+        value: &ValueStore,
+        fill: impl Fn(
+            &CompiledSchema,
+            &mut BTreeMap<String, Value>,
+            &HashMap<&str, Arc<CompiledSchema>>,
+        ),
+    ) -> Result<ValueStore> {
+        let Some(descriptor) = value.schema_descriptor() else {
+            return Ok(value.clone());
+        };
+
+        let registry = self.registry();
+
+        let Some(main_schema) = self.compiled(&registry, descriptor.main())? else {
+            return Ok(value.clone());
+        };
+
+        let mut properties = HashMap::new();
+        for (field, name) in descriptor.properties().as_ref() {
+            if let Some(schema) = self.compiled(&registry, name)? {
+                properties.insert(*field, schema);
+            }
+        }
+
+        let mut values = value.get_all().clone();
+        fill(&main_schema, &mut values, &properties);
+
+        let version = main_schema.schema().version();
+        Ok(ValueStore::builder()
+            .with_schema(descriptor.main())
+            .with_schema_version(value.schema_version().unwrap_or(version))
+            .with_object_properties_schemas(value.object_properties_schemas().as_ref().clone())
+            .with_values(values)
+            .build())
     }
 
+    /// Fails with [`Error::ValidationError`] holding every violation found, see [`SchemaManager::report`].
     pub fn validate(&self, value: &ValueStore) -> Result<()> {
-        let descriptor = value
-            .schema_descriptor()
-            .ok_or(ValidationError(ValidatorError::SchemaIdentifierMissing))?;
-
-        let main_schema = {
-            self.registry
-                .read()
-                .map_err(|_| Error::LockError)?
-                .get_schema(descriptor.main())
-                .ok_or(ValidationError(ValidatorError::SchemaNotFound(
-                    descriptor.main().to_string(),
-                )))?
+        let report = self.report(value)?;
+
+        if !report.is_valid() {
+            return Err(Error::ValidationError(report));
+        }
+
+        Ok(())
+    }
+
+    /// Validate `value` without stopping at the first problem.
+    ///
+    /// Only fails if the registry can't be read, an invalid value gives a non empty report.
+    pub fn report(&self, value: &ValueStore) -> Result<ValidationReport> {
+        let mut report = ValidationReport::default();
+
+        self.with_validator(value, &mut report, |validator, report| {
+            validator.validate(value, report)
+        })?;
+
+        Ok(report)
+    }
+
+    /// The reference fields set in `value`, nested objects included, see
+    /// [`FieldType::Reference`]. Values whose schema is not registered have none.
+    pub fn references(&self, value: &ValueStore) -> Result<Vec<Reference>> {
+        let mut report = ValidationReport::default();
+
+        let references =
+            self.with_validator(value, &mut report, |validator, _| validator.references(value))?;
+
+        Ok(references.unwrap_or_default())
+    }
+
+    // Note : `None` when `value` can't be validated at all, `report` says why
+    fn with_validator<T>(
+        &self,
+        value: &ValueStore,
+        report: &mut ValidationReport,
+        f: impl FnOnce(&Validator, &mut ValidationReport) -> T,
+    ) -> Result<Option<T>> {
+        let Some(descriptor) = value.schema_descriptor() else {
+            report.push(Violation::new(
+                Path::root(),
+                ViolationKind::SchemaIdentifierMissing,
+            ));
+            return Ok(None);
+        };
+
+        let registry = self.registry();
+
+        // Validated against every field it inherits, a broken hierarchy is an error of the registry
+        let Some(main_schema) = self.compiled(&registry, descriptor.main())? else {
+            report.push(Violation::new(
+                Path::root(),
+                ViolationKind::SchemaNotFound {
+                    schema: descriptor.main().to_string(),
+                },
+            ));
+            return Ok(None);
         };
 
         let mut properties = HashMap::new();
         for (field, name) in descriptor.properties().as_ref() {
-            let s = {
-                self.registry
-                    .read()
-                    .map_err(|_| Error::LockError)?
-                    .get_schema(name)
-                    .ok_or(ValidationError(ValidatorError::SchemaNotFound(
-                        name.to_string(),
-                    )))?
-            };
-            properties.entry(*field).or_insert(s);
+            match self.compiled(&registry, name)? {
+                Some(schema) => {
+                    properties.entry(*field).or_insert(schema);
+                }
+                None => report.push(Violation::new(
+                    Path::root().key(*field),
+                    ViolationKind::SchemaNotFound {
+                        schema: name.to_string(),
+                    },
+                )),
+            }
         }
 
         let validation_descriptor = ValidationDescriptor {
             main: main_schema,
-            properties: Arc::new(properties),
+            properties,
         };
 
         let validator = Validator::new(validation_descriptor);
 
-        validator.validate(value)?;
+        Ok(Some(f(&validator, report)))
+    }
 
-        Ok(())
+    // Note : `None` when `name` is not registered. Compiled at most once per generation of the
+    //        registry, `registry` being a snapshot it can't change in between
+    fn compiled(&self, registry: &Registry, name: &str) -> Result<Option<Arc<CompiledSchema>>> {
+        let generation = registry.generation();
+
+        let cached = self.compiled.read().map_err(|_| Error::LockError)?.get(generation, name);
+        if cached.is_some() {
+            return Ok(cached);
+        }
+
+        if registry.get_schema(name).is_none() {
+            return Ok(None);
+        }
+        let schema = Arc::new(CompiledSchema::compile(registry.resolve(name)?)?);
+
+        self.compiled
+            .write()
+            .map_err(|_| Error::LockError)?
+            .insert(generation, name, schema.clone());
+
+        Ok(Some(schema))
+    }
+}
+
+// Note : Lets the data layer check metadata before writing it, see `ModelManager::with_validator`
+impl MetadataValidator for SchemaManager {
+    fn report(&self, metadata: &ValueStore) -> lib_model::Result<ValidationReport> {
+        SchemaManager::report(self, metadata)
+            .map_err(|err| lib_model::Error::SchemaError(err.to_string()))
+    }
+
+    fn references(&self, metadata: &ValueStore) -> lib_model::Result<Vec<Reference>> {
+        SchemaManager::references(self, metadata)
+            .map_err(|err| lib_model::Error::SchemaError(err.to_string()))
+    }
+
+    fn materialize(&self, metadata: &ValueStore) -> lib_model::Result<ValueStore> {
+        self.compute(metadata).map_err(|err| lib_model::Error::SchemaError(err.to_string()))
+    }
+
+    fn normalize(&self, metadata: &ValueStore) -> lib_model::Result<ValueStore> {
+        SchemaManager::normalize(self, metadata)
+            .map_err(|err| lib_model::Error::SchemaError(err.to_string()))
     }
+
+    fn migrate(&self, metadata: &ValueStore) -> lib_model::Result<ValueStore> {
+        SchemaManager::migrate(self, metadata)
+            .map_err(|err| lib_model::Error::SchemaError(err.to_string()))
+    }
+
+    fn warn(&self, metadata: &ValueStore, report: &ValidationReport) {
+        if let Some(handler) = &self.on_warning {
+            handler(metadata, report);
+        }
+    }
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use lib_commons;
 }
 
 mod error {
     pub type Result<T> = core::result::Result<T, Error>;
 
-    use crate::{registry, validator};
+    use crate::{JsonSchemaError, LoadError, ValidationReport, registry};
+    use lib_commons::MigrationError;
 
     #[derive(Debug)]
     pub enum Error {
-        ValidationError(validator::ValidatorError),
+        ValidationError(ValidationReport),
         RegistryError(registry::Error),
+        JsonSchemaError(JsonSchemaError),
+        LoadError(LoadError),
+        MigrationError(MigrationError),
+        // Note : Boxed, `lib_model::Error` is large
+        ModelError(Box<lib_model::Error>),
 
         LockError,
     }
 
-    impl From<validator::ValidatorError> for Error {
-        fn from(value: validator::ValidatorError) -> Self {
-            Error::ValidationError(value)
+    impl From<lib_model::Error> for Error {
+        fn from(value: lib_model::Error) -> Self {
+            Error::ModelError(Box::new(value))
         }
     }
 
@@ -117,19 +473,44 @@ mod error {
         }
     }
 
+    impl From<LoadError> for Error {
+        fn from(value: LoadError) -> Self {
+            Error::LoadError(value)
+        }
+    }
+
+    impl From<MigrationError> for Error {
+        fn from(value: MigrationError) -> Self {
+            Error::MigrationError(value)
+        }
+    }
+
+    impl From<JsonSchemaError> for Error {
+        fn from(value: JsonSchemaError) -> Self {
+            Error::JsonSchemaError(value)
+        }
+    }
+
     impl std::error::Error for Error {}
 
     impl std::fmt::Display for Error {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            todo!()
+            write!(f, "{:?}", self)
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{SchemaManager, registry::Registry, schema::Schema};
-    use lib_commons::{Field, FieldType, Value, ValueStore};
+    use crate::{
+        AdditionalProperties, FieldGroup, SchemaManager, ViolationKind, registry::Registry,
+        schema::Schema,
+    };
+    use lib_commons::{
+        ConstraintViolation, Constraints, Dimension, Field, FieldType, Measurement, Unit, Value,
+        ValueStore,
+    };
+    use serde_json::json;
     use std::sync::{Arc, RwLock};
 
     #[test]
@@ -138,7 +519,7 @@ mod tests {
             "TestSchema",
             vec![
                 Field::create("name", FieldType::String, true, Value::Null),
-                Field::create("age", FieldType::Number, false, Value::Number(30)),
+                Field::create("age", FieldType::Number, false, Value::Number(30.into())),
             ],
         );
 
@@ -161,7 +542,7 @@ mod tests {
             "TestSchema",
             vec![
                 Field::create("name", FieldType::String, true, Value::Null),
-                Field::create("age", FieldType::Number, false, Value::Number(30)),
+                Field::create("age", FieldType::Number, false, Value::Number(30.into())),
             ],
         );
 
@@ -175,7 +556,7 @@ mod tests {
             "TestSchema",
             vec![
                 Field::create("name", FieldType::String, true, Value::Null),
-                Field::create("age", FieldType::Number, false, Value::Number(30)),
+                Field::create("age", FieldType::Number, false, Value::Number(30.into())),
                 Field::create("other", FieldType::Object, true, Value::Null),
             ],
         );
@@ -185,9 +566,7 @@ mod tests {
             vec![Field::create("a", FieldType::Number, true, Value::Null)],
         );
 
-        let schema_manager = SchemaManager {
-            registry: Arc::new(RwLock::new(Registry::new())),
-        };
+        let schema_manager = SchemaManager::from_registry(Registry::new());
 
         schema_manager.register("TestSchema", schema).unwrap();
 
@@ -219,7 +598,7 @@ mod tests {
             "TestSchema",
             vec![
                 Field::create("name", FieldType::String, true, Value::Null),
-                Field::create("age", FieldType::Number, false, Value::Number(30)),
+                Field::create("age", FieldType::Number, false, Value::Number(30.into())),
                 Field::create("other", FieldType::Object, true, Value::Null),
             ],
         );
@@ -229,9 +608,7 @@ mod tests {
             vec![Field::create("a", FieldType::Number, true, Value::Null)],
         );
 
-        let schema_manager = SchemaManager {
-            registry: Arc::new(RwLock::new(Registry::new())),
-        };
+        let schema_manager = SchemaManager::from_registry(Registry::new());
 
         schema_manager.register("TestSchema", schema).unwrap();
 
@@ -262,4 +639,1023 @@ mod tests {
 
         println!("{:?}", value);
     }
+
+    #[test]
+    fn test_measurement_validation() {
+        let schema = Schema::create(
+            "Box",
+            vec![
+                Field::create(
+                    "width",
+                    FieldType::Measurement(Dimension::Length),
+                    true,
+                    Value::Null,
+                ),
+                Field::create(
+                    "weight",
+                    FieldType::Measurement(Dimension::Mass),
+                    false,
+                    Value::Null,
+                ),
+                Field::create("dims", FieldType::Object, false, Value::Null),
+            ],
+        );
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+
+        schema_manager.register("Box", schema).unwrap();
+
+        let value = ValueStore::builder()
+            .with_schema("Box")
+            .measurement("width", Measurement::new(12, Unit::In))
+            .measurement("weight", Measurement::new(2, Unit::Lb))
+            .build();
+
+        assert!(schema_manager.validate(&value).is_ok());
+
+        let value = ValueStore::builder()
+            .with_schema("Box")
+            .measurement("width", Measurement::new(12, Unit::Kg))
+            .null("weight")
+            .build();
+
+        assert_eq!(
+            violations(schema_manager.validate(&value)),
+            vec![("/width".to_string(), ViolationKind::IncompatibleUnit)]
+        );
+
+        // Read back from storage, only the measurement field is taken as one
+        let raw = r#"{"schema_name":"Box","object_properties_schemas":{},"values":{"dims":{"quantity":5,"unit":"m"},"width":{"quantity":1.5,"unit":"m"}}}"#;
+        let value: ValueStore = serde_json::from_str(raw).unwrap();
+        assert_eq!(value.to_string(), raw);
+        assert!(schema_manager.validate(&value).is_ok());
+
+        let computed = schema_manager.compute(&value).unwrap();
+        assert_eq!(
+            computed.get("width"),
+            Some(&Value::Measurement(Measurement::new(1500, Unit::Mm)))
+        );
+        assert_eq!(computed.get("dims"), value.get("dims"));
+    }
+
+    #[test]
+    fn test_rich_field_types_validation() {
+        let schema = Schema::create(
+            "Purchase",
+            vec![
+                Field::create("bought_on", FieldType::Date, true, Value::Null),
+                Field::create(
+                    "status",
+                    FieldType::Enum(vec!["new".into(), "used".into()]),
+                    true,
+                    Value::Null,
+                ),
+                Field::create("shop", FieldType::Url, false, Value::Null),
+                Field::create("seller", FieldType::Email, false, Value::Null),
+            ],
+        );
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+
+        schema_manager.register("Purchase", schema).unwrap();
+
+        let value = ValueStore::builder()
+            .with_schema("Purchase")
+            .string("bought_on", "2025-03-14")
+            .string("status", "used")
+            .string("shop", "https://example.com/shop")
+            .string("seller", "seller@example.com")
+            .build();
+
+        assert!(schema_manager.validate(&value).is_ok());
+
+        let value = ValueStore::builder()
+            .with_schema("Purchase")
+            .string("bought_on", "2025-03-14")
+            .string("status", "broken")
+            .null("shop")
+            .null("seller")
+            .build();
+
+        assert_eq!(
+            violations(schema_manager.validate(&value)),
+            vec![("/status".to_string(), ViolationKind::InvalidFormat)]
+        );
+
+        let value = ValueStore::builder()
+            .with_schema("Purchase")
+            .number("bought_on", 20250314)
+            .string("status", "new")
+            .null("shop")
+            .null("seller")
+            .build();
+
+        assert_eq!(
+            violations(schema_manager.validate(&value)),
+            vec![("/bought_on".to_string(), ViolationKind::InvalidType)]
+        );
+    }
+
+    #[test]
+    fn test_constraints_validation() {
+        let schema = Schema::create(
+            "Part",
+            vec![
+                Field::create("code", FieldType::String, true, Value::Null)
+                    .with_constraints(Constraints::default().max_length(8).pattern("^P-")),
+                Field::create("stock", FieldType::Number, true, Value::Null)
+                    .with_constraints(Constraints::default().min(0)),
+                Field::create("sizes", FieldType::Array, false, Value::Null).with_constraints(
+                    Constraints::default()
+                        .min_items(1)
+                        .items(FieldType::Measurement(Dimension::Length)),
+                ),
+            ],
+        );
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+
+        schema_manager.register("Part", schema).unwrap();
+
+        let value = ValueStore::builder()
+            .with_schema("Part")
+            .string("code", "P-001")
+            .number("stock", 3)
+            .array(
+                "sizes",
+                Value::builder()
+                    .array()
+                    .push_measurement(Measurement::new(4, Unit::Cm)),
+                None,
+            )
+            .build();
+
+        assert!(schema_manager.validate(&value).is_ok());
+
+        let value = ValueStore::builder()
+            .with_schema("Part")
+            .string("code", "P-001")
+            .number("stock", -2)
+            .null("sizes")
+            .build();
+
+        let result = violations(schema_manager.validate(&value));
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].0, "/stock");
+        assert!(matches!(
+            result[0].1,
+            ViolationKind::ConstraintViolation(ConstraintViolation::Min { .. })
+        ));
+
+        let value = ValueStore::builder()
+            .with_schema("Part")
+            .string("code", "P-0000001")
+            .number("stock", 2)
+            .null("sizes")
+            .build();
+
+        assert_eq!(
+            violations(schema_manager.validate(&value)),
+            vec![(
+                "/code".to_string(),
+                ViolationKind::ConstraintViolation(ConstraintViolation::MaxLength {
+                    max_length: 8,
+                    length: 9
+                })
+            )]
+        );
+
+        // A broken pattern is refused up front instead of failing every value
+        let broken = Schema::create(
+            "Broken",
+            vec![
+                Field::create("code", FieldType::String, true, Value::Null)
+                    .with_constraints(Constraints::default().pattern("P-(")),
+            ],
+        );
+        assert!(matches!(
+            schema_manager.register("Broken", broken),
+            Err(crate::Error::RegistryError(crate::registry::Error::InvalidPattern { .. }))
+        ));
+        assert!(schema_manager.registry().get_schema("Broken").is_none());
+    }
+
+    #[test]
+    fn test_validation_report() {
+        let schema = Schema::create(
+            "TestSchema",
+            vec![
+                Field::create("name", FieldType::String, true, Value::Null),
+                Field::create("age", FieldType::Number, false, Value::Number(30.into())),
+                Field::create("email", FieldType::Email, true, Value::Null),
+                Field::create("other", FieldType::Object, true, Value::Null),
+            ],
+        );
+
+        let inner_schema = Schema::create(
+            "InnerSchema",
+            vec![
+                Field::create("a", FieldType::Number, true, Value::Null),
+                Field::create("b", FieldType::Boolean, true, Value::Null),
+            ],
+        );
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+
+        schema_manager.register("TestSchema", schema).unwrap();
+        schema_manager
+            .register("InnerSchema", inner_schema)
+            .unwrap();
+
+        let value = ValueStore::builder()
+            .with_schema("TestSchema")
+            .null("name")
+            .string("age", "twenty")
+            .object(
+                "other",
+                Value::builder().object().push_string("a", "ten").into_map(),
+                Some("InnerSchema"),
+            )
+            .build();
+
+        let report = schema_manager.report(&value).unwrap();
+
+        assert_eq!(
+            report
+                .violations()
+                .iter()
+                .map(|v| (v.path().to_string(), v.kind().clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("/name".to_string(), ViolationKind::RequiredFieldMissing),
+                ("/age".to_string(), ViolationKind::InvalidType),
+                ("/email".to_string(), ViolationKind::MissingField),
+                ("/other/a".to_string(), ViolationKind::InvalidType),
+                ("/other/b".to_string(), ViolationKind::MissingField),
+            ]
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json["violations"][1],
+            json!({ "path": "/age", "kind": "invalid_type", "expected": "number", "actual": "twenty" })
+        );
+        assert_eq!(
+            json["violations"][2],
+            json!({ "path": "/email", "kind": "missing_field", "expected": "email" })
+        );
+
+        let value = ValueStore::builder().with_schema("Unknown").build();
+        assert_eq!(
+            violations(schema_manager.validate(&value)),
+            vec![(
+                "".to_string(),
+                ViolationKind::SchemaNotFound {
+                    schema: "Unknown".into()
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_optional_fields_and_normalize() {
+        let schema = Schema::create(
+            "TestSchema",
+            vec![
+                Field::create("name", FieldType::String, true, Value::Null),
+                Field::create("age", FieldType::Number, false, Value::Number(30.into())),
+                Field::create("nickname", FieldType::String, false, Value::Null),
+                Field::create("other", FieldType::Object, false, Value::Null),
+            ],
+        );
+
+        let inner_schema = Schema::create(
+            "InnerSchema",
+            vec![Field::create(
+                "a",
+                FieldType::Number,
+                true,
+                Value::Number(1.into()),
+            )],
+        );
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager.register("TestSchema", schema).unwrap();
+        schema_manager
+            .register("InnerSchema", inner_schema)
+            .unwrap();
+
+        // Optional fields can be left out entirely
+        let value = ValueStore::builder()
+            .with_schema("TestSchema")
+            .string("name", "Ookuma Wakana")
+            .build();
+
+        assert!(schema_manager.validate(&value).is_ok());
+
+        let value = ValueStore::builder()
+            .with_schema("TestSchema")
+            .string("name", "Ookuma Wakana")
+            .null("age")
+            .object(
+                "other",
+                Value::builder().object().into_map(),
+                Some("InnerSchema"),
+            )
+            .build();
+
+        // The required `a` of the inner object is missing
+        assert!(schema_manager.validate(&value).is_err());
+
+        let normalized = schema_manager.normalize(&value).unwrap();
+
+        assert_eq!(normalized.get_path_as::<u64>("age").unwrap(), 30);
+        assert_eq!(normalized.get_path_as::<u64>("other.a").unwrap(), 1);
+        assert!(normalized.get("nickname").is_none());
+        assert_eq!(normalized.schema_name(), value.schema_name());
+        assert!(schema_manager.validate(&normalized).is_ok());
+    }
+
+    #[test]
+    fn test_additional_properties() {
+        let schema = Schema::create(
+            "TestSchema",
+            vec![
+                Field::create("name", FieldType::String, true, Value::Null),
+                Field::create("other", FieldType::Object, false, Value::Null),
+            ],
+        )
+        .with_additional_properties(AdditionalProperties::Warn);
+        let inner_schema = Schema::create(
+            "InnerSchema",
+            vec![Field::create("a", FieldType::Number, true, Value::Null)],
+        )
+        .with_additional_properties(AdditionalProperties::Reject);
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager.register("TestSchema", schema).unwrap();
+        schema_manager
+            .register("InnerSchema", inner_schema)
+            .unwrap();
+
+        let value = ValueStore::builder()
+            .with_schema("TestSchema")
+            .string("name", "Ookuma Wakana")
+            .string("nickname", "Waka")
+            .object(
+                "other",
+                Value::builder()
+                    .object()
+                    .push_number("a", 10)
+                    .push_number("b", 20)
+                    .into_map(),
+                Some("InnerSchema"),
+            )
+            .build();
+
+        let report = schema_manager.report(&value).unwrap();
+
+        let warnings: Vec<_> = report
+            .warnings()
+            .iter()
+            .map(|v| (v.path().to_string(), v.kind().clone()))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![("/nickname".to_string(), ViolationKind::AdditionalProperty)]
+        );
+        assert_eq!(
+            violations(schema_manager.validate(&value)),
+            vec![("/other/b".to_string(), ViolationKind::AdditionalProperty)]
+        );
+
+        // Only warnings left
+        let value = ValueStore::builder()
+            .with_schema("TestSchema")
+            .string("name", "Ookuma Wakana")
+            .string("nickname", "Waka")
+            .build();
+
+        let report = schema_manager.report(&value).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.warnings().len(), 1);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["warnings"][0]["kind"], "additional_property");
+
+        // A typo doesn't make a strict schema lenient
+        assert_eq!(
+            AdditionalProperties::try_from("reject").unwrap(),
+            AdditionalProperties::Reject
+        );
+        assert!(AdditionalProperties::try_from("rejct").is_err());
+    }
+
+    #[test]
+    fn test_array_of_objects_validation() {
+        let rack_schema = Schema::create(
+            "RackSchema",
+            vec![
+                Field::create("rack_name", FieldType::String, true, Value::Null),
+                Field::create("shelves", FieldType::Array, false, Value::Null)
+                    .with_constraints(Constraints::default().items(FieldType::String)),
+                Field::create("levels", FieldType::Number, false, Value::Number(1.into())),
+            ],
+        );
+        let schema = Schema::create(
+            "LocationSchema",
+            vec![Field::create("racks", FieldType::Array, true, Value::Null)],
+        );
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager.register("RackSchema", rack_schema).unwrap();
+        schema_manager.register("LocationSchema", schema).unwrap();
+
+        let racks: Value = json!([
+            { "rack_name": "Rack A", "shelves": ["Shelf α", "Shelf β"] },
+            { "rack_name": "Rack B", "shelves": ["Shelf γ"] }
+        ])
+        .try_into()
+        .unwrap();
+        let value = ValueStore::builder()
+            .with_schema("LocationSchema")
+            .array("racks", racks.as_array().unwrap().clone(), Some("RackSchema"))
+            .build();
+
+        assert!(schema_manager.validate(&value).is_ok());
+
+        let normalized = schema_manager.normalize(&value).unwrap();
+        assert_eq!(normalized.get_path_as::<u64>("racks.1.levels").unwrap(), 1);
+
+        let racks: Value = json!([
+            { "rack_name": "Rack A", "shelves": ["Shelf α"] },
+            { "shelves": ["Shelf β", 2] },
+            "Rack C"
+        ])
+        .try_into()
+        .unwrap();
+        let value = ValueStore::builder()
+            .with_schema("LocationSchema")
+            .array("racks", racks.as_array().unwrap().clone(), Some("RackSchema"))
+            .build();
+
+        assert_eq!(
+            violations(schema_manager.validate(&value)),
+            vec![
+                ("/racks/1/rack_name".to_string(), ViolationKind::MissingField),
+                (
+                    "/racks/1/shelves".to_string(),
+                    ViolationKind::ConstraintViolation(ConstraintViolation::ItemType {
+                        index: 1,
+                        expected: FieldType::String
+                    })
+                ),
+                ("/racks/2".to_string(), ViolationKind::InvalidType),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_inheritance() {
+        let mut registry = Registry::new();
+        registry.register_group(
+            "Purchase",
+            FieldGroup::create(
+                "Purchase",
+                vec![Field::create("purchase_date", FieldType::Date, false, Value::Null)],
+            ),
+        );
+        registry.register(
+            "Device",
+            Schema::create(
+                "Device",
+                vec![
+                    Field::create("manufacturer", FieldType::String, true, Value::Null),
+                    Field::create("model", FieldType::String, true, Value::Null),
+                ],
+            )
+            .with_group("Purchase"),
+        );
+        registry.register(
+            "Powered",
+            Schema::create(
+                "Powered",
+                vec![Field::create("voltage", FieldType::Number, true, Value::Null)],
+            )
+            .with_group("Purchase"),
+        );
+        registry.register(
+            "Drill",
+            Schema::create(
+                "Drill",
+                vec![Field::create("chuck_size", FieldType::Number, false, Value::Null)],
+            )
+            .with_parent("Device")
+            .with_parent("Powered"),
+        );
+
+        // Groups come before the own fields of each schema, `purchase_date` being included twice
+        // but defined the same way
+        let drill = registry.resolve("Drill").unwrap();
+        let names: Vec<_> = drill.fields().iter().map(|f| f.name()).collect();
+        assert_eq!(
+            names,
+            vec!["purchase_date", "manufacturer", "model", "voltage", "chuck_size"]
+        );
+
+        let schema_manager = SchemaManager::from_registry(registry);
+
+        let value = ValueStore::builder()
+            .with_schema("Drill")
+            .string("manufacturer", "Makita")
+            .string("purchase_date", "yesterday")
+            .build();
+        assert_eq!(
+            violations(schema_manager.validate(&value)),
+            vec![
+                ("/purchase_date".to_string(), ViolationKind::InvalidFormat),
+                ("/model".to_string(), ViolationKind::MissingField),
+                ("/voltage".to_string(), ViolationKind::MissingField),
+            ]
+        );
+
+        schema_manager
+            .edit_registry(|registry| {
+                registry.register(
+                    "CordlessDrill",
+                    Schema::create(
+                        "CordlessDrill",
+                        vec![Field::create("voltage", FieldType::String, true, Value::Null)],
+                    )
+                    .with_parent("Drill"),
+                );
+                assert!(matches!(
+                    registry.resolve("CordlessDrill"),
+                    Err(crate::registry::Error::ConflictingField { field, .. })
+                        if field == "voltage"
+                ));
+
+                registry.register(
+                    "Device",
+                    Schema::create("Device", vec![]).with_parent("Drill"),
+                );
+                assert!(matches!(
+                    registry.resolve("Drill"),
+                    Err(crate::registry::Error::InheritanceCycle(cycle))
+                        if cycle == vec!["Drill", "Device", "Drill"]
+                ));
+            })
+            .unwrap();
+
+        assert!(matches!(
+            schema_manager.validate(&value),
+            Err(crate::Error::RegistryError(_))
+        ));
+    }
+
+    #[test]
+    fn test_schema_versioning() {
+        use lib_commons::{MigrationError, MigrationStep};
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager
+            .register(
+                "Box",
+                Schema::create(
+                    "Box",
+                    vec![
+                        Field::create("qty", FieldType::String, true, Value::Null),
+                        Field::create("legacy", FieldType::Boolean, false, Value::Null),
+                    ],
+                ),
+            )
+            .unwrap();
+
+        // Written before the schema changed, so against its first version
+        let old = ValueStore::builder()
+            .with_schema("Box")
+            .string("qty", "4")
+            .bool("legacy", true)
+            .build();
+
+        let version = schema_manager
+            .evolve(
+                "Box",
+                vec![
+                    MigrationStep::rename("qty", "quantity"),
+                    MigrationStep::retype("quantity", FieldType::Number),
+                    MigrationStep::drop("legacy"),
+                ],
+            )
+            .unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(
+            schema_manager
+                .evolve(
+                    "Box",
+                    vec![MigrationStep::add(Field::create(
+                        "fragile",
+                        FieldType::Boolean,
+                        true,
+                        Value::Boolean(false),
+                    ))],
+                )
+                .unwrap(),
+            3
+        );
+
+        assert!(schema_manager.validate(&old).is_err());
+
+        let migrated = schema_manager.migrate(&old).unwrap();
+        assert_eq!(migrated.schema_version(), Some(3));
+        assert_eq!(migrated.get_path_as::<u64>("quantity").unwrap(), 4);
+        assert!(!migrated.get_path_as::<bool>("fragile").unwrap());
+        assert!(migrated.get("legacy").is_none());
+        assert!(schema_manager.validate(&migrated).is_ok());
+
+        // Already up to date
+        let again = schema_manager.migrate(&migrated).unwrap();
+        assert_eq!(again.get_all(), migrated.get_all());
+
+        // New values are pinned to the current version
+        let new = schema_manager
+            .normalize(&ValueStore::builder().with_schema("Box").number("quantity", 1).build())
+            .unwrap();
+        assert_eq!(new.schema_version(), Some(3));
+
+        let future = ValueStore::builder().with_schema("Box").with_schema_version(7).build();
+        assert!(matches!(
+            schema_manager.migrate(&future),
+            Err(crate::Error::MigrationError(MigrationError::UnknownVersion { version: 7, .. }))
+        ));
+
+        // A step that does not fit the schema leaves it on its version
+        assert!(matches!(
+            schema_manager.evolve("Box", vec![MigrationStep::drop("qty")]),
+            Err(crate::Error::MigrationError(MigrationError::UnknownField(_)))
+        ));
+        let registry = schema_manager.registry();
+        assert_eq!(registry.get_schema("Box").unwrap().version(), 3);
+    }
+
+    #[test]
+    fn test_computed_fields() {
+        let length = || FieldType::Measurement(Dimension::Length);
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager
+            .register(
+                "Drawer",
+                Schema::create(
+                    "Drawer",
+                    vec![
+                        Field::create("brand", FieldType::String, true, Value::Null),
+                        Field::create("model", FieldType::String, true, Value::Null),
+                        Field::create("width", length(), true, Value::Null),
+                        Field::create("depth", length(), true, Value::Null),
+                        Field::create("perimeter", length(), false, Value::Null)
+                            .with_expression("2 * (width + depth)"),
+                        // Uses the one computed before it
+                        Field::create("perimeter_cm", FieldType::Number, false, Value::Null)
+                            .with_expression("perimeter / 10"),
+                        Field::create("area", FieldType::Number, false, Value::Null)
+                            .with_expression("width * depth"),
+                        Field::create("display_name", FieldType::String, true, Value::Null)
+                            .with_expression("brand + \" \" + model"),
+                        Field::create("ratio", FieldType::Number, false, Value::Null)
+                            .with_expression("width / (depth - depth)"),
+                    ],
+                ),
+            )
+            .unwrap();
+
+        let drawer = ValueStore::builder()
+            .with_schema("Drawer")
+            .string("brand", "Ikea")
+            .string("model", "Alex")
+            .measurement("width", Measurement::new(3, Unit::Cm))
+            .measurement("depth", Measurement::new(50, Unit::Mm))
+            // Overwritten, computed fields are never entered
+            .string("display_name", "Something else")
+            .build();
+
+        let computed = schema_manager.compute(&drawer).unwrap();
+        assert_eq!(
+            computed.get("perimeter"),
+            Some(&Value::Measurement(Measurement::new(160, Unit::Mm)))
+        );
+        assert_eq!(computed.get("perimeter_cm"), Some(&Value::Number(16.into())));
+        assert_eq!(computed.get("area"), Some(&Value::Number(1500.into())));
+        assert_eq!(
+            computed.get("display_name"),
+            Some(&Value::String("Ikea Alex".to_string()))
+        );
+        assert_eq!(computed.get("ratio"), Some(&Value::Null));
+
+        // Evaluation errors are validation errors
+        assert_eq!(
+            violations(schema_manager.validate(&computed)),
+            vec![(
+                "/ratio".to_string(),
+                ViolationKind::ExpressionError {
+                    expression: "width / (depth - depth)".to_string(),
+                    error: "DivisionByZero".to_string(),
+                }
+            )]
+        );
+
+        // A required computed field missing its inputs is null
+        let partial = ValueStore::builder()
+            .with_schema("Drawer")
+            .string("brand", "Ikea")
+            .build();
+        let normalized = schema_manager.normalize(&partial).unwrap();
+        assert_eq!(normalized.get("display_name"), Some(&Value::Null));
+        assert!(
+            violations(schema_manager.validate(&normalized))
+                .contains(&("/display_name".to_string(), ViolationKind::RequiredFieldMissing))
+        );
+    }
+
+    #[test]
+    fn test_conditional_rules() {
+        use lib_commons::Rule;
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager
+            .register(
+                "Product",
+                Schema::create(
+                    "Product",
+                    vec![
+                        Field::create("perishable", FieldType::Boolean, false, Value::Null),
+                        Field::create("expiry_date", FieldType::Date, false, Value::Null),
+                        Field::create("barcode", FieldType::String, false, Value::Null),
+                        Field::create("sku", FieldType::String, false, Value::Null),
+                    ],
+                )
+                .with_rule(Rule::required_if("perishable == true", &["expiry_date"]))
+                .with_rule(Rule::at_least_one_of(&["barcode", "sku"])),
+            )
+            .unwrap();
+        schema_manager
+            .register(
+                "Tool",
+                Schema::create(
+                    "Tool",
+                    vec![
+                        Field::create("powered", FieldType::Boolean, true, Value::Null),
+                        Field::create("battery_type", FieldType::String, false, Value::Null),
+                        Field::create("cord_length", FieldType::Number, false, Value::Null),
+                    ],
+                )
+                .with_parent("Product")
+                .with_rule(Rule::required_if("powered", &["battery_type"]))
+                .with_rule(Rule::mutually_exclusive(&["battery_type", "cord_length"])),
+            )
+            .unwrap();
+
+        let tool = || ValueStore::builder().with_schema("Tool").string("sku", "T-1");
+
+        assert!(schema_manager.validate(&tool().bool("powered", false).build()).is_ok());
+        assert!(
+            schema_manager
+                .validate(&tool().bool("powered", true).string("battery_type", "18V").build())
+                .is_ok()
+        );
+
+        assert_eq!(
+            violations(schema_manager.validate(&tool().bool("powered", true).build())),
+            vec![(
+                "/battery_type".to_string(),
+                ViolationKind::RuleViolation(Rule::required_if("powered", &["battery_type"]))
+            )]
+        );
+
+        // Rules of the parent are checked too, and a null field is not set
+        let broken = ValueStore::builder()
+            .with_schema("Tool")
+            .bool("powered", false)
+            .bool("perishable", true)
+            .null("sku")
+            .string("battery_type", "18V")
+            .number("cord_length", 2)
+            .build();
+        assert_eq!(
+            violations(schema_manager.validate(&broken)),
+            vec![
+                (
+                    "/expiry_date".to_string(),
+                    ViolationKind::RuleViolation(Rule::required_if(
+                        "perishable == true",
+                        &["expiry_date"]
+                    ))
+                ),
+                (
+                    "".to_string(),
+                    ViolationKind::RuleViolation(Rule::at_least_one_of(&["barcode", "sku"]))
+                ),
+                (
+                    "".to_string(),
+                    ViolationKind::RuleViolation(Rule::mutually_exclusive(&[
+                        "battery_type",
+                        "cord_length"
+                    ]))
+                ),
+            ]
+        );
+    }
+
+    fn violations(result: crate::Result<()>) -> Vec<(String, ViolationKind)> {
+        match result {
+            Err(crate::Error::ValidationError(report)) => report
+                .violations()
+                .iter()
+                .map(|v| (v.path().to_string(), v.kind().clone()))
+                .collect(),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reference_fields() {
+        use lib_commons::ReferenceTarget;
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager
+            .register(
+                "Spare",
+                Schema::create(
+                    "Spare",
+                    vec![
+                        Field::create(
+                            "for_item",
+                            FieldType::Reference(ReferenceTarget::Item),
+                            true,
+                            Value::Null,
+                        ),
+                        Field::create(
+                            "hall",
+                            FieldType::Reference(ReferenceTarget::LocationMetadata),
+                            false,
+                            Value::Null,
+                        ),
+                        Field::create(
+                            "shelf",
+                            FieldType::Reference(ReferenceTarget::Location),
+                            false,
+                            Value::Null,
+                        ),
+                    ],
+                ),
+            )
+            .unwrap();
+
+        let value = ValueStore::builder()
+            .with_schema("Spare")
+            .number("for_item", 4)
+            .number("shelf", 2)
+            .build();
+        assert!(schema_manager.validate(&value).is_ok());
+        let references = schema_manager.references(&value).unwrap();
+        let references: Vec<(String, ReferenceTarget, i64)> = references
+            .iter()
+            .map(|r| (r.path().to_string(), r.target(), r.id()))
+            .collect();
+        assert_eq!(
+            references,
+            [
+                ("/for_item".to_string(), ReferenceTarget::Item, 4),
+                ("/shelf".to_string(), ReferenceTarget::Location, 2)
+            ]
+        );
+
+        // Ids only
+        let invalid = ValueStore::builder()
+            .with_schema("Spare")
+            .string("for_item", "Drill")
+            .bool("shelf", true)
+            .build();
+        assert!(schema_manager.validate(&invalid).is_err());
+        assert!(schema_manager.references(&invalid).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compiled_schemas_follow_the_registry() {
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        schema_manager
+            .register(
+                "Base",
+                Schema::create(
+                    "Base",
+                    vec![Field::create("label", FieldType::String, false, Value::Null)],
+                ),
+            )
+            .unwrap();
+        schema_manager
+            .register(
+                "Part",
+                Schema::create(
+                    "Part",
+                    vec![Field::create("size", FieldType::Number, false, Value::Null)],
+                )
+                .with_parent("Base"),
+            )
+            .unwrap();
+
+        let value = ValueStore::builder()
+            .with_schema("Part")
+            .number("label", 3)
+            .number("size", 2)
+            .build();
+        // The second time from the compiled schema
+        assert!(schema_manager.validate(&value).is_err());
+        assert!(schema_manager.validate(&value).is_err());
+
+        // A change of the parent is seen by the schemas extending it
+        schema_manager
+            .edit_registry(|registry| {
+                registry.register(
+                    "Base",
+                    Schema::create(
+                        "Base",
+                        vec![Field::create("label", FieldType::Number, false, Value::Null)],
+                    ),
+                )
+            })
+            .unwrap();
+        assert!(schema_manager.validate(&value).is_ok());
+
+        schema_manager
+            .edit_registry(|registry| registry.remove_schema("Part"))
+            .unwrap();
+        let report = schema_manager.report(&value).unwrap();
+        assert!(matches!(
+            report.violations()[0].kind(),
+            ViolationKind::SchemaNotFound { .. }
+        ));
+    }
+
+    #[test]
+    fn test_registry_events() {
+        use crate::SchemaEvent;
+        use tokio::sync::broadcast::error::TryRecvError;
+
+        let base = |label: FieldType| {
+            Schema::create(
+                "Base",
+                vec![Field::create("label", label, false, Value::Null)],
+            )
+        };
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        let mut events = schema_manager.subscribe();
+        let mut received = || {
+            let mut received = Vec::new();
+            loop {
+                match events.try_recv() {
+                    Ok(event) => received.push(event),
+                    Err(TryRecvError::Empty) => return received,
+                    Err(err) => panic!("{:?}", err),
+                }
+            }
+        };
+
+        schema_manager.register("Base", base(FieldType::String)).unwrap();
+        schema_manager
+            .register("Part", Schema::create("Part", vec![]).with_parent("Base"))
+            .unwrap();
+        assert_eq!(
+            received(),
+            [
+                SchemaEvent::Added("Base".to_string()),
+                SchemaEvent::Added("Part".to_string())
+            ]
+        );
+
+        // Registered again as it was
+        schema_manager.register("Base", base(FieldType::String)).unwrap();
+        assert!(received().is_empty());
+
+        let before = schema_manager.registry();
+        schema_manager.register("Base", base(FieldType::Number)).unwrap();
+        assert_eq!(
+            received(),
+            [
+                SchemaEvent::Changed("Base".to_string()),
+                SchemaEvent::Changed("Part".to_string())
+            ]
+        );
+        // Snapshots never change
+        let label = |registry: &Registry| {
+            registry.get_schema("Base").unwrap().fields()[0].field_type().clone()
+        };
+        assert_eq!(label(&before), FieldType::String);
+        assert_eq!(label(&schema_manager.registry()), FieldType::Number);
+
+        schema_manager
+            .edit_registry(|registry| registry.remove_schema("Part"))
+            .unwrap();
+        assert_eq!(received(), [SchemaEvent::Removed("Part".to_string())]);
+    }
 }
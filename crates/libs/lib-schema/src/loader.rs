@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::registry::{BulkSchema, Registry};
+use crate::shared::SharedRegistry;
+
+pub use error::LoadError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl SchemaFormat {
+    /// Picked from the extension, files with any other one are not schema files.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(SchemaFormat::Json),
+            "toml" => Some(SchemaFormat::Toml),
+            "yaml" | "yml" => Some(SchemaFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+impl BulkSchema {
+    /// A file holds either a single schema, or `schemas` and `groups` lists.
+    pub fn parse(content: &str, format: SchemaFormat) -> Result<Self, LoadError> {
+        let value: serde_json::Value = match format {
+            SchemaFormat::Json => serde_json::from_str(content).map_err(LoadError::parse)?,
+            SchemaFormat::Toml => toml::from_str(content).map_err(LoadError::parse)?,
+            SchemaFormat::Yaml => serde_yaml::from_str(content).map_err(LoadError::parse)?,
+        };
+
+        let is_bundle = value
+            .as_object()
+            .is_some_and(|o| o.contains_key("schemas") || o.contains_key("groups"));
+
+        let bulk = if is_bundle {
+            serde_json::from_value(value).map_err(LoadError::parse)?
+        } else {
+            let schema = serde_json::from_value(value).map_err(LoadError::parse)?;
+            BulkSchema::new(vec![schema])
+        };
+
+        // Note : Checked here so that a broken pattern is reported with its file
+        for schema in bulk.schemas() {
+            schema.check_patterns().map_err(LoadError::parse)?;
+        }
+        for group in bulk.groups() {
+            group.check_patterns().map_err(LoadError::parse)?;
+        }
+
+        Ok(bulk)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, LoadError> {
+        let format = SchemaFormat::from_path(path)
+            .ok_or_else(|| LoadError::UnsupportedFormat(path.to_path_buf()))?;
+        let content = std::fs::read_to_string(path).map_err(LoadError::io)?;
+
+        BulkSchema::parse(&content, format)
+    }
+}
+
+#[derive(Debug)]
+pub struct FileError {
+    pub path: PathBuf,
+    pub error: LoadError,
+}
+
+/// The names a file registered, to know what to unregister once it no longer defines them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FileSchemas {
+    schemas: Vec<String>,
+    groups: Vec<String>,
+}
+
+/// What a (re)load did, one broken file does not stop the others from loading.
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    files: Vec<PathBuf>,
+    schemas: Vec<String>,
+    removed: Vec<String>,
+    errors: Vec<FileError>,
+    sources: HashMap<PathBuf, FileSchemas>,
+}
+
+impl LoadReport {
+    /// The files that were loaded without error.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// The names of the schemas registered from those files.
+    pub fn schemas(&self) -> &[String] {
+        &self.schemas
+    }
+
+    /// The names of the schemas unregistered because their file was removed, or no longer
+    /// defines them.
+    pub fn removed(&self) -> &[String] {
+        &self.removed
+    }
+
+    pub fn errors(&self) -> &[FileError] {
+        &self.errors
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub(crate) fn sources(&self) -> &HashMap<PathBuf, FileSchemas> {
+        &self.sources
+    }
+}
+
+/// Every `.json`, `.toml`, `.yaml` and `.yml` file of `dir`, sorted by name.
+fn schema_files(dir: &Path) -> Result<Vec<PathBuf>, LoadError> {
+    let mut files = std::fs::read_dir(dir)
+        .map_err(LoadError::io)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && SchemaFormat::from_path(path).is_some())
+        .collect::<Vec<_>>();
+    files.sort();
+
+    Ok(files)
+}
+
+/// (Re)load `paths`, `sources` being what each file registered so far.
+///
+/// A file that is gone gets everything it registered unregistered, a broken one keeps it.
+pub(crate) fn load_files<'a>(
+    registry: &mut Registry,
+    paths: impl IntoIterator<Item = &'a PathBuf>,
+    sources: &mut HashMap<PathBuf, FileSchemas>,
+) -> LoadReport {
+    let mut report = LoadReport::default();
+
+    for path in paths {
+        if !path.exists() {
+            if let Some(previous) = sources.remove(path) {
+                let unchanged = FileSchemas::default();
+                report.removed.extend(unregister(registry, sources, previous, &unchanged));
+            }
+            continue;
+        }
+
+        match BulkSchema::from_file(path) {
+            Ok(bulk) => {
+                let groups = bulk.groups().iter().map(|group| group.name()).collect();
+                let schemas = registry.register_bulk(bulk);
+                let current = FileSchemas { schemas, groups };
+
+                report.schemas.extend(current.schemas.iter().cloned());
+                report.files.push(path.clone());
+
+                if let Some(previous) = sources.insert(path.clone(), current.clone()) {
+                    report.removed.extend(unregister(registry, sources, previous, &current));
+                }
+            }
+            Err(error) => report.errors.push(FileError {
+                path: path.clone(),
+                error,
+            }),
+        }
+    }
+
+    report
+}
+
+/// Unregister what `previous` defined and `current` doesn't, unless another file defines it
+/// too, giving back the names of the schemas.
+fn unregister(
+    registry: &mut Registry,
+    sources: &HashMap<PathBuf, FileSchemas>,
+    previous: FileSchemas,
+    current: &FileSchemas,
+) -> Vec<String> {
+    let mut removed = Vec::new();
+
+    for name in previous.schemas {
+        let elsewhere = sources.values().any(|file| file.schemas.contains(&name));
+        if !current.schemas.contains(&name)
+            && !elsewhere
+            && registry.remove_schema(&name).is_some()
+        {
+            removed.push(name);
+        }
+    }
+    for name in previous.groups {
+        let elsewhere = sources.values().any(|file| file.groups.contains(&name));
+        if !current.groups.contains(&name) && !elsewhere {
+            registry.remove_group(&name);
+        }
+    }
+
+    removed
+}
+
+pub(crate) fn load_dir(registry: &mut Registry, dir: &Path) -> Result<LoadReport, LoadError> {
+    let files = schema_files(dir)?;
+
+    let mut sources = HashMap::new();
+    let mut report = load_files(registry, &files, &mut sources);
+    report.sources = sources;
+
+    Ok(report)
+}
+
+/// Keeps a directory in sync with a registry until dropped, see [`crate::SchemaManager::watch_dir`].
+pub struct SchemaWatcher {
+    _watcher: RecommendedWatcher,
+    reports: Receiver<LoadReport>,
+}
+
+impl SchemaWatcher {
+    /// `sources` being what the files of `dir` registered so far, see [`LoadReport`].
+    pub(crate) fn new(
+        registry: Arc<SharedRegistry>,
+        dir: &Path,
+        mut sources: HashMap<PathBuf, FileSchemas>,
+    ) -> Result<Self, LoadError> {
+        let (sender, reports) = mpsc::channel();
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+
+                if !matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) {
+                    return;
+                }
+
+                // Note : Removed (or renamed away) files no longer exist, `load_files` unregisters
+                //        what they defined
+                let paths: Vec<PathBuf> = event
+                    .paths
+                    .into_iter()
+                    .filter(|path| !path.is_dir() && SchemaFormat::from_path(path).is_some())
+                    .collect();
+                if paths.is_empty() {
+                    return;
+                }
+
+                // Note : Through the shared registry, so its subscribers hear about it
+                let Ok(report) =
+                    registry.edit(|registry| load_files(registry, &paths, &mut sources))
+                else {
+                    return;
+                };
+
+                let _ = sender.send(report);
+            })
+            .map_err(LoadError::watch)?;
+
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(LoadError::watch)?;
+
+        Ok(SchemaWatcher {
+            _watcher: watcher,
+            reports,
+        })
+    }
+
+    /// One report per batch of changed files, in the order they were reloaded.
+    pub fn reports(&self) -> &Receiver<LoadReport> {
+        &self.reports
+    }
+}
+
+mod error {
+    use std::fmt;
+    use std::path::PathBuf;
+
+    #[derive(Debug)]
+    pub enum LoadError {
+        Io(String),
+        Parse(String),
+        UnsupportedFormat(PathBuf),
+        Watch(String),
+    }
+
+    impl LoadError {
+        pub(crate) fn io(err: std::io::Error) -> Self {
+            LoadError::Io(err.to_string())
+        }
+
+        pub(crate) fn parse(err: impl fmt::Display) -> Self {
+            LoadError::Parse(err.to_string())
+        }
+
+        pub(crate) fn watch(err: notify::Error) -> Self {
+            LoadError::Watch(err.to_string())
+        }
+    }
+
+    impl fmt::Display for LoadError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for LoadError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::Registry;
+    use crate::{FieldType, LoadError, SchemaManager};
+    use lib_commons::{Dimension, Value};
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn schema_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("invi-schemas-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = schema_dir("load");
+
+        std::fs::write(
+            dir.join("box.json"),
+            r#"{ "name": "Box", "fields": [
+                { "name": "label", "field_type": "string", "required": true, "default": null }
+            ] }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("tools.toml"),
+            r#"
+            [[groups]]
+            name = "Purchase"
+            fields = [{ name = "purchase_date", field_type = "date", required = false }]
+
+            [[schemas]]
+            name = "Drill"
+            groups = ["Purchase"]
+            fields = [
+                { name = "chuck", field_type = { measurement = "length" }, required = true },
+                { name = "count", field_type = "number", required = false, default = 1 },
+            ]
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("shelf.yaml"),
+            "name: Shelf\nadditional_properties: reject\nfields:\n  - name: level\n    field_type: number\n    required: true\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.yml"), "name: [").unwrap();
+        std::fs::write(dir.join("README.md"), "Not a schema").unwrap();
+
+        let mut registry = Registry::new();
+        let report = registry.load_dir(&dir).unwrap();
+
+        // In the order of the file names
+        assert_eq!(report.schemas(), &["Box", "Shelf", "Drill"]);
+        assert_eq!(report.errors().len(), 1);
+        assert_eq!(report.errors()[0].path, dir.join("broken.yml"));
+        assert!(matches!(report.errors()[0].error, LoadError::Parse(_)));
+
+        let drill = registry.resolve("Drill").unwrap();
+        assert_eq!(
+            drill.get_field("chuck").unwrap().field_type(),
+            &FieldType::Measurement(Dimension::Length)
+        );
+        assert_eq!(
+            drill.get_field("count").unwrap().default_value(),
+            &Value::Number(1.into())
+        );
+        assert_eq!(
+            drill.get_field("purchase_date").unwrap().field_type(),
+            &FieldType::Date
+        );
+
+        assert!(matches!(
+            registry.load_dir(dir.join("missing")),
+            Err(LoadError::Io(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_watch_dir() {
+        let dir = schema_dir("watch");
+        let write_box = |field_type: &str| {
+            std::fs::write(
+                dir.join("box.json"),
+                format!(
+                    r#"{{ "name": "Box", "fields": [
+                        {{ "name": "label", "field_type": "{}", "required": true }}
+                    ] }}"#,
+                    field_type
+                ),
+            )
+            .unwrap();
+        };
+        write_box("string");
+
+        let schema_manager = SchemaManager::from_registry(Registry::new());
+        let (report, watcher) = schema_manager.watch_dir(&dir).unwrap();
+        assert_eq!(report.schemas(), &["Box"]);
+
+        write_box("number");
+
+        let label_type = || {
+            schema_manager
+                .registry()
+                .get_schema("Box")
+                .unwrap()
+                .get_field("label")
+                .unwrap()
+                .field_type()
+                .clone()
+        };
+
+        // A single write can come as several events, the first ones possibly seeing a half
+        // written (so broken) file
+        while label_type() != FieldType::Number {
+            watcher
+                .reports()
+                .recv_timeout(Duration::from_secs(5))
+                .expect("the change to be picked up");
+        }
+
+        let wait_until = |done: &dyn Fn(&Registry) -> bool| {
+            while !done(&schema_manager.registry()) {
+                watcher
+                    .reports()
+                    .recv_timeout(Duration::from_secs(5))
+                    .expect("the change to be picked up");
+            }
+        };
+        let write_bundle = |names: &[&str]| {
+            let schemas: Vec<String> = names
+                .iter()
+                .map(|name| format!(r#"{{ "name": "{}", "fields": [] }}"#, name))
+                .collect();
+            let content = format!(r#"{{ "schemas": [{}] }}"#, schemas.join(", "));
+            std::fs::write(dir.join("bundle.json"), content).unwrap();
+        };
+
+        write_bundle(&["Shelf", "Rack"]);
+        wait_until(&|registry| registry.get_schema("Rack").is_some());
+
+        // No longer in its file
+        write_bundle(&["Shelf"]);
+        wait_until(&|registry| registry.get_schema("Rack").is_none());
+        assert!(schema_manager.registry().get_schema("Shelf").is_some());
+
+        // Its file removed
+        std::fs::remove_file(dir.join("box.json")).unwrap();
+        wait_until(&|registry| registry.get_schema("Box").is_none());
+        assert!(schema_manager.registry().get_schema("Shelf").is_some());
+
+        drop(watcher);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use lib_commons::{Field, FieldType, Measurement, Value};
+
+use crate::compiled::CompiledSchema;
+use crate::{Expression, ExpressionError};
+
+/// Fill the absent or null fields of `values` with their defaults, walking nested objects
+/// and arrays of objects, the same way the validator does.
+pub(crate) fn fill_defaults(
+    schema: &CompiledSchema,
+    values: &mut BTreeMap<String, Value>,
+    properties: &HashMap<&str, Arc<CompiledSchema>>,
+) {
+    fill(schema, values, properties, fill_field_defaults);
+}
+
+/// Materialise the computed fields of `values`, nested objects included. A field that can't
+/// be computed is set to null, the validator reports why.
+pub(crate) fn fill_computed(
+    schema: &CompiledSchema,
+    values: &mut BTreeMap<String, Value>,
+    properties: &HashMap<&str, Arc<CompiledSchema>>,
+) {
+    fill(schema, values, properties, fill_field_computed);
+}
+
+/// Turn the values of the measurement fields of `values` (arrays of measurements included) into
+/// [`Value::Measurement`]s in their base unit, nested objects included.
+///
+/// Note : Only where the schema declares a dimension, an object shaped as a measurement anywhere
+///        else is left alone.
+pub(crate) fn fill_measurements(
+    schema: &CompiledSchema,
+    values: &mut BTreeMap<String, Value>,
+    properties: &HashMap<&str, Arc<CompiledSchema>>,
+) {
+    fill(schema, values, properties, fill_field_measurements);
+}
+
+/// The value of the computed `field` given its siblings, a number computed for a measurement
+/// field being taken in the base unit.
+pub(crate) fn compute(
+    field: &Field,
+    expression: &Expression,
+    values: &BTreeMap<String, Value>,
+) -> Result<Value, ExpressionError> {
+    let value = expression.evaluate(values)?;
+
+    Ok(match (field.dimension(), value) {
+        (Some(dimension), Value::Number(n)) => {
+            Value::Measurement(Measurement::new(n, dimension.base_unit()))
+        }
+        (_, value) => value,
+    })
+}
+
+fn fill_field_defaults(schema: &CompiledSchema, values: &mut BTreeMap<String, Value>) {
+    for field in schema.fields() {
+        let default = field.default_value();
+        if default.is_null() {
+            continue;
+        }
+
+        let value = values
+            .entry(field.name().to_string())
+            .or_insert(Value::Null);
+
+        if value.is_null() {
+            *value = default.clone();
+        }
+    }
+}
+
+fn fill_field_measurements(schema: &CompiledSchema, values: &mut BTreeMap<String, Value>) {
+    for field in schema.fields() {
+        let Some(value) = values.get_mut(field.name()) else {
+            continue;
+        };
+
+        match (field.field_type(), value) {
+            (FieldType::Measurement(_), value) => to_measurement(value),
+            (FieldType::Array, Value::Array(items))
+                if matches!(field.constraints().item_type(), Some(FieldType::Measurement(_))) =>
+            {
+                items.iter_mut().for_each(to_measurement);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Note : Anything else is left for the validator to report
+fn to_measurement(value: &mut Value) {
+    if let Value::Object(_) = value
+        && let Some(measurement) = value.as_measurement()
+    {
+        *value = measurement.into();
+    }
+}
+
+// Note : In the order of the fields, so a computed field can use the ones computed before it
+fn fill_field_computed(schema: &CompiledSchema, values: &mut BTreeMap<String, Value>) {
+    for (index, field) in schema.fields().iter().enumerate() {
+        let Some(expression) = schema.expression(index) else {
+            continue;
+        };
+
+        let value = match expression {
+            Ok(expression) => compute(field, expression, values).unwrap_or(Value::Null),
+            Err(_) => Value::Null,
+        };
+        values.insert(field.name().to_string(), value);
+    }
+}
+
+// Note : Walks nested objects and arrays of objects, the same way the validator does
+fn fill(
+    schema: &CompiledSchema,
+    values: &mut BTreeMap<String, Value>,
+    properties: &HashMap<&str, Arc<CompiledSchema>>,
+    fill_fields: fn(&CompiledSchema, &mut BTreeMap<String, Value>),
+) {
+    fill_fields(schema, values);
+
+    for (field_name, schema) in properties {
+        match values.get_mut(*field_name) {
+            Some(Value::Object(inner)) => fill(schema, inner, properties, fill_fields),
+            Some(Value::Array(items)) => {
+                for item in items {
+                    if let Value::Object(inner) = item {
+                        fill(schema, inner, properties, fill_fields);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,50 +1,443 @@
-use crate::schema::Schema;
+use crate::schema::{FieldGroup, Schema};
+use lib_commons::{Field, Rule};
 use lib_model::ModelManager;
-use lib_model_schema::exec::{get_schema, register_schema, update_schema};
+use lib_model_schema::exec::{
+    apply_schema_changes, get_field_groups, get_schema, register_schema, update_schema,
+};
 use lib_model_schema::types::Schemas;
 use lib_model_schema::types::params::{
+    FieldGroupRegisterPayload, FieldGroupUpdatePayload, SchemaChange, SchemaDeletePayload,
     SchemaGetPayload, SchemaRegisterPayload, SchemaUpdatePayload,
 };
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::{collections::HashMap, sync::Arc};
 
+use crate::loader::{self, LoadError, LoadReport};
+use serde::{Deserialize, Serialize};
+
 use crate::registry::error::Result;
 
 pub use crate::registry::error::Error;
 
-#[derive(Debug, Default)]
+/// A schema that differs from the schema db, see [`Registry::pending_changes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingChange {
+    New(String),
+    Modified(String),
+    Deleted(String),
+}
+
+// Note : Shared by every registry, so one replaced by another never reuses a generation
+static GENERATION: AtomicU64 = AtomicU64::new(1);
+
+fn next_generation() -> u64 {
+    GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A schema that changed in the registry of a [`crate::SchemaManager`], see
+/// [`crate::SchemaManager::subscribe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaEvent {
+    Added(String),
+    /// Itself or what it is built from, i.e. its parents and field groups
+    Changed(String),
+    Removed(String),
+}
+
+#[derive(Debug, Clone)]
 pub struct Registry {
     schemas: HashMap<String, Arc<Schema>>,
+    groups: HashMap<String, Arc<FieldGroup>>,
+    /// The schemas as they are in the schema db
+    persisted: HashMap<String, Arc<Schema>>,
+    /// Names touched since the last load/flush, they may or may not differ from `persisted`
+    dirty: HashSet<String>,
+    /// Same as `persisted` and `dirty`, for the groups
+    persisted_groups: HashMap<String, Arc<FieldGroup>>,
+    dirty_groups: HashSet<String>,
+    generation: u64,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Registry {
     pub fn new() -> Self {
         Registry {
             schemas: HashMap::new(),
+            groups: HashMap::new(),
+            persisted: HashMap::new(),
+            dirty: HashSet::new(),
+            persisted_groups: HashMap::new(),
+            dirty_groups: HashSet::new(),
+            generation: next_generation(),
         }
     }
 
+    /// Changes every time a schema or a group does, for what is derived from them to know
+    /// when it is stale, e.g. the compiled schemas of [`crate::SchemaManager`].
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn touch(&mut self) {
+        self.generation = next_generation();
+    }
+
     pub fn register(&mut self, name: &str, schema: Schema) {
         self.schemas.insert(name.to_string(), Arc::new(schema));
+        self.dirty.insert(name.to_string());
+        self.touch();
     }
 
     pub fn get_schema(&self, name: &str) -> Option<Arc<Schema>> {
         self.schemas.get(name).cloned()
     }
 
+    // Note : Counts as a change even if nothing ends up being edited, unchanged schemas are
+    //        filtered out by `pending_changes`.
     pub fn edit_schema(&mut self, name: &str) -> Option<&mut Arc<Schema>> {
-        self.schemas.get_mut(name)
+        self.touch();
+        let schema = self.schemas.get_mut(name)?;
+        self.dirty.insert(name.to_string());
+        Some(schema)
+    }
+
+    /// Remove the schema, from the schema db too on the next [`Registry::flush`].
+    pub fn remove_schema(&mut self, name: &str) -> Option<Arc<Schema>> {
+        let schema = self.schemas.remove(name)?;
+        self.dirty.insert(name.to_string());
+        self.touch();
+        Some(schema)
+    }
+
+    /// The schemas [`Registry::flush`] would write, sorted by name.
+    pub fn pending_changes(&self) -> Vec<PendingChange> {
+        diff(&self.dirty, &self.schemas, &self.persisted)
+    }
+
+    /// The groups [`Registry::flush`] would write, sorted by name.
+    pub fn pending_group_changes(&self) -> Vec<PendingChange> {
+        diff(&self.dirty_groups, &self.groups, &self.persisted_groups)
+    }
+
+    pub fn has_pending_changes(&self) -> bool {
+        !self.pending_changes().is_empty() || !self.pending_group_changes().is_empty()
+    }
+
+    /// What changed from `previous` to this registry, sorted by name.
+    pub fn changes_since(&self, previous: &Registry) -> Vec<SchemaEvent> {
+        if self.generation == previous.generation {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        // The schemas whose own definition changed, the ones built from them follow
+        let mut changed: HashSet<&str> = HashSet::new();
+
+        for (name, schema) in &self.schemas {
+            match previous.schemas.get(name) {
+                None => events.push(SchemaEvent::Added(name.clone())),
+                Some(before) if !Arc::ptr_eq(before, schema) && before != schema => {
+                    events.push(SchemaEvent::Changed(name.clone()));
+                }
+                Some(_) => continue,
+            }
+            changed.insert(name);
+        }
+        for name in previous.schemas.keys() {
+            if !self.schemas.contains_key(name) {
+                events.push(SchemaEvent::Removed(name.clone()));
+                changed.insert(name);
+            }
+        }
+
+        let changed_groups: HashSet<&str> = self
+            .groups
+            .iter()
+            .filter(|(name, group)| match previous.groups.get(*name) {
+                Some(before) => !Arc::ptr_eq(before, group) && before != *group,
+                None => true,
+            })
+            .map(|(name, _)| name.as_str())
+            .collect();
+
+        // Note : Until nothing else changes, a schema can be several parents away from the change
+        loop {
+            let dependents: Vec<&String> = self
+                .schemas
+                .iter()
+                .filter(|(name, _)| !changed.contains(name.as_str()))
+                .filter(|(_, schema)| {
+                    schema.extends().iter().any(|parent| changed.contains(parent.as_str()))
+                        || schema.groups().iter().any(|g| changed_groups.contains(g.as_str()))
+                })
+                .map(|(name, _)| name)
+                .collect();
+            if dependents.is_empty() {
+                break;
+            }
+
+            for name in dependents {
+                events.push(SchemaEvent::Changed(name.clone()));
+                changed.insert(name);
+            }
+        }
+
+        events.sort_by(|a, b| event_name(a).cmp(event_name(b)));
+        events
+    }
+
+    /// Go back to the schemas and groups as they are in the schema db.
+    pub fn discard_changes(&mut self) {
+        self.touch();
+        restore(&mut self.dirty, &mut self.schemas, &self.persisted);
+        restore(&mut self.dirty_groups, &mut self.groups, &self.persisted_groups);
+    }
+
+    /// Write every pending change to the schema db in one transaction, the new schemas and groups
+    /// getting their id back.
+    ///
+    /// Nothing is written (and the changes stay pending) if any of them fails.
+    pub async fn flush(&mut self, mm: &ModelManager) -> Result<()> {
+        let pending = self.pending_changes();
+        let pending_groups = self.pending_group_changes();
+        if pending.is_empty() && pending_groups.is_empty() {
+            self.dirty.clear();
+            self.dirty_groups.clear();
+            return Ok(());
+        }
+
+        let mut writes = Vec::with_capacity(pending.len() + pending_groups.len());
+
+        // Note : Groups go first so the schemas written along find them, and the removed ones
+        //        last, once no schema written here uses them anymore.
+        let mut written_groups = Vec::new();
+        let mut removed_groups = Vec::new();
+        for change in pending_groups {
+            match change {
+                PendingChange::New(name) | PendingChange::Modified(name) => {
+                    let group = &self.groups[&name];
+                    let id = group
+                        .id()
+                        .or_else(|| self.persisted_groups.get(&name).and_then(|p| p.id()));
+
+                    writes.push(match id {
+                        Some(id) => SchemaChange::UpdateGroup(FieldGroupUpdatePayload {
+                            id,
+                            name: Some(name.clone()),
+                            fields: Some(Arc::from(group.fields().clone())),
+                        }),
+                        None => SchemaChange::RegisterGroup(FieldGroupRegisterPayload {
+                            name: name.clone(),
+                            fields: Arc::from(group.fields().clone()),
+                        }),
+                    });
+                    written_groups.push((name, id));
+                }
+                PendingChange::Deleted(name) => {
+                    if let Some(id) = self.persisted_groups[&name].id() {
+                        removed_groups.push(SchemaChange::DeleteGroup(id));
+                    }
+                }
+            }
+        }
+
+        // The schema each write is for, along with its id when it already has one
+        let mut written = Vec::with_capacity(pending.len());
+        for change in pending {
+            match change {
+                PendingChange::New(name) | PendingChange::Modified(name) => {
+                    let schema = &self.schemas[&name];
+                    // Note : A schema registered again over a persisted one has no id of its own
+                    let id = schema
+                        .id()
+                        .or_else(|| self.persisted.get(&name).and_then(|p| p.id()));
+
+                    writes.push(match id {
+                        Some(id) => SchemaChange::Update(update_payload(id, &name, schema)),
+                        None => SchemaChange::Register(register_payload(&name, schema)),
+                    });
+                    written.push((name, id));
+                }
+                PendingChange::Deleted(name) => {
+                    if let Some(id) = self.persisted[&name].id() {
+                        writes.push(SchemaChange::Delete(SchemaDeletePayload::new(id)));
+                        written.push((name, None));
+                    }
+                }
+            }
+        }
+
+        writes.extend(removed_groups);
+
+        let mut ids = apply_schema_changes(mm, writes).await?.into_iter();
+
+        for ((name, known_id), new_id) in written_groups.into_iter().zip(ids.by_ref()) {
+            if let Some(id) = known_id.or(new_id)
+                && let Some(group) = self.groups.get_mut(&name)
+                && group.id() != Some(id)
+            {
+                Arc::make_mut(group).set_id(id);
+                self.generation = next_generation();
+            }
+        }
+
+        for ((name, known_id), new_id) in written.into_iter().zip(ids) {
+            if let Some(id) = known_id.or(new_id)
+                && let Some(schema) = self.schemas.get_mut(&name)
+                && schema.id() != Some(id)
+            {
+                Arc::make_mut(schema).set_id(id);
+                self.generation = next_generation();
+            }
+        }
+
+        persist(&mut self.dirty, &self.schemas, &mut self.persisted);
+        persist(&mut self.dirty_groups, &self.groups, &mut self.persisted_groups);
+
+        Ok(())
+    }
+
+    /// Bring in what got persisted by `flushed`, a copy of `before` that was flushed or
+    /// finalized while this registry kept being edited.
+    ///
+    /// What was edited again meanwhile is kept as is, and stays pending.
+    pub(crate) fn merge_persisted(&mut self, before: &Registry, flushed: &Registry) {
+        // Note : Only the names the flush took care of, i.e. dirty before it and not after
+        for name in before.dirty.difference(&flushed.dirty) {
+            let tracked = (&mut self.dirty, &mut self.schemas, &mut self.persisted);
+            merge(name, tracked, &before.schemas, &flushed.schemas, &flushed.persisted);
+        }
+        for name in before.dirty_groups.difference(&flushed.dirty_groups) {
+            let tracked = (&mut self.dirty_groups, &mut self.groups, &mut self.persisted_groups);
+            merge(name, tracked, &before.groups, &flushed.groups, &flushed.persisted_groups);
+        }
+        self.touch();
+    }
+
+    pub fn register_group(&mut self, name: &str, group: FieldGroup) {
+        self.groups.insert(name.to_string(), Arc::new(group));
+        self.dirty_groups.insert(name.to_string());
+        self.touch();
+    }
+
+    /// Remove the group, from the schema db too on the next [`Registry::flush`].
+    pub fn remove_group(&mut self, name: &str) -> Option<Arc<FieldGroup>> {
+        let group = self.groups.remove(name)?;
+        self.dirty_groups.insert(name.to_string());
+        self.touch();
+        Some(group)
+    }
+
+    pub fn get_group(&self, name: &str) -> Option<Arc<FieldGroup>> {
+        self.groups.get(name).cloned()
+    }
+
+    /// The schema `name` with every field it inherits (parents first, then groups, then its own),
+    /// and the rules of its parents on top of its own.
+    ///
+    /// The same field coming from several places is fine as long as it is defined the same way,
+    /// anything else is a [`Error::ConflictingField`].
+    pub fn resolve(&self, name: &str) -> Result<Arc<Schema>> {
+        let schema = self
+            .get_schema(name)
+            .ok_or(Error::SchemaNotFound(name.to_string()))?;
+
+        if schema.extends().is_empty() && schema.groups().is_empty() {
+            return Ok(schema);
+        }
+
+        let (fields, rules) = self.effective_fields(name, &schema, &mut Vec::new())?;
+
+        Ok(Arc::new(schema.flattened(fields, rules)))
+    }
+
+    fn effective_fields(
+        &self,
+        name: &str,
+        schema: &Schema,
+        stack: &mut Vec<String>,
+    ) -> Result<(Vec<Field>, Vec<Rule>)> {
+        if let Some(start) = stack.iter().position(|s| s == name) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(Error::InheritanceCycle(cycle));
+        }
+        stack.push(name.to_string());
+
+        let mut fields = Vec::new();
+        let mut rules: Vec<Rule> = Vec::new();
+
+        for parent in schema.extends() {
+            let parent_schema = self
+                .get_schema(parent)
+                .ok_or(Error::SchemaNotFound(parent.clone()))?;
+
+            let (parent_fields, parent_rules) =
+                self.effective_fields(parent, &parent_schema, stack)?;
+            for field in parent_fields {
+                merge_field(name, &mut fields, field)?;
+            }
+            merge_rules(&mut rules, parent_rules);
+        }
+
+        for group in schema.groups() {
+            let group = self
+                .get_group(group)
+                .ok_or(Error::FieldGroupNotFound(group.clone()))?;
+
+            for field in group.fields() {
+                merge_field(name, &mut fields, field.clone())?;
+            }
+        }
+
+        for field in schema.fields() {
+            merge_field(name, &mut fields, field.clone())?;
+        }
+        merge_rules(&mut rules, schema.rules().to_vec());
+
+        stack.pop();
+
+        Ok((fields, rules))
     }
 
     pub fn load_from_file(schemas: BulkSchema) -> Self {
         let mut registry = Self::new();
-        for schema in schemas.schemas {
+        registry.register_bulk(schemas);
+        registry
+    }
+
+    /// Register the groups then the schemas of `bulk`, giving back the names of the schemas.
+    pub fn register_bulk(&mut self, bulk: BulkSchema) -> Vec<String> {
+        for group in bulk.groups {
+            let name = group.name();
+            self.register_group(&name, group);
+        }
+
+        let mut names = Vec::new();
+        for schema in bulk.schemas {
             let name = schema.name();
-            registry.register(&name, schema);
+            self.register(&name, schema);
+            names.push(name);
         }
-        registry
+        names
     }
 
-    pub async fn finalize_schema(&self, mm: &ModelManager, name: &str) -> Result<Option<i64>> {
+    /// Load every schema file of `dir`, see [`BulkSchema::parse`] for what a file looks like.
+    ///
+    /// Only fails if the directory can't be read, broken files are listed in the report.
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> std::result::Result<LoadReport, LoadError> {
+        loader::load_dir(self, dir.as_ref())
+    }
+
+    /// Persist the schema `name` alone, see [`Registry::flush`] for all of them at once.
+    pub async fn finalize_schema(&mut self, mm: &ModelManager, name: &str) -> Result<Option<i64>> {
         let schema = self
             .schemas
             .get(name)
@@ -53,64 +446,286 @@ impl Registry {
 
         // Check if id exists,
         // If exists update the data
-        if let Some(id) = schema.id() {
-            let params = SchemaUpdatePayload {
-                id,
-                name: Some(name.to_string()),
-                fields: Some(Arc::from(schema.fields().clone())),
-            };
-            update_schema(mm, params).await?;
+        // Note : A schema registered again over a persisted one has no id of its own
+        let id = schema
+            .id()
+            .or_else(|| self.persisted.get(name).and_then(|p| p.id()));
+        if let Some(id) = id {
+            update_schema(mm, update_payload(id, name, &schema)).await?;
+            if let Some(schema) = self.schemas.get_mut(name)
+                && schema.id() != Some(id)
+            {
+                Arc::make_mut(schema).set_id(id);
+                self.touch();
+            }
+            self.mark_persisted(name);
 
             return Ok(None);
         }
 
         // Or else create a new one
-        let params = SchemaRegisterPayload {
-            name: name.to_string(),
-            fields: Arc::from(schema.fields().clone()),
-        };
-        let id = register_schema(mm, params).await?;
+        let id = register_schema(mm, register_payload(name, &schema)).await?;
+        if let Some(schema) = self.schemas.get_mut(name) {
+            Arc::make_mut(schema).set_id(id);
+        }
+        self.touch();
+        self.mark_persisted(name);
+
         Ok(Some(id))
     }
 
+    fn mark_persisted(&mut self, name: &str) {
+        if let Some(schema) = self.schemas.get(name) {
+            self.persisted.insert(name.to_string(), schema.clone());
+        }
+        self.dirty.remove(name);
+    }
+
+    /// Persist the group `name` alone, see [`Registry::flush`] for all of them at once.
+    pub async fn finalize_group(&mut self, mm: &ModelManager, name: &str) -> Result<Option<i64>> {
+        let group = self
+            .groups
+            .get(name)
+            .cloned()
+            .ok_or(Error::FieldGroupNotFound(name.to_string()))?;
+
+        let id = group
+            .id()
+            .or_else(|| self.persisted_groups.get(name).and_then(|p| p.id()));
+        let change = match id {
+            Some(id) => SchemaChange::UpdateGroup(FieldGroupUpdatePayload {
+                id,
+                name: Some(name.to_string()),
+                fields: Some(Arc::from(group.fields().clone())),
+            }),
+            None => SchemaChange::RegisterGroup(FieldGroupRegisterPayload {
+                name: name.to_string(),
+                fields: Arc::from(group.fields().clone()),
+            }),
+        };
+        let new_id = apply_schema_changes(mm, vec![change]).await?.pop().flatten();
+
+        if let Some(group) = self.groups.get_mut(name) {
+            if let Some(id) = id.or(new_id)
+                && group.id() != Some(id)
+            {
+                Arc::make_mut(group).set_id(id);
+                self.generation = next_generation();
+            }
+            self.persisted_groups.insert(name.to_string(), group.clone());
+        }
+        self.dirty_groups.remove(name);
+
+        Ok(new_id)
+    }
+
     pub async fn load_from_db(mm: &ModelManager) -> Result<Self> {
         let mut registry = Registry::new();
         let schemas = get_schema(mm, SchemaGetPayload::default()).await?;
 
         match schemas {
             Schemas::Bulk(schemas) => {
-                schemas
-                    .iter()
-                    .map(|schema| schema.into())
-                    .for_each(|s: Schema| {
-                        let name = s.name();
-                        registry.register(&name, s);
-                    });
+                for schema in schemas.iter() {
+                    let s: Schema = schema.try_into()?;
+                    let name = s.name();
+                    registry.register(&name, s);
+                }
             }
             Schemas::Single(schema) => {
+                let schema: Schema = schema.try_into()?;
                 let name = schema.name();
-                registry.register(name, schema.clone().into());
+                registry.register(&name, schema);
             }
         }
+        for group in get_field_groups(mm).await? {
+            let group: FieldGroup = group.into();
+            let name = group.name();
+            registry.register_group(&name, group);
+        }
+
+        // Everything registered so far is what the db holds
+        registry.persisted = registry.schemas.clone();
+        registry.dirty.clear();
+        registry.persisted_groups = registry.groups.clone();
+        registry.dirty_groups.clear();
+
         Ok(registry)
     }
 }
 
+fn register_payload(name: &str, schema: &Schema) -> SchemaRegisterPayload {
+    SchemaRegisterPayload {
+        name: name.to_string(),
+        fields: Arc::from(schema.fields().clone()),
+        additional_properties: schema.additional_properties().as_str().to_string(),
+        extends: Arc::from(schema.extends()),
+        groups: Arc::from(schema.groups()),
+        migrations: Arc::from(schema.migrations()),
+        rules: Arc::from(schema.rules()),
+    }
+}
+
+fn update_payload(id: i64, name: &str, schema: &Schema) -> SchemaUpdatePayload {
+    SchemaUpdatePayload {
+        id,
+        name: Some(name.to_string()),
+        fields: Some(Arc::from(schema.fields().clone())),
+        additional_properties: Some(schema.additional_properties().as_str().to_string()),
+        extends: Some(Arc::from(schema.extends())),
+        groups: Some(Arc::from(schema.groups())),
+        migrations: Some(Arc::from(schema.migrations())),
+        rules: Some(Arc::from(schema.rules())),
+    }
+}
+
+// Note : Shared by the schemas and the groups, which are tracked the same way
+fn diff<T: PartialEq>(
+    dirty: &HashSet<String>,
+    current: &HashMap<String, Arc<T>>,
+    persisted: &HashMap<String, Arc<T>>,
+) -> Vec<PendingChange> {
+    let mut names: Vec<&String> = dirty.iter().collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .filter_map(|name| match (current.get(name), persisted.get(name)) {
+            (Some(_), None) => Some(PendingChange::New(name.clone())),
+            (Some(current), Some(persisted)) if current != persisted => {
+                Some(PendingChange::Modified(name.clone()))
+            }
+            (None, Some(_)) => Some(PendingChange::Deleted(name.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn restore<T>(
+    dirty: &mut HashSet<String>,
+    current: &mut HashMap<String, Arc<T>>,
+    persisted: &HashMap<String, Arc<T>>,
+) {
+    for name in dirty.drain() {
+        match persisted.get(&name) {
+            Some(persisted) => {
+                current.insert(name, persisted.clone());
+            }
+            None => {
+                current.remove(&name);
+            }
+        }
+    }
+}
+
+fn persist<T>(
+    dirty: &mut HashSet<String>,
+    current: &HashMap<String, Arc<T>>,
+    persisted: &mut HashMap<String, Arc<T>>,
+) {
+    for name in dirty.drain() {
+        match current.get(&name) {
+            Some(value) => {
+                persisted.insert(name, value.clone());
+            }
+            None => {
+                persisted.remove(&name);
+            }
+        }
+    }
+}
+
+// What a registry keeps for its schemas (or groups) : the dirty names, the current and the
+// persisted values
+type Tracked<'a, T> = (
+    &'a mut HashSet<String>,
+    &'a mut HashMap<String, Arc<T>>,
+    &'a mut HashMap<String, Arc<T>>,
+);
+
+fn merge<T>(
+    name: &str,
+    (dirty, current, persisted): Tracked<'_, T>,
+    before: &HashMap<String, Arc<T>>,
+    flushed: &HashMap<String, Arc<T>>,
+    flushed_persisted: &HashMap<String, Arc<T>>,
+) {
+    match flushed_persisted.get(name) {
+        Some(value) => persisted.insert(name.to_string(), value.clone()),
+        None => persisted.remove(name),
+    };
+
+    let untouched = match (current.get(name), before.get(name)) {
+        (Some(now), Some(then)) => Arc::ptr_eq(now, then),
+        (None, None) => true,
+        _ => false,
+    };
+    if !untouched {
+        return;
+    }
+
+    // The flushed one, it may have gotten an id
+    if let Some(value) = flushed.get(name) {
+        current.insert(name.to_string(), value.clone());
+    }
+    dirty.remove(name);
+}
+
+fn event_name(event: &SchemaEvent) -> &str {
+    match event {
+        SchemaEvent::Added(name) | SchemaEvent::Changed(name) | SchemaEvent::Removed(name) => name,
+    }
+}
+
+fn merge_field(schema: &str, fields: &mut Vec<Field>, field: Field) -> Result<()> {
+    match fields.iter().find(|f| f.name() == field.name()) {
+        Some(existing) if existing == &field => Ok(()),
+        Some(_) => Err(Error::ConflictingField {
+            schema: schema.to_string(),
+            field: field.name().to_string(),
+        }),
+        None => {
+            fields.push(field);
+            Ok(())
+        }
+    }
+}
+
+// Note : The same parent can be inherited through several paths, its rules are kept once
+fn merge_rules(rules: &mut Vec<Rule>, inherited: Vec<Rule>) {
+    for rule in inherited {
+        if !rules.contains(&rule) {
+            rules.push(rule);
+        }
+    }
+}
+
 mod error {
     use std::fmt::Formatter;
 
     pub type Result<T> = core::result::Result<T, Error>;
 
     #[derive(Debug)]
+    #[allow(clippy::enum_variant_names)]
     pub enum Error {
         SchemaNotFound(String),
+        FieldGroupNotFound(String),
+
+        /// `field` is defined differently by the schemas/groups `schema` is built from
+        ConflictingField { schema: String, field: String },
+        /// The schemas extending each other, the first one repeated at the end
+        InheritanceCycle(Vec<String>),
+        /// Not one of `allow`, `warn` or `reject`, see [`crate::AdditionalProperties`]
+        InvalidAdditionalProperties(String),
+        /// The `pattern` constraint of `field` is not a valid regex
+        InvalidPattern { field: String, pattern: String },
 
-        LibModelError(lib_model::Error),
+        // Note : Boxed, `lib_model::Error` is large and most of the registry never touches the db
+        LibModelError(Box<lib_model::Error>),
     }
 
     impl From<lib_model::Error> for Error {
         fn from(value: lib_model::Error) -> Self {
-            Error::LibModelError(value)
+            Error::LibModelError(Box::new(value))
         }
     }
 
@@ -118,11 +733,211 @@ mod error {
 
     impl std::fmt::Display for Error {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-            todo!()
+            write!(f, "{:?}", self)
         }
     }
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BulkSchema {
+    #[serde(default)]
     schemas: Vec<Schema>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<FieldGroup>,
+}
+
+impl BulkSchema {
+    pub fn new(schemas: Vec<Schema>) -> Self {
+        BulkSchema {
+            schemas,
+            groups: Vec::new(),
+        }
+    }
+
+    pub fn with_groups(mut self, groups: Vec<FieldGroup>) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    pub fn schemas(&self) -> &[Schema] {
+        &self.schemas
+    }
+
+    pub fn groups(&self) -> &[FieldGroup] {
+        &self.groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::{PendingChange, Registry};
+    use crate::SchemaManager;
+    use crate::schema::{FieldGroup, Schema};
+    use lib_commons::{Field, FieldType, Value};
+    use lib_model::_dev_utils::get_dev_env;
+    use lib_model_schema::exec::get_schema;
+    use lib_model_schema::types::Schemas;
+    use lib_model_schema::types::params::SchemaGetPayload;
+    use serial_test::serial;
+    use std::sync::Arc;
+
+    fn schema(name: &str) -> Schema {
+        Schema::create(
+            name,
+            vec![Field::create("name", FieldType::String, true, Value::Null)],
+        )
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_registry_flush() {
+        let mm = get_dev_env().await.unwrap();
+        let mut registry = Registry::load_from_db(&mm).await.unwrap();
+
+        registry.register("Box", schema("Box"));
+        registry.register("Shelf", schema("Shelf"));
+        assert_eq!(
+            registry.pending_changes(),
+            vec![
+                PendingChange::New("Box".to_string()),
+                PendingChange::New("Shelf".to_string())
+            ]
+        );
+
+        registry.flush(&mm).await.unwrap();
+        assert!(!registry.has_pending_changes());
+
+        let box_id = registry.get_schema("Box").unwrap().id();
+        assert!(box_id.is_some());
+
+        let loaded = Registry::load_from_db(&mm).await.unwrap();
+        assert_eq!(loaded.get_schema("Box").unwrap().id(), box_id);
+        assert!(loaded.get_schema("Shelf").is_some());
+
+        // Edits can be thrown away
+        Arc::make_mut(registry.edit_schema("Box").unwrap()).push_field(Field::create(
+            "size",
+            FieldType::Number,
+            false,
+            Value::Null,
+        ));
+        registry.remove_schema("Shelf");
+        assert_eq!(
+            registry.pending_changes(),
+            vec![
+                PendingChange::Modified("Box".to_string()),
+                PendingChange::Deleted("Shelf".to_string())
+            ]
+        );
+
+        registry.discard_changes();
+        assert!(!registry.has_pending_changes());
+        assert!(registry.get_schema("Shelf").is_some());
+        assert!(registry.get_schema("Box").unwrap().get_field("size").is_none());
+
+        // Or written
+        Arc::make_mut(registry.edit_schema("Box").unwrap()).push_field(Field::create(
+            "size",
+            FieldType::Number,
+            false,
+            Value::Null,
+        ));
+        registry.remove_schema("Shelf");
+        registry.flush(&mm).await.unwrap();
+
+        let loaded = Registry::load_from_db(&mm).await.unwrap();
+        assert!(loaded.get_schema("Box").unwrap().get_field("size").is_some());
+        assert!(loaded.get_schema("Shelf").is_none());
+
+        // Groups are written along
+        let fields = vec![Field::create("width", FieldType::Number, false, Value::Null)];
+        registry.register_group("Sized", FieldGroup::create("Sized", fields));
+        assert_eq!(
+            registry.pending_group_changes(),
+            vec![PendingChange::New("Sized".to_string())]
+        );
+        registry.flush(&mm).await.unwrap();
+        assert!(!registry.has_pending_changes());
+        assert!(registry.get_group("Sized").unwrap().id().is_some());
+
+        let loaded = Registry::load_from_db(&mm).await.unwrap();
+        assert!(!loaded.has_pending_changes());
+        assert!(loaded.get_group("Sized").is_some());
+
+        registry.remove_group("Sized");
+        registry.flush(&mm).await.unwrap();
+        let loaded = Registry::load_from_db(&mm).await.unwrap();
+        assert!(loaded.get_group("Sized").is_none());
+
+        // A single failing write rolls back all of them
+        let mut missing = schema("Missing");
+        missing.set_id(9999);
+        registry.register("Missing", missing);
+        registry.register("Rack", schema("Rack"));
+
+        assert!(registry.flush(&mm).await.is_err());
+        assert_eq!(registry.pending_changes().len(), 2);
+
+        let loaded = Registry::load_from_db(&mm).await.unwrap();
+        assert!(loaded.get_schema("Rack").is_none());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_finalize_schema_registered_again() {
+        let mm = get_dev_env().await.unwrap();
+        let mut registry = Registry::load_from_db(&mm).await.unwrap();
+
+        registry.register("Crate", schema("Crate"));
+        let id = registry.finalize_schema(&mm, "Crate").await.unwrap();
+        assert!(id.is_some());
+
+        // No id of its own, the persisted one is updated
+        registry.register("Crate", schema("Crate"));
+        assert_eq!(registry.finalize_schema(&mm, "Crate").await.unwrap(), None);
+        assert_eq!(registry.get_schema("Crate").unwrap().id(), id);
+        assert!(!registry.has_pending_changes());
+
+        let rows = match get_schema(&mm, SchemaGetPayload::default()).await.unwrap() {
+            Schemas::Bulk(schemas) => schemas.iter().filter(|s| s.name() == "Crate").count(),
+            Schemas::Single(schema) => usize::from(schema.name() == "Crate"),
+        };
+        assert_eq!(rows, 1);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_manager_flush() {
+        let mm = get_dev_env().await.unwrap();
+        let manager = SchemaManager::new(&mm).await.unwrap();
+
+        manager.register("Tote", schema("Tote")).unwrap();
+        manager.register("Bin", schema("Bin")).unwrap();
+
+        // Edited while the db gets written
+        let mut bin = schema("Bin");
+        bin.push_field(Field::create("size", FieldType::Number, false, Value::Null));
+        manager
+            .registry
+            .persist(async |registry: &mut Registry| {
+                manager.register("Bin", bin).unwrap();
+                registry.flush(&mm).await
+            })
+            .await
+            .unwrap();
+
+        let registry = manager.registry();
+        assert!(registry.get_schema("Tote").unwrap().id().is_some());
+        assert_eq!(
+            registry.pending_changes(),
+            vec![PendingChange::Modified("Bin".to_string())]
+        );
+
+        manager.flush(&mm).await.unwrap();
+        assert!(!manager.registry().has_pending_changes());
+
+        let loaded = Registry::load_from_db(&mm).await.unwrap();
+        assert!(loaded.get_schema("Bin").unwrap().get_field("size").is_some());
+        assert!(loaded.get_schema("Tote").is_some());
+    }
 }
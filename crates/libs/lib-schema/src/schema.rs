@@ -1,12 +1,76 @@
 use serde::{Deserialize, Serialize};
-use lib_commons::{FieldType, Value};
+use lib_commons::{Constraints, FieldType, Migration, MigrationError, MigrationStep, Rule, Value, ValueStore};
 use crate::Field;
+use crate::registry::Error as RegistryError;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What to do with values that are not declared as a field of the schema.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdditionalProperties {
+    #[default]
+    Allow,
+    /// Valid, but listed in [`crate::ValidationReport::warnings`]
+    Warn,
+    Reject,
+}
+
+impl AdditionalProperties {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdditionalProperties::Allow => "allow",
+            AdditionalProperties::Warn => "warn",
+            AdditionalProperties::Reject => "reject",
+        }
+    }
+}
+
+impl TryFrom<&str> for AdditionalProperties {
+    type Error = RegistryError;
+
+    // Note : Anything unknown is an error, so a typo can't quietly make a schema lenient
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "allow" => Ok(AdditionalProperties::Allow),
+            "warn" => Ok(AdditionalProperties::Warn),
+            "reject" => Ok(AdditionalProperties::Reject),
+            _ => Err(RegistryError::InvalidAdditionalProperties(value.to_string())),
+        }
+    }
+}
+
+fn check_patterns(fields: &[Field]) -> Result<(), RegistryError> {
+    for field in fields {
+        field
+            .constraints()
+            .compile_pattern()
+            .map_err(|_| RegistryError::InvalidPattern {
+                field: field.name().to_string(),
+                pattern: field.constraints().get_pattern().unwrap_or_default().to_string(),
+            })?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Schema {
     id : Option<i64>,
     name: String,
     fields: Vec<Field>,
+    #[serde(default)]
+    additional_properties: AdditionalProperties,
+    /// Parent schemas whose fields are inherited, see [`crate::Registry::resolve`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    extends: Vec<String>,
+    /// [`FieldGroup`]s whose fields are included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<String>,
+    /// How to bring values from each previous version to the next one, see [`Schema::evolve`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    migrations: Vec<Migration>,
+    /// Checks between the fields, e.g. one required only when another is set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rules: Vec<Rule>,
 }
 
 impl Schema {
@@ -14,6 +78,11 @@ impl Schema {
         &self.fields
     }
 
+    /// Fails on the first field whose `pattern` constraint does not compile.
+    pub fn check_patterns(&self) -> Result<(), RegistryError> {
+        check_patterns(&self.fields)
+    }
+
     pub fn get_field(&self, name: &str) -> Option<&Field> {
         self.fields.iter().find(|field| field.name() == name)
     }
@@ -27,9 +96,75 @@ impl Schema {
             name: name.to_string(),
             fields,
             id : None,
+            additional_properties: AdditionalProperties::default(),
+            extends: Vec::new(),
+            groups: Vec::new(),
+            migrations: Vec::new(),
+            rules: Vec::new(),
         }
     }
 
+    pub fn with_parent(mut self, parent: &str) -> Self {
+        self.extends.push(parent.to_string());
+        self
+    }
+
+    pub fn with_group(mut self, group: &str) -> Self {
+        self.groups.push(group.to_string());
+        self
+    }
+
+    pub fn extends(&self) -> &[String] {
+        &self.extends
+    }
+
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+
+    pub fn set_extends(&mut self, extends: Vec<String>) {
+        self.extends = extends;
+    }
+
+    pub fn set_groups(&mut self, groups: Vec<String>) {
+        self.groups = groups;
+    }
+
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: Vec<Rule>) {
+        self.rules = rules;
+    }
+
+    /// The same schema with `fields` and `rules` as its own, see [`crate::Registry::resolve`]
+    pub(crate) fn flattened(&self, fields: Vec<Field>, rules: Vec<Rule>) -> Self {
+        Schema {
+            fields,
+            rules,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_additional_properties(mut self, additional_properties: AdditionalProperties) -> Self {
+        self.additional_properties = additional_properties;
+        self
+    }
+
+    pub fn additional_properties(&self) -> AdditionalProperties {
+        self.additional_properties
+    }
+
+    pub fn set_additional_properties(&mut self, additional_properties: AdditionalProperties) {
+        self.additional_properties = additional_properties;
+    }
+
     pub fn push_field(&mut self, field: Field) {
         self.fields.push(field);
     }
@@ -58,6 +193,11 @@ impl Schema {
         Some(())
     }
 
+    pub fn set_constraints_field(&mut self, index: usize, constraints : Constraints) -> Option<()> {
+        self.fields.get_mut(index)?.set_constraints(constraints);
+        Some(())
+    }
+
     pub fn set_id(&mut self, id : i64) {
         self.id = Some(id);
     }
@@ -66,20 +206,127 @@ impl Schema {
         self.id
     }
 
+    /// Starts at 1, each [`Schema::evolve`] adds one.
+    pub fn version(&self) -> u32 {
+        self.migrations.last().map_or(1, |m| m.version())
+    }
+
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// Change the fields through `steps` as a new version, values written against the previous
+    /// ones are brought up to date by [`Schema::migrate`]. Gives back the new version.
+    ///
+    /// Nothing is changed if one of the steps can't be applied, e.g. renaming a missing field.
+    pub fn evolve(&mut self, steps: Vec<MigrationStep>) -> Result<u32, MigrationError> {
+        let migration = Migration::new(self.version() + 1, steps);
+        migration.apply_to_fields(&mut self.fields)?;
+
+        let version = migration.version();
+        self.migrations.push(migration);
+
+        Ok(version)
+    }
+
+    /// Apply the migrations `value` is missing, values without a version are taken as
+    /// written against the first one. Gives back whether anything was applied.
+    ///
+    /// Note : Only the values themselves, the nested objects don't carry a version
+    pub fn migrate(&self, value: &mut ValueStore) -> Result<bool, MigrationError> {
+        let version = value.schema_version().unwrap_or(1);
+        if version > self.version() {
+            return Err(MigrationError::UnknownVersion {
+                schema: self.name(),
+                version,
+            });
+        }
+
+        let pending = self.migrations.iter().filter(|m| m.version() > version);
+        let mut applied = false;
+        for migration in pending {
+            migration.apply(value)?;
+            applied = true;
+        }
+
+        Ok(applied)
+    }
 }
 
-impl From<lib_model_schema::types::Schema> for Schema {
-    fn from(value: lib_model_schema::types::Schema) -> Self {
-        Schema {
+impl TryFrom<lib_model_schema::types::Schema> for Schema {
+    type Error = RegistryError;
+
+    fn try_from(value: lib_model_schema::types::Schema) -> Result<Self, Self::Error> {
+        Ok(Schema {
             id : Some(value.id()),
             name : value.name().to_string(),
             fields : value.fields().as_ref().to_vec(),
+            additional_properties: value.additional_properties().try_into()?,
+            extends: value.extends().to_vec(),
+            groups: value.groups().to_vec(),
+            migrations: value.migrations().to_vec(),
+            rules: value.rules().to_vec(),
+        })
+    }
+}
+
+impl TryFrom<&lib_model_schema::types::Schema> for Schema {
+    type Error = RegistryError;
+
+    fn try_from(value: &lib_model_schema::types::Schema) -> Result<Self, Self::Error> {
+        value.clone().try_into()
+    }
+}
+
+/// A reusable set of fields, included by schemas through [`Schema::with_group`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldGroup {
+    id : Option<i64>,
+    name: String,
+    fields: Vec<Field>,
+}
+
+impl FieldGroup {
+    pub fn create(name: &str, fields: Vec<Field>) -> Self {
+        FieldGroup {
+            id: None,
+            name: name.to_string(),
+            fields,
         }
     }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn fields(&self) -> &Vec<Field> {
+        &self.fields
+    }
+
+    /// See [`Schema::check_patterns`].
+    pub fn check_patterns(&self) -> Result<(), RegistryError> {
+        check_patterns(&self.fields)
+    }
+
+    pub fn push_field(&mut self, field: Field) {
+        self.fields.push(field);
+    }
+
+    pub fn set_id(&mut self, id : i64) {
+        self.id = Some(id);
+    }
+
+    pub fn id(&self) -> Option<i64> {
+        self.id
+    }
 }
 
-impl From<&lib_model_schema::types::Schema> for Schema {
-    fn from(value: &lib_model_schema::types::Schema) -> Self {
-        value.clone().into()
+impl From<lib_model_schema::types::FieldGroup> for FieldGroup {
+    fn from(value: lib_model_schema::types::FieldGroup) -> Self {
+        FieldGroup {
+            id: Some(value.id()),
+            name: value.name().to_string(),
+            fields: value.fields().as_ref().to_vec(),
+        }
     }
 }
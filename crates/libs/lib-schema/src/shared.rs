@@ -0,0 +1,86 @@
+use std::sync::{Arc, Mutex};
+
+use arc_swap::ArcSwap;
+use tokio::sync::broadcast;
+
+use crate::registry::{self, Registry, SchemaEvent};
+use crate::{Error, Result};
+
+// How many events a subscriber can fall behind before missing some
+const EVENTS_CAPACITY: usize = 256;
+
+/// The registry shared by a [`crate::SchemaManager`] and its watchers.
+///
+/// Readers get the current snapshot without ever waiting, writers edit a copy of it that
+/// replaces it once done, one writer at a time.
+pub(crate) struct SharedRegistry {
+    current: ArcSwap<Registry>,
+    writer: Mutex<()>,
+    // Note : A tokio one, it is held while the schema db gets written
+    persister: tokio::sync::Mutex<()>,
+    events: broadcast::Sender<SchemaEvent>,
+}
+
+impl SharedRegistry {
+    pub fn new(registry: Registry) -> Self {
+        let (events, _) = broadcast::channel(EVENTS_CAPACITY);
+
+        SharedRegistry {
+            current: ArcSwap::from_pointee(registry),
+            writer: Mutex::new(()),
+            persister: tokio::sync::Mutex::new(()),
+            events,
+        }
+    }
+
+    pub fn snapshot(&self) -> Arc<Registry> {
+        self.current.load_full()
+    }
+
+    /// Apply `edit` to a copy of the current registry then swap it in, sending an event for
+    /// every schema that changed.
+    ///
+    /// Note : The copy is swapped in whatever `edit` returns, errors included, same as when
+    ///        editing the registry in place.
+    pub fn edit<T>(&self, edit: impl FnOnce(&mut Registry) -> T) -> Result<T> {
+        let _writer = self.writer.lock().map_err(|_| Error::LockError)?;
+
+        let previous = self.current.load_full();
+        let mut registry = Registry::clone(&previous);
+        let result = edit(&mut registry);
+
+        let events = registry.changes_since(&previous);
+        self.current.store(Arc::new(registry));
+
+        for event in events {
+            // Note : Only fails when nobody is subscribed
+            let _ = self.events.send(event);
+        }
+
+        Ok(result)
+    }
+
+    /// Run `write` (a flush or a finalize) on a copy of the current registry then bring what it
+    /// persisted into the registry as it is by then, one at a time.
+    ///
+    /// Note : The other writers don't wait on the db, what they edit meanwhile is kept and stays
+    ///        pending. Nothing changes if `write` fails.
+    pub async fn persist<T>(
+        &self,
+        write: impl AsyncFnOnce(&mut Registry) -> std::result::Result<T, registry::Error>,
+    ) -> Result<T> {
+        let _persister = self.persister.lock().await;
+
+        let before = self.snapshot();
+        let mut flushed = Registry::clone(&before);
+        let result = write(&mut flushed).await?;
+
+        self.edit(|registry| registry.merge_persisted(&before, &flushed))?;
+
+        Ok(result)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SchemaEvent> {
+        self.events.subscribe()
+    }
+}
@@ -1,119 +1,301 @@
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-
-use crate::{Registry, Schema, ValidationDescriptor};
-
-pub(crate) use error::{Result, ValidatorError};
-
-use lib_commons::{ValueStore, ValueStoreError};
-
+use std::collections::BTreeMap;
 
+use crate::compiled::CompiledSchema;
+use crate::{AdditionalProperties, ValidationDescriptor, normalizer};
 
+use lib_commons::{
+    FieldType, Path, Reference, Rule, ValidationReport, Value, ValueStore, Violation,
+    ViolationKind,
+};
 
 pub(crate) struct Validator<'a> {
-    validation_descriptor: ValidationDescriptor<'a>
+    validation_descriptor: ValidationDescriptor<'a>,
 }
 
 impl<'a> Validator<'a> {
-    pub fn new(validation_descriptor : ValidationDescriptor<'a> ) -> Self {
-        Self { validation_descriptor }
+    pub fn new(validation_descriptor: ValidationDescriptor<'a>) -> Self {
+        Self {
+            validation_descriptor,
+        }
     }
 
-    fn validate_fields(&self, schema: Arc<Schema>, value: &ValueStore) -> Result<()> {
-        let object_properties_schema = value.object_properties_schemas();
-        for field in schema.fields() {
+    fn validate_fields(
+        &self,
+        schema: &CompiledSchema,
+        values: &BTreeMap<String, Value>,
+        path: &Path,
+        report: &mut ValidationReport,
+    ) {
+        for (index, field) in schema.fields().iter().enumerate() {
             let field_name = field.name();
-            let field_value = value.get(field_name).ok_or_else(|| {
-                ValidatorError::MissingField(format!(
-                    "Field '{}' is missing in the value store",
-                    field_name
-                ))
-            })?;
-
-            // println!(
-            //     "Validating for field : {} with the value of {}",
-            //     field_name,
-            //     field_value.to_value_string()
-            // );
-
-            if field.required() && field_value.is_null() {
-                return Err(ValidatorError::RequiredFieldMissing(field_name.to_string()));
+            // Note : Only built for the fields that have something wrong with them
+            let field_path = || path.clone().key(field_name);
+
+            let error = match schema.expression(index) {
+                Some(Ok(expression)) => normalizer::compute(field, expression, values).err(),
+                Some(Err(error)) => Some(error.clone()),
+                None => None,
+            };
+            if let Some(error) = error {
+                report.push(Violation::new(
+                    field_path(),
+                    ViolationKind::ExpressionError {
+                        expression: field.expression().unwrap_or_default().to_string(),
+                        error: error.to_string(),
+                    },
+                ));
             }
 
-            if !field_value.is_null() && !field.field_type().is_valid(field_value) {
-                return Err(ValidatorError::InvalidType(format!(
-                    "Field '{}' has invalid type: expected {:?}, got {:?}",
-                    field_name,
-                    field.field_type(),
-                    field_value
-                )));
+            let Some(field_value) = values.get(field_name) else {
+                if field.required() {
+                    report.push(
+                        Violation::new(field_path(), ViolationKind::MissingField)
+                            .expected(field.field_type()),
+                    );
+                }
+                continue;
+            };
+
+            if field_value.is_null() {
+                if field.required() {
+                    report.push(
+                        Violation::new(field_path(), ViolationKind::RequiredFieldMissing)
+                            .expected(field.field_type())
+                            .actual(field_value),
+                    );
+                }
+                continue;
+            }
+
+            if !field.field_type().is_valid(field_value) {
+                let kind = if field.dimension().is_some() && field_value.as_measurement().is_some()
+                {
+                    ViolationKind::IncompatibleUnit
+                } else if field.field_type().is_string_based() && field_value.as_string().is_some()
+                {
+                    ViolationKind::InvalidFormat
+                } else {
+                    ViolationKind::InvalidType
+                };
+
+                report.push(
+                    Violation::new(field_path(), kind)
+                        .expected(field.field_type())
+                        .actual(field_value),
+                );
+                continue;
+            }
+
+            let pattern = schema.pattern(index);
+            if let Err(violation) = field.constraints().check_with(field_value, pattern) {
+                report.push(
+                    Violation::new(field_path(), ViolationKind::ConstraintViolation(violation))
+                        .expected(field.field_type())
+                        .actual(field_value),
+                );
             }
         }
-        Ok(())
     }
 
-    fn validate_inner_object(&self, value: &ValueStore) -> Result<()> {
-        let object_properties_schema = value.object_properties_schemas();
+    fn validate_rules(
+        &self,
+        schema: &CompiledSchema,
+        values: &BTreeMap<String, Value>,
+        path: &Path,
+        report: &mut ValidationReport,
+    ) {
+        let is_set = |field: &String| values.get(field).is_some_and(|value| !value.is_null());
 
-        for (field_name, schema) in self.validation_descriptor.properties.as_ref() {
-            let Some(value) = value.get(field_name) else {
-                continue;
-            };
+        for (index, rule) in schema.rules().iter().enumerate() {
+            match rule {
+                Rule::RequiredIf { when, then } => {
+                    let condition = match schema.condition(index) {
+                        Some(Ok(condition)) => condition.evaluate(values),
+                        Some(Err(error)) => Err(error.clone()),
+                        None => continue,
+                    };
 
+                    match condition {
+                        Ok(Value::Boolean(true)) => {}
+                        // Note : A condition reading absent fields is null, so does not hold
+                        Ok(_) => continue,
+                        Err(error) => {
+                            report.push(Violation::new(
+                                path.clone(),
+                                ViolationKind::ExpressionError {
+                                    expression: when.clone(),
+                                    error: error.to_string(),
+                                },
+                            ));
+                            continue;
+                        }
+                    }
 
-            let temp = ValueStore::builder()
-                .with_schema(schema.name())
-                .with_object_properties_schemas(object_properties_schema.as_ref().clone())
-                .with_value(value)?
-                .build();
-            
+                    for field in then.iter().filter(|field| !is_set(field)) {
+                        let mut violation = Violation::new(
+                            path.clone().key(field),
+                            ViolationKind::RuleViolation(rule.clone()),
+                        );
+                        if let Some(field) = schema.get_field(field) {
+                            violation = violation.expected(field.field_type());
+                        }
+                        report.push(violation);
+                    }
+                }
+                Rule::MutuallyExclusive { fields } => {
+                    if fields.iter().filter(|field| is_set(field)).count() > 1 {
+                        report.push(Violation::new(
+                            path.clone(),
+                            ViolationKind::RuleViolation(rule.clone()),
+                        ));
+                    }
+                }
+                Rule::AtLeastOneOf { fields } => {
+                    if !fields.iter().any(is_set) {
+                        report.push(Violation::new(
+                            path.clone(),
+                            ViolationKind::RuleViolation(rule.clone()),
+                        ));
+                    }
+                }
+            }
+        }
+    }
 
-            self._validate(schema.clone(), &temp)?;
+    fn validate_additional_properties(
+        &self,
+        schema: &CompiledSchema,
+        values: &BTreeMap<String, Value>,
+        path: &Path,
+        report: &mut ValidationReport,
+    ) {
+        let policy = schema.schema().additional_properties();
+        if policy == AdditionalProperties::Allow {
+            return;
         }
 
-        Ok(())
-    }
+        for (key, value) in values {
+            if schema.get_field(key).is_some() {
+                continue;
+            }
 
-    fn _validate(&self, schema: Arc<Schema>, value: &ValueStore) -> Result<()> {
-        self.validate_fields(schema, value)?;
-        self.validate_inner_object(value)?;
+            let violation =
+                Violation::new(path.clone().key(key), ViolationKind::AdditionalProperty)
+                    .actual(value);
 
-        Ok(())
+            match policy {
+                AdditionalProperties::Reject => report.push(violation),
+                _ => report.warn(violation),
+            }
+        }
     }
 
-    #[inline]
-    pub fn validate(&self, value: &ValueStore) -> Result<()> {
-        self._validate(self.validation_descriptor.main(),value)
-    }
-}
+    fn validate_inner_object(
+        &self,
+        values: &BTreeMap<String, Value>,
+        path: &Path,
+        report: &mut ValidationReport,
+    ) {
+        for (field_name, schema) in &self.validation_descriptor.properties {
+            let Some(value) = values.get(*field_name) else {
+                continue;
+            };
 
-mod error {
-    use std::fmt;
-    pub type Result<T> = std::result::Result<T, ValidatorError>;
+            let field_path = path.clone().key(*field_name);
 
-    #[derive(Debug)]
-    pub enum ValidatorError {
-        MissingField(String),
-        InvalidType(String),
-        RequiredFieldMissing(String),
-        SchemaNotFound(String),
-        SchemaIdentifierMissing,
+            match value {
+                Value::Object(inner) => self._validate(schema, inner, &field_path, report),
+                // Every element of an array of objects is validated against the same schema
+                Value::Array(items) => {
+                    for (index, item) in items.iter().enumerate() {
+                        let item_path = field_path.clone().index(index);
 
-        // -- ValueStoreError --
-        ValueStoreError(lib_commons::ValueStoreError),
+                        match item.as_object() {
+                            Some(inner) => self._validate(schema, inner, &item_path, report),
+                            None => report.push(
+                                Violation::new(item_path, ViolationKind::InvalidType)
+                                    .expected(&FieldType::Object)
+                                    .actual(item),
+                            ),
+                        }
+                    }
+                }
+                // Already reported by `validate_fields` if the field is in the schema
+                _ => {}
+            }
+        }
     }
 
-    impl From<lib_commons::ValueStoreError> for ValidatorError {
-        fn from(err: lib_commons::ValueStoreError) -> Self {
-            ValidatorError::ValueStoreError(err)
+    fn collect_references(
+        &self,
+        schema: &CompiledSchema,
+        values: &BTreeMap<String, Value>,
+        path: &Path,
+        references: &mut Vec<Reference>,
+    ) {
+        for field in schema.fields() {
+            let FieldType::Reference(target) = field.field_type() else {
+                continue;
+            };
+
+            // Note : Anything but an id is already reported by `validate_fields`
+            let id = values.get(field.name()).and_then(Value::as_number).and_then(|n| n.as_i64());
+            if let Some(id) = id {
+                references.push(Reference::new(path.clone().key(field.name()), *target, id));
+            }
         }
-    }
 
-    impl fmt::Display for ValidatorError {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            write!(f, "{:?}", self)
+        for (field_name, schema) in &self.validation_descriptor.properties {
+            let field_path = path.clone().key(*field_name);
+
+            match values.get(*field_name) {
+                Some(Value::Object(inner)) => {
+                    self.collect_references(schema, inner, &field_path, references)
+                }
+                Some(Value::Array(items)) => {
+                    for (index, item) in items.iter().enumerate() {
+                        if let Some(inner) = item.as_object() {
+                            let item_path = field_path.clone().index(index);
+                            self.collect_references(schema, inner, &item_path, references);
+                        }
+                    }
+                }
+                _ => {}
+            }
         }
     }
 
-    impl std::error::Error for ValidatorError {}
+    fn _validate(
+        &self,
+        schema: &CompiledSchema,
+        values: &BTreeMap<String, Value>,
+        path: &Path,
+        report: &mut ValidationReport,
+    ) {
+        self.validate_fields(schema, values, path, report);
+        self.validate_rules(schema, values, path, report);
+        self.validate_additional_properties(schema, values, path, report);
+        self.validate_inner_object(values, path, report);
+    }
+
+    pub fn references(&self, value: &ValueStore) -> Vec<Reference> {
+        let mut references = Vec::new();
+        self.collect_references(
+            &self.validation_descriptor.main,
+            value.get_all(),
+            &Path::root(),
+            &mut references,
+        );
+        references
+    }
+
+    #[inline]
+    pub fn validate(&self, value: &ValueStore, report: &mut ValidationReport) {
+        self._validate(
+            &self.validation_descriptor.main,
+            value.get_all(),
+            &Path::root(),
+            report,
+        )
+    }
 }
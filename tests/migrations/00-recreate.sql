@@ -4,5 +4,6 @@ DROP TABLE IF EXISTS items;
 DROP TABLE IF EXISTS image;
 DROP TABLE IF EXISTS location_metadata;
 DROP TABLE IF EXISTS location_data;
+DROP TABLE IF EXISTS location_types;
 
 PRAGMA foreign_keys = ON;
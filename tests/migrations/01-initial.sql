@@ -1,8 +1,15 @@
+CREATE TABLE IF NOT EXISTS location_types
+(
+    name           TEXT PRIMARY KEY NOT NULL UNIQUE,
+    default_schema TEXT
+);
+
 CREATE TABLE IF NOT EXISTS location_metadata
 (
-    id       INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL UNIQUE,
-    name     TEXT                              NOT NULL,
-    metadata TEXT
+    id            INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL UNIQUE,
+    name          TEXT                              NOT NULL,
+    metadata      TEXT,
+    location_type TEXT REFERENCES location_types (name) ON DELETE SET NULL ON UPDATE CASCADE
 );
 
 CREATE TABLE IF NOT EXISTS location_data
@@ -1,6 +1,8 @@
-INSERT INTO location_metadata (id, name, metadata)
-VALUES (1, 'Container 1', '{"schema_name": "TestSchema","object_properties_schemas": {},"values": {"racks": [{"rack_name": "Rack A","shelves": ["Shelf α","Shelf β","Shelf γ"]},{"rack_name": "Rack B","shelves": ["Shelf δ","Shelf ε","Shelf ζ"]},{"rack_name": "Rack C","shelves": ["Shelf θ","Shelf ι"]}]}}');
-INSERT INTO location_metadata (id, name) VALUES (2, 'Hall 1');
+INSERT INTO location_types (name, default_schema) VALUES ('container', 'TestSchema');
+INSERT INTO location_types (name) VALUES ('hall');
+INSERT INTO location_metadata (id, name, location_type, metadata)
+VALUES (1, 'Container 1', 'container', '{"schema_name": "TestSchema","object_properties_schemas": {},"values": {"racks": [{"rack_name": "Rack A","shelves": ["Shelf α","Shelf β","Shelf γ"]},{"rack_name": "Rack B","shelves": ["Shelf δ","Shelf ε","Shelf ζ"]},{"rack_name": "Rack C","shelves": ["Shelf θ","Shelf ι"]}]}}');
+INSERT INTO location_metadata (id, name, location_type) VALUES (2, 'Hall 1', 'hall');
 INSERT INTO location_data (id, location, rack, bin)
 VALUES (1, 1, 'Rack 1', 'Bin 1');
 INSERT INTO location_data (id, location) VALUES (2, 2);
@@ -1,4 +1,5 @@
 PRAGMA foreign_keys = OFF;
 DROP TABLE IF EXISTS schema;
+DROP TABLE IF EXISTS field_group;
 
 PRAGMA foreign_keys = ON;
@@ -2,7 +2,19 @@ CREATE TABLE IF NOT EXISTS schema
 (
     id     INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL UNIQUE,
     name   TEXT                              NOT NULL,
-    fields TEXT                              NOT NULL 
+    fields TEXT                              NOT NULL,
+    additional_properties TEXT               NOT NULL DEFAULT 'allow',
+    extends TEXT                             NOT NULL DEFAULT '[]',
+    groups TEXT                              NOT NULL DEFAULT '[]',
+    migrations TEXT                          NOT NULL DEFAULT '[]',
+    rules TEXT                               NOT NULL DEFAULT '[]'
+);
+
+CREATE TABLE IF NOT EXISTS field_group
+(
+    id     INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL UNIQUE,
+    name   TEXT                              NOT NULL UNIQUE,
+    fields TEXT                              NOT NULL
 );
 
 